@@ -1,3 +1,4 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
 use std::io::{BufRead, Read};
 
@@ -11,7 +12,7 @@ mod day07;
 mod day08;
 mod day09;
 
-pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<String> {
+pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<Answer> {
     match day {
         1 => day01::get_result(data, level),
         2 => day02::get_result(data, level),
@@ -22,6 +23,6 @@ pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<St
         7 => day07::get_result(data, level),
         8 => day08::get_result(data, level),
         9 => day09::get_result(data, level),
-        _ => Err(Error::new(format!("Day {} not implemented", day))),
+        _ => Err(Error::Unimplemented(format!("Day {} not implemented", day))),
     }
 }
@@ -1,11 +1,15 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -1,18 +1,23 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::parsing::number;
 use nom::{
-    branch::alt, bytes::complete::tag, bytes::complete::take_while1, character::complete::char,
-    IResult,
+    branch::alt, bytes::complete::tag, character::complete::char, combinator::value, IResult,
 };
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
+#[derive(Clone, Copy)]
 enum Action {
     TurnOn,
     TurnOff,
@@ -26,46 +31,37 @@ struct Instruction {
 }
 
 fn parse_action(i: &str) -> IResult<&str, Action> {
-    let (i, a) = alt((tag("turn on"), tag("turn off"), tag("toggle")))(i)?;
-    Ok((
-        i,
-        match a {
-            "turn on" => Action::TurnOn,
-            "turn off" => Action::TurnOff,
-            "toggle" => Action::Toggle,
-            _ => panic!("foo"),
-        },
-    ))
+    alt((
+        value(Action::TurnOn, tag("turn on")),
+        value(Action::TurnOff, tag("turn off")),
+        value(Action::Toggle, tag("toggle")),
+    ))(i)
 }
 
 fn parse_instruction(i: &str) -> IResult<&str, Instruction> {
     let (i, a) = parse_action(i)?;
     let (i, _) = char(' ')(i)?;
-    let (i, x1) = take_while1(|c: char| c.is_digit(10))(i)?;
+    let (i, x1) = number(i)?;
     let (i, _) = char(',')(i)?;
-    let (i, y1) = take_while1(|c: char| c.is_digit(10))(i)?;
+    let (i, y1) = number(i)?;
     let (i, _) = tag(" through ")(i)?;
-    let (i, x2) = take_while1(|c: char| c.is_digit(10))(i)?;
+    let (i, x2) = number(i)?;
     let (i, _) = char(',')(i)?;
-    let (i, y2) = take_while1(|c: char| c.is_digit(10))(i)?;
+    let (i, y2) = number(i)?;
 
     Ok((
         i,
         Instruction {
             action: a,
-            corner1: (x1.parse().unwrap(), y1.parse().unwrap()),
-            corner2: (x2.parse().unwrap(), y2.parse().unwrap()),
+            corner1: (x1, y1),
+            corner2: (x2, y2),
         },
     ))
 }
 
 fn level_1(lines: &[String]) -> ACResult<u32> {
     let mut grid = [[false; 1000]; 1000];
-    for i in lines
-        .iter()
-        .map(|l| -> ACResult<Instruction> { Ok(parse_instruction(l)?.1) })
-    {
-        let i = i?;
+    for i in crate::parsing::parse_lines(lines, parse_instruction)? {
         grid.iter_mut()
             .skip(i.corner1.0)
             .take(i.corner2.0 - i.corner1.0 + 1)
@@ -92,11 +88,7 @@ fn level_1(lines: &[String]) -> ACResult<u32> {
 
 fn level_2(lines: &[String]) -> ACResult<u32> {
     let mut grid = [[0; 1000]; 1000];
-    for i in lines
-        .iter()
-        .map(|l| -> ACResult<Instruction> { Ok(parse_instruction(l)?.1) })
-    {
-        let i = i?;
+    for i in crate::parsing::parse_lines(lines, parse_instruction)? {
         grid.iter_mut()
             .skip(i.corner1.0)
             .take(i.corner2.0 - i.corner1.0 + 1)
@@ -1,13 +1,18 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::parsing::number;
 use nom::{branch::alt, bytes::complete::tag, bytes::complete::take_while1, IResult};
 use std::collections::HashMap;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -26,8 +31,8 @@ enum Instruction {
 }
 
 fn parse_input_value(i: &str) -> IResult<&str, Input> {
-    let (i, v) = take_while1(|c: char| c.is_digit(10))(i)?;
-    Ok((i, Input::Value(v.parse().unwrap())))
+    let (i, v) = number(i)?;
+    Ok((i, Input::Value(v)))
 }
 
 fn parse_input_wire(i: &str) -> IResult<&str, Input> {
@@ -67,25 +72,19 @@ fn parse_instruction_or(i: &str) -> IResult<&str, Instruction> {
 fn parse_instruction_lshift(i: &str) -> IResult<&str, Instruction> {
     let (i, i1) = parse_input(i)?;
     let (i, _) = tag(" LSHIFT ")(i)?;
-    let (i, shift) = take_while1(|c: char| c.is_digit(10))(i)?;
+    let (i, shift) = number(i)?;
     let (i, _) = tag(" -> ")(i)?;
     let (i, o) = take_while1(|c: char| c.is_alphabetic())(i)?;
-    Ok((
-        i,
-        Instruction::LShift(i1, shift.parse().unwrap(), o.to_owned()),
-    ))
+    Ok((i, Instruction::LShift(i1, shift, o.to_owned())))
 }
 
 fn parse_instruction_rshift(i: &str) -> IResult<&str, Instruction> {
     let (i, i1) = parse_input(i)?;
     let (i, _) = tag(" RSHIFT ")(i)?;
-    let (i, shift) = take_while1(|c: char| c.is_digit(10))(i)?;
+    let (i, shift) = number(i)?;
     let (i, _) = tag(" -> ")(i)?;
     let (i, o) = take_while1(|c: char| c.is_alphabetic())(i)?;
-    Ok((
-        i,
-        Instruction::RShift(i1, shift.parse().unwrap(), o.to_owned()),
-    ))
+    Ok((i, Instruction::RShift(i1, shift, o.to_owned())))
 }
 
 fn parse_instruction_not(i: &str) -> IResult<&str, Instruction> {
@@ -200,20 +199,14 @@ fn compute(
 }
 
 fn level_1(lines: &[String]) -> ACResult<u16> {
-    let instructions = lines
-        .iter()
-        .map(|l| -> ACResult<Instruction> { Ok(parse_instruction(l)?.1) })
-        .collect::<ACResult<Vec<_>>>()?;
+    let instructions = crate::parsing::parse_lines(lines, parse_instruction)?;
     let mut cables = HashMap::<String, u16>::new();
     compute(&mut cables, &instructions, None);
     Ok(*cables.get("a").unwrap())
 }
 
 fn level_2(lines: &[String]) -> ACResult<u16> {
-    let instructions = lines
-        .iter()
-        .map(|l| -> ACResult<Instruction> { Ok(parse_instruction(l)?.1) })
-        .collect::<ACResult<Vec<_>>>()?;
+    let instructions = crate::parsing::parse_lines(lines, parse_instruction)?;
     let mut cables = HashMap::<String, u16>::new();
     compute(&mut cables, &instructions, None);
     let a = *cables.get("a").unwrap();
@@ -1,51 +1,56 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use nom::branch::alt;
+use nom::bytes::complete::take_while_m_n;
+use nom::character::complete::{char, none_of};
+use nom::combinator::map_res;
+use nom::multi::fold_many0;
+use nom::sequence::{delimited, preceded};
+use nom::IResult;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
-fn unescape(input: &str) -> ACResult<String> {
-    let mut result = String::with_capacity(input.len());
-    let chars = input.chars().collect::<Vec<_>>();
-    if chars.len() < 2 || chars[0] != '"' || chars[chars.len() - 1] != '"' {
-        return Err(Error::new_str("Invalid escaped string"));
-    }
-    let mut i = 1;
-    while i < chars.len() - 1 {
-        if chars[i] == '\\' {
-            i += 1;
-            match chars[i] {
-                '\\' => result.push('\\'),
-                '"' => result.push('"'),
-                'x' => {
-                    let hex_string = format!("{}{}", chars[i + 1], chars[i + 2]);
-                    i += 2;
-                    let c = char::from(u8::from_str_radix(&hex_string, 16).unwrap());
-                    result.push(c);
-                }
-                _ => return Err(Error::new_str("invalid escape sequence")),
-            }
-        } else {
-            result.push(chars[i]);
-        }
-        i += 1;
-    }
-    Ok(result)
+fn escaped_char(input: &str) -> IResult<&str, char> {
+    let hex = map_res(take_while_m_n(2, 2, |c: char| c.is_ascii_hexdigit()), |h| {
+        u8::from_str_radix(h, 16).map(char::from)
+    });
+    alt((
+        preceded(
+            char('\\'),
+            alt((char('\\'), char('"'), preceded(char('x'), hex))),
+        ),
+        none_of("\\\""),
+    ))(input)
+}
+
+fn unescape(input: &str) -> IResult<&str, String> {
+    let content = fold_many0(escaped_char, String::new(), |mut result, c| {
+        result.push(c);
+        result
+    });
+    delimited(char('"'), content, char('"'))(input)
 }
 
 fn level_1(lines: &[String]) -> ACResult<usize> {
+    let unescaped = crate::parsing::parse_lines(lines, unescape)?;
     Ok(lines
         .iter()
-        .map(|l| l.chars().count() - unescape(l).unwrap().chars().count())
+        .zip(unescaped.iter())
+        .map(|(l, u)| l.chars().count() - u.chars().count())
         .sum())
 }
 
-fn escape(input: &str) -> ACResult<String> {
+fn escape(input: &str) -> String {
     let mut result = String::with_capacity(2 * input.len());
     result.push('"');
     for c in input.chars() {
@@ -56,13 +61,13 @@ fn escape(input: &str) -> ACResult<String> {
         }
     }
     result.push('"');
-    Ok(result)
+    result
 }
 
 fn level_2(lines: &[String]) -> ACResult<usize> {
     Ok(lines
         .iter()
-        .map(|l| escape(l).unwrap().chars().count() - l.chars().count())
+        .map(|l| escape(l).chars().count() - l.chars().count())
         .sum())
 }
 
@@ -1,4 +1,6 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::parsing::number;
 use itertools::Itertools;
 use nom::{
     bytes::complete::{tag, take_while1},
@@ -7,11 +9,14 @@ use nom::{
 use std::collections::HashSet;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -27,14 +32,14 @@ fn parse_link(i: &str) -> IResult<&str, Link> {
     let (i, _) = tag(" to ")(i)?;
     let (i, to) = take_while1(|c: char| c.is_alphabetic())(i)?;
     let (i, _) = tag(" = ")(i)?;
-    let (i, distance) = take_while1(|c: char| c.is_digit(10))(i)?;
+    let (i, distance) = number(i)?;
 
     Ok((
         i,
         Link {
             from: from.to_string(),
             to: to.to_string(),
-            distance: distance.parse().unwrap(),
+            distance,
         },
     ))
 }
@@ -70,10 +75,7 @@ fn step(
 }
 
 fn level_1(lines: &[String]) -> ACResult<usize> {
-    let links = lines
-        .iter()
-        .map(|l| parse_link(l).map(|l| l.1).map_err(Error::from))
-        .collect::<ACResult<Vec<_>>>()?;
+    let links = crate::parsing::parse_lines(lines, parse_link)?;
     let links = links
         .iter()
         .cloned()
@@ -103,10 +105,7 @@ fn level_1(lines: &[String]) -> ACResult<usize> {
 }
 
 fn level_2(lines: &[String]) -> ACResult<usize> {
-    let links = lines
-        .iter()
-        .map(|l| parse_link(l).map(|l| l.1).map_err(Error::from))
-        .collect::<ACResult<Vec<_>>>()?;
+    let links = crate::parsing::parse_lines(lines, parse_link)?;
     let links = links
         .iter()
         .cloned()
@@ -1,3 +1,4 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
 use std::io::{BufRead, Read};
 
@@ -5,11 +6,11 @@ mod day01;
 mod day02;
 mod day25;
 
-pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<String> {
+pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<Answer> {
     match day {
         1 => day01::get_result(data, level),
         2 => day02::get_result(data, level),
         25 => day25::get_result(data, level),
-        _ => Err(Error::new(format!("Day {} not implemented", day))),
+        _ => Err(Error::Unimplemented(format!("Day {} not implemented", day))),
     }
 }
@@ -1,24 +1,20 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_line(data)?),
-        2 => level_2(&crate::utils::read_line(data)?),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_line(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
-    .map(|r| r.to_string())
 }
 
 fn level_1(line: &str) -> ACResult<u32> {
-    let numbers = line
-        .chars()
-        .map(|c| {
-            c.to_string()
-                .parse()
-                .map_err(|_| Error::new_str("Failed to parse char to number"))
-        })
-        .collect::<ACResult<Vec<u8>>>()?;
+    let numbers = crate::parsing::parse_all(line, crate::parsing::digits)?;
 
     let mut sum: u32 = 0;
     for (i, digit) in numbers.iter().enumerate() {
@@ -33,14 +29,7 @@ fn level_1(line: &str) -> ACResult<u32> {
 }
 
 fn level_2(line: &str) -> ACResult<u32> {
-    let numbers = line
-        .chars()
-        .map(|c| {
-            c.to_string()
-                .parse()
-                .map_err(|_| Error::new_str("Failed to parse char to number"))
-        })
-        .collect::<ACResult<Vec<u8>>>()?;
+    let numbers = crate::parsing::parse_all(line, crate::parsing::digits)?;
 
     let mut sum: u32 = 0;
     let skip = numbers.len() / 2;
@@ -1,33 +1,34 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::parsing::number;
+use nom::character::complete::{space0, space1};
+use nom::multi::separated_nonempty_list;
+use nom::sequence::preceded;
+use nom::IResult;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?),
-        2 => level_2(&crate::utils::read_lines(data)?),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
-    .map(|r| r.to_string())
+}
+
+fn parse_row(input: &str) -> IResult<&str, Vec<u32>> {
+    preceded(space0, separated_nonempty_list(space1, number))(input)
 }
 
 fn level_1(lines: &[String]) -> ACResult<u32> {
-    let lines = lines
-        .iter()
-        .map(|line| {
-            line.split_whitespace()
-                .map(|c| {
-                    c.to_string()
-                        .parse()
-                        .map_err(|_| Error::new_str("Failed to parse char to number"))
-                })
-                .collect::<ACResult<Vec<u32>>>()
-        })
-        .collect::<ACResult<Vec<Vec<u32>>>>()?;
+    let lines = crate::parsing::parse_lines(lines, parse_row)?;
 
     let mut checksum = 0;
     for numbers in lines.iter() {
-        let min = numbers.iter().min().unwrap();
-        let max = numbers.iter().max().unwrap();
+        let min = numbers.iter().min().unwrap_or(&0);
+        let max = numbers.iter().max().unwrap_or(&0);
         checksum += max - min;
     }
 
@@ -35,18 +36,7 @@ fn level_1(lines: &[String]) -> ACResult<u32> {
 }
 
 fn level_2(lines: &[String]) -> ACResult<u32> {
-    let lines = lines
-        .iter()
-        .map(|line| {
-            line.split_whitespace()
-                .map(|c| {
-                    c.to_string()
-                        .parse()
-                        .map_err(|_| Error::new_str("Failed to parse char to number"))
-                })
-                .collect::<ACResult<Vec<u32>>>()
-        })
-        .collect::<ACResult<Vec<Vec<u32>>>>()?;
+    let lines = crate::parsing::parse_lines(lines, parse_row)?;
 
     let mut sum = 0;
     'outer: for numbers in lines.iter() {
@@ -54,11 +44,11 @@ fn level_2(lines: &[String]) -> ACResult<u32> {
             for ib in ia + 1..numbers.len() {
                 let a = numbers[ia];
                 let b = numbers[ib];
-                if a % b == 0 {
+                if b != 0 && a % b == 0 {
                     sum += a / b;
                     continue 'outer;
                 }
-                if b % a == 0 {
+                if a != 0 && b % a == 0 {
                     sum += b / a;
                     continue 'outer;
                 }
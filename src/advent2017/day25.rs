@@ -1,15 +1,18 @@
+use crate::answer::Answer;
 mod parser;
 
 use self::parser::{TuringBlueprint, TuringDirection, TuringState};
 use crate::errors::{ACResult, Error};
 use std::io::Read;
 
-pub fn get_result<T: Read>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: Read>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&parser::parse_turing(data)?),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&parser::parse_turing(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
-    .map(|r| r.to_string())
 }
 
 pub fn level_1(blueprint: &TuringBlueprint) -> ACResult<u32> {
@@ -1,4 +1,4 @@
-use crate::errors::{ACResult, Error};
+use crate::errors::{ACResult, Context};
 use nom::{
     alt, complete, delimited, do_parse, flat_map, many1, map, named, parse_to, tag, take_while,
 };
@@ -84,9 +84,7 @@ named!(turing_blueprint<&str, TuringBlueprint>,
 pub fn parse_turing<T: Read>(mut data: T) -> ACResult<TuringBlueprint> {
     let mut contents = String::new();
     data.read_to_string(&mut contents)
-        .map_err(|_| Error::new_str("Failed to read stdin"))?;
+        .context("Failed to read stdin")?;
 
-    turing_blueprint(&contents)
-        .map(|x| x.1)
-        .map_err(|_| Error::new_str("Failed to parse blueprint"))
+    crate::parsing::parse_all(&contents, turing_blueprint)
 }
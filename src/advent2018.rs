@@ -1,3 +1,4 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
 use std::io::{BufRead, Read};
 
@@ -27,7 +28,7 @@ mod day23;
 mod day24;
 mod day25;
 
-pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<String> {
+pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<Answer> {
     match day {
         1 => day01::get_result(data, level),
         2 => day02::get_result(data, level),
@@ -54,6 +55,16 @@ pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<St
         23 => day23::get_result(data, level),
         24 => day24::get_result(data, level),
         25 => day25::get_result(data, level),
-        _ => Err(Error::new(format!("Day {} not implemented", day))),
+        _ => Err(Error::Unimplemented(format!("Day {} not implemented", day))),
+    }
+}
+
+/// Only parses the input, for days that expose their parsing separately.
+pub fn parse_input<T: Read + BufRead>(data: T, day: u8) -> Option<ACResult<()>> {
+    match day {
+        15 => Some(day15::parse(data)),
+        22 => Some(day22::parse(data)),
+        23 => Some(day23::parse(data)),
+        _ => None,
     }
 }
@@ -1,58 +1,44 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::parsing::number;
+use nom::character::complete::one_of;
+use nom::IResult;
 use std::collections::HashSet;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?),
-        2 => level_2(&crate::utils::read_lines(data)?),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
-    .map(|r| r.to_string())
 }
 
-fn level_1(lines: &[String]) -> ACResult<i32> {
-    let mut freq: i32 = 0;
-
-    for line in lines.iter() {
-        let (sign, number) = line.split_at(1);
-
-        let number: i32 = number
-            .parse()
-            .map_err(|_| Error::new(format!("Invalid input: {}", line)))?;
+fn parse_change(input: &str) -> IResult<&str, i32> {
+    let (input, sign) = one_of("+-")(input)?;
+    let (input, value): (_, i32) = number(input)?;
+    Ok((input, if sign == '-' { -value } else { value }))
+}
 
-        match sign {
-            "-" => freq -= number,
-            "+" => freq += number,
-            _ => {
-                return Err(Error::new(format!("Invalid input: {}", line)));
-            }
-        }
-    }
-    Ok(freq)
+fn level_1(lines: &[String]) -> ACResult<i32> {
+    Ok(crate::parsing::parse_lines(lines, parse_change)?
+        .into_iter()
+        .sum())
 }
 
 fn level_2(lines: &[String]) -> ACResult<i32> {
+    let changes = crate::parsing::parse_lines(lines, parse_change)?;
     let mut freq = 0;
 
     let mut freqs = HashSet::new();
     freqs.insert(freq);
 
     loop {
-        for line in lines.iter() {
-            let (sign, number) = line.split_at(1);
-
-            let number: i32 = number
-                .parse()
-                .map_err(|_| Error::new(format!("Invalid input: {}", line)))?;
-
-            match sign {
-                "-" => freq -= number,
-                "+" => freq += number,
-                _ => {
-                    return Err(Error::new(format!("Invalid input: {}", line)));
-                }
-            }
+        for change in changes.iter() {
+            freq += change;
 
             if freqs.contains(&freq) {
                 return Ok(freq);
@@ -60,5 +46,6 @@ fn level_2(lines: &[String]) -> ACResult<i32> {
 
             freqs.insert(freq);
         }
+        crate::progress::check_cancelled()?;
     }
 }
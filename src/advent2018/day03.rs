@@ -1,13 +1,17 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
-use nom::{character::complete::digit1, combinator::map, IResult};
+use nom::{character::complete::digit1, combinator::map_res, IResult};
 use nom::{do_parse, tag};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -20,11 +24,7 @@ struct FabricPieceInfo {
 }
 
 fn number(input: &str) -> IResult<&str, usize> {
-    map(digit1, |s: &str| {
-        s.parse()
-            .map_err(|_e| nom::Err::Error(("", nom::error::ErrorKind::ParseTo)))
-            .unwrap()
-    })(input)
+    map_res(digit1, |s: &str| s.parse())(input)
 }
 
 fn info_line(input: &str) -> IResult<&str, FabricPieceInfo> {
@@ -86,11 +86,7 @@ fn get_max_width_height((w, h): (usize, usize), info: &FabricPieceInfo) -> (usiz
 }
 
 fn level_1(lines: &[String]) -> ACResult<usize> {
-    let infos = lines
-        .iter()
-        .map(|line| info_line(&line).map(|x| x.1))
-        .collect::<Result<Vec<FabricPieceInfo>, _>>()
-        .map_err(|_| Error::new_str("Failed to parse blueprint"))?;
+    let infos = crate::parsing::parse_lines(lines, info_line)?;
 
     let (max_width, max_height) = infos.iter().fold((0, 0), get_max_width_height);
 
@@ -121,11 +117,7 @@ fn level_1(lines: &[String]) -> ACResult<usize> {
 }
 
 fn level_2(lines: &[String]) -> ACResult<usize> {
-    let infos = lines
-        .iter()
-        .map(|line| info_line(&line).map(|x| x.1))
-        .collect::<Result<Vec<FabricPieceInfo>, _>>()
-        .map_err(|_| Error::new_str("Failed to parse blueprint"))?;
+    let infos = crate::parsing::parse_lines(lines, info_line)?;
 
     let (max_width, max_height) = infos.iter().fold((0, 0), get_max_width_height);
 
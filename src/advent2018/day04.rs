@@ -1,14 +1,18 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
 use nom::{alt, complete, delimited, do_parse, flat_map, map, named, parse_to, tag, take_while};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -61,12 +65,10 @@ impl Ord for GuardLine {
 named!(number<&str, u32>, flat_map!(complete!(take_while!(|c: char| {c.is_digit(10)})), parse_to!(u32)));
 
 named!(guard_event<&str, GuardEvent>,
-  map!(alt!(tag!("wakes up")| tag!("falls asleep")| delimited!(tag!("Guard #"), complete!(take_while!(|c: char| {c.is_digit(10)})), tag!(" begins shift"))),
-    |dir| {match dir {
-        "wakes up" => GuardEvent::WakeUp,
-        "falls asleep" => GuardEvent::Asleep,
-        g => GuardEvent::Begin(g.parse().unwrap()),
-    }}
+  alt!(
+    map!(tag!("wakes up"), |_| GuardEvent::WakeUp) |
+    map!(tag!("falls asleep"), |_| GuardEvent::Asleep) |
+    map!(delimited!(tag!("Guard #"), number, tag!(" begins shift")), GuardEvent::Begin)
   )
 );
 
@@ -89,11 +91,7 @@ named!(info_line<&str, GuardLine>,
 );
 
 fn level_1(lines: &[String]) -> ACResult<u32> {
-    let mut infos = lines
-        .iter()
-        .map(|line| info_line(&line).map(|x| x.1))
-        .collect::<Result<Vec<GuardLine>, _>>()
-        .map_err(|_| Error::new_str("Failed to parse guard line"))?;
+    let mut infos = crate::parsing::parse_lines(lines, info_line)?;
 
     infos.sort();
     let mut guard_id_to_minute_map = HashMap::new();
@@ -133,11 +131,7 @@ fn level_1(lines: &[String]) -> ACResult<u32> {
 }
 
 fn level_2(lines: &[String]) -> ACResult<u32> {
-    let mut infos = lines
-        .iter()
-        .map(|line| info_line(&line).map(|x| x.1))
-        .collect::<Result<Vec<GuardLine>, _>>()
-        .map_err(|_| Error::new_str("Failed to parse guard line"))?;
+    let mut infos = crate::parsing::parse_lines(lines, info_line)?;
 
     infos.sort();
     let mut guard_id_to_minute_map = HashMap::new();
@@ -1,21 +1,26 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use nom::character::complete::alpha1;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_line(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
-fn level_1(lines: &[String]) -> ACResult<usize> {
-    let line = &lines[0];
+fn level_1(line: &str) -> ACResult<usize> {
+    let line = crate::parsing::parse_all(line, alpha1)?;
     Ok(get_reacted_len(line))
 }
 
-fn level_2(lines: &[String]) -> ACResult<usize> {
-    let line = &lines[0];
+fn level_2(line: &str) -> ACResult<usize> {
+    let line = crate::parsing::parse_all(line, alpha1)?;
 
     let alphabet = (b'A'..=b'Z').map(|c| c as char);
 
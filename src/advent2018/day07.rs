@@ -1,13 +1,17 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
 use nom::{do_parse, named, tag, take};
 use std::collections::HashMap;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -29,11 +33,7 @@ named!(info_line<&str, Info>,
 
 // Step F must be finished before step E can begin.
 fn level_1(lines: &[String]) -> ACResult<String> {
-    let infos = lines
-        .iter()
-        .map(|l| info_line(&l).map(|x| x.1))
-        .collect::<Result<Vec<Info>, _>>()
-        .map_err(|_| Error::new_str("Failed to parse line"))?;
+    let infos = crate::parsing::parse_lines(lines, info_line)?;
     let mut name_to_dependencies_map = HashMap::new();
     for l in infos {
         let entry = name_to_dependencies_map
@@ -86,11 +86,7 @@ struct Worker {
 }
 
 fn level_2(lines: &[String]) -> ACResult<u32> {
-    let infos = lines
-        .iter()
-        .map(|l| info_line(&l).map(|x| x.1))
-        .collect::<Result<Vec<Info>, _>>()
-        .map_err(|_| Error::new_str("Failed to parse guard line"))?;
+    let infos = crate::parsing::parse_lines(lines, info_line)?;
     let mut deps = HashMap::new();
     for l in infos {
         let entry = deps.entry(l.name).or_insert_with(Vec::new);
@@ -1,11 +1,20 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::parsing::number;
+use nom::character::complete::char;
+use nom::multi::count;
+use nom::sequence::{preceded, separated_pair};
+use nom::IResult;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_line(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -14,12 +23,8 @@ struct Node {
     metadata: Vec<u32>,
 }
 
-fn level_1(lines: &[String]) -> ACResult<u32> {
-    let numbers: Vec<u32> = lines[0]
-        .split(' ')
-        .map(|s| s.parse().map_err(|_| Error::new_str("Failed to parse")))
-        .collect::<ACResult<_>>()?;
-    let node = parse_node(&mut numbers.iter());
+fn level_1(line: &str) -> ACResult<u32> {
+    let node = crate::parsing::parse_all(line, parse_node)?;
     let count = count_node(&node);
 
     Ok(count)
@@ -29,20 +34,11 @@ fn count_node(node: &Node) -> u32 {
     node.children.iter().map(|c| count_node(c)).sum::<u32>() + node.metadata.iter().sum::<u32>()
 }
 
-fn parse_node<'a, T: std::iter::Iterator<Item = &'a u32>>(numbers: &mut T) -> Node {
-    let child_count = numbers.next().unwrap();
-
-    let meta_data_count = numbers.next().unwrap();
-
-    let mut children = Vec::new();
-    for _ in 0..*child_count {
-        children.push(parse_node(numbers));
-    }
-    let mut metadata = Vec::new();
-    for _ in 0..*meta_data_count {
-        metadata.push(*numbers.next().unwrap());
-    }
-    Node { children, metadata }
+fn parse_node(input: &str) -> IResult<&str, Node> {
+    let (input, (child_count, metadata_count)) = separated_pair(number, char(' '), number)(input)?;
+    let (input, children) = count(preceded(char(' '), parse_node), child_count)(input)?;
+    let (input, metadata) = count(preceded(char(' '), number), metadata_count)(input)?;
+    Ok((input, Node { children, metadata }))
 }
 
 fn count_node_2(node: &Node) -> u32 {
@@ -52,23 +48,17 @@ fn count_node_2(node: &Node) -> u32 {
         node.metadata
             .iter()
             .map(|m| {
-                let m = *m as usize - 1;
-                if m < node.children.len() {
-                    count_node_2(&node.children[m])
-                } else {
-                    0
-                }
+                (*m as usize)
+                    .checked_sub(1)
+                    .and_then(|m| node.children.get(m))
+                    .map_or(0, count_node_2)
             })
             .sum()
     }
 }
 
-fn level_2(lines: &[String]) -> ACResult<u32> {
-    let numbers: Vec<u32> = lines[0]
-        .split(' ')
-        .map(|s| s.parse().map_err(|_| Error::new_str("Failed to parse")))
-        .collect::<ACResult<_>>()?;
-    let node = parse_node(&mut numbers.iter());
+fn level_2(line: &str) -> ACResult<u32> {
+    let node = crate::parsing::parse_all(line, parse_node)?;
     let count = count_node_2(&node);
 
     Ok(count)
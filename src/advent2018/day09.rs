@@ -1,12 +1,16 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
 use nom::{complete, do_parse, flat_map, named, parse_to, tag, take_while};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_line(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -27,13 +31,13 @@ named!(info_line<&str, Info>,
   )
 );
 
-fn level_1(lines: &[String]) -> ACResult<u32> {
-    let info = info_line(&lines[0]).unwrap().1;
+fn level_1(line: &str) -> ACResult<u32> {
+    let info = crate::parsing::parse_all(line, info_line)?;
     Ok(run_game(info.player_count, info.last_marble_worth))
 }
 
-fn level_2(lines: &[String]) -> ACResult<u32> {
-    let info = info_line(&lines[0]).unwrap().1;
+fn level_2(line: &str) -> ACResult<u32> {
+    let info = crate::parsing::parse_all(line, info_line)?;
     Ok(run_game(info.player_count, info.last_marble_worth * 100))
 }
 
@@ -1,12 +1,17 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
-use nom::{complete, do_parse, map, named, tag, take_while};
+use crate::utils::Field;
+use nom::{complete, do_parse, map_res, named, tag, take_while};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from_picture),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -28,9 +33,9 @@ struct Point {
     velocity: Velocity,
 }
 
-named!(number<&str, i64>, map!(
+named!(number<&str, i64>, map_res!(
     complete!(take_while!(|c: char| {c == '-' || c == ' '  || c.is_digit(10)})),
-    |s| s.trim().parse().unwrap()
+    |s: &str| s.trim().parse()
 ));
 
 // position=< 9,  1> velocity=< 0,  2>
@@ -49,17 +54,31 @@ named!(info_line<&str, Point>,
   )
 );
 
-fn level_1(lines: &[String]) -> ACResult<String> {
-    let mut points: Vec<_> = lines.iter().map(|l| info_line(&l).unwrap().1).collect();
+/// The message appears within this many seconds for all known inputs.
+const MAX_SECONDS: u64 = 30000;
 
-    for _ in 0..30000 {
+/// Every second moves all points, so progress is reported more often than usual.
+const PROGRESS_INTERVAL: u64 = 1000;
+
+fn not_found() -> Error {
+    Error::new(format!(
+        "No message appeared within {} seconds",
+        MAX_SECONDS
+    ))
+}
+
+fn level_1(lines: &[String]) -> ACResult<Field<bool>> {
+    let mut points = crate::parsing::parse_lines(lines, info_line)?;
+
+    for i in 0..MAX_SECONDS {
         if possible_message(&points) {
             let field = assemble_points(&points);
             return Ok(field);
         }
         simulate_second(&mut points);
+        crate::progress::step_every(i, PROGRESS_INTERVAL, || format!("{} seconds simulated", i))?;
     }
-    panic!("Not found");
+    Err(not_found())
 }
 
 fn simulate_second(points: &mut [Point]) {
@@ -83,7 +102,7 @@ fn possible_message(points: &[Point]) -> bool {
     false
 }
 
-fn assemble_points(points: &[Point]) -> String {
+fn assemble_points(points: &[Point]) -> Field<bool> {
     let mut offset_x = i64::max_value();
     let mut offset_y = i64::max_value();
     let mut width = 0;
@@ -104,29 +123,27 @@ fn assemble_points(points: &[Point]) -> String {
     }
     let width = (width - offset_x) as usize + 1;
     let height = (height - offset_y) as usize + 1;
-    let mut field: Vec<u8> = vec![b'.'; (width + 1) * height];
-    for y in 0..height {
-        field[(y * (width + 1)) + width] = b'\n';
-    }
+    let mut field = Field::new(width as u32, height as u32);
     for p in points.iter() {
         let x = p.position.x - offset_x;
         let y = p.position.y - offset_y;
         if x >= width as i64 || y >= height as i64 {
             continue;
         }
-        field[(x + y * (width as i64 + 1)) as usize] = b'#';
+        *field.get_mut(x as u32, y as u32) = true;
     }
-    String::from_utf8(field).unwrap()
+    field
 }
 
 fn level_2(lines: &[String]) -> ACResult<u64> {
-    let mut points: Vec<_> = lines.iter().map(|l| info_line(&l).unwrap().1).collect();
+    let mut points = crate::parsing::parse_lines(lines, info_line)?;
 
-    for i in 0..30000 {
+    for i in 0..MAX_SECONDS {
         if possible_message(&points) {
             return Ok(i);
         }
         simulate_second(&mut points);
+        crate::progress::step_every(i, PROGRESS_INTERVAL, || format!("{} seconds simulated", i))?;
     }
-    panic!("Not found");
+    Err(not_found())
 }
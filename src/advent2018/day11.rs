@@ -1,16 +1,21 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::parsing::number;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?),
-        2 => level_2(&crate::utils::read_lines(data)?),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_line(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
-fn level_1(lines: &[String]) -> ACResult<String> {
-    let serial: i32 = lines[0].parse().unwrap();
+fn level_1(line: &str) -> ACResult<String> {
+    let serial = crate::parsing::parse_all(line, number)?;
 
     let size: u32 = 300;
 
@@ -67,8 +72,8 @@ fn print(grid: &[i32]) {
     }
 }
 
-fn level_2(lines: &[String]) -> ACResult<String> {
-    let serial: i32 = lines[0].parse().unwrap();
+fn level_2(line: &str) -> ACResult<String> {
+    let serial = crate::parsing::parse_all(line, number)?;
 
     let size = 300;
 
@@ -1,14 +1,26 @@
+use crate::answer::Answer;
+use crate::automaton::LineAutomaton;
 use crate::errors::{ACResult, Error};
+use crate::utils::SparseLine;
 use nom::{complete, do_parse, many1, map, named, tag, take, take_while};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&parse(&crate::utils::read_all(data).unwrap()).unwrap().1)
-            .map(|r| r.to_string()),
-        2 => level_2(&parse(&crate::utils::read_all(data).unwrap()).unwrap().1)
-            .map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::parsing::parse_all(
+            &crate::utils::read_all(data)?,
+            parse,
+        )?)
+        .map(Answer::from),
+        2 => level_2(&crate::parsing::parse_all(
+            &crate::utils::read_all(data)?,
+            parse,
+        )?)
+        .map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -48,121 +60,79 @@ named!(parse<&str, Input>,
   )
 );
 
-#[derive(Clone)]
-struct Tunnel {
-    pots_left: Vec<bool>,
-    // pot zero is in pots_right
-    pots_right: Vec<bool>,
-}
-
-impl Tunnel {
-    fn new(initial_state: &[bool]) -> Tunnel {
-        let mut pots_right = vec![false; initial_state.len() * 2];
-        let pots_left = vec![false; initial_state.len()];
-
-        for (i, p) in initial_state.iter().enumerate() {
-            pots_right[i] = *p;
-        }
-        Tunnel {
-            pots_left,
-            pots_right,
-        }
-    }
-
-    fn get(&self, i: isize) -> bool {
-        if i < 0 {
-            self.pots_left[-i as usize - 1]
-        } else {
-            self.pots_right[i as usize]
-        }
-    }
-
-    fn set(&mut self, i: isize, v: bool) {
-        if i < 0 {
-            self.pots_left[-i as usize - 1] = v;
-        } else {
-            self.pots_right[i as usize] = v;
-        }
-    }
-
-    fn tick(&mut self, instructions: &[Instruction]) {
-        let old_tunnel = self.clone();
-        for i in -(self.pots_left.len() as isize) + 3..(self.pots_right.len() as isize - 2) {
-            let inst = instructions.iter().find(|inst| {
-                for j in 0..inst.pattern.len() {
-                    if inst.pattern[j] != old_tunnel.get(i + j as isize - 2) {
-                        return false;
-                    }
-                }
-                true
-            });
-            if inst.is_none() {
-                self.set(i, false);
-                eprintln!("No instruction found ... removing the plant");
-                continue;
-            }
-            self.set(i, inst.unwrap().new_state);
-        }
-        let len = self.pots_left.len();
-        if self.pots_left[len - 3] || self.pots_left[len - 4] {
-            self.pots_left.resize(len * 2, false);
-        }
-        let len = self.pots_right.len();
-        if self.pots_right[len - 3] || self.pots_right[len - 4] {
-            self.pots_right.resize(len * 2, false);
+/// Pots that have a plant, the others are left out.
+fn plants(initial_state: &[bool]) -> SparseLine<bool> {
+    let mut plants = SparseLine::new();
+    for (i, p) in initial_state.iter().enumerate() {
+        if *p {
+            plants.insert(i as i64, true);
         }
     }
+    plants
+}
 
-    fn sum(&self) -> isize {
-        self.pots_left
+/// Patterns without an instruction don't grow a plant.
+fn grow(instructions: &[Instruction], pots: &[Option<&bool>]) -> Option<bool> {
+    let inst = instructions.iter().find(|inst| {
+        inst.pattern
             .iter()
-            .enumerate()
-            .map(|(i, p)| if *p { -(i as isize) - 1 } else { 0 })
-            .sum::<isize>()
-            + self
-                .pots_right
-                .iter()
-                .enumerate()
-                .map(|(i, p)| if *p { i as isize } else { 0 })
-                .sum::<isize>()
+            .zip(pots.iter())
+            .all(|(p, pot)| *p == pot.is_some())
+    });
+    if inst.is_some_and(|inst| inst.new_state) {
+        Some(true)
+    } else {
+        None
     }
+}
 
-    #[allow(dead_code)]
-    fn print_pots(&self) {
-        let line: String = self
-            .pots_left
-            .iter()
-            .chain(self.pots_right.iter())
-            .map(|p| if *p { '#' } else { '.' })
-            .collect();
-        println!("{}", line);
-    }
+fn sum(plants: &SparseLine<bool>) -> i64 {
+    plants.iter().map(|(i, _)| i).sum()
 }
 
-fn level_1(input: &Input) -> ACResult<isize> {
-    let mut tunnel = Tunnel::new(&input.initial_state);
+fn level_1(input: &Input) -> ACResult<i64> {
+    let mut tunnel = LineAutomaton::new(plants(&input.initial_state), 2, |pots| {
+        grow(&input.instructions, pots)
+    });
 
-    for _ in 0..20 {
-        tunnel.tick(&input.instructions);
-    }
-    let sum = tunnel.sum();
-    Ok(sum)
+    tunnel.skip_to(20, |_, _| {})?;
+    Ok(sum(tunnel.state()))
 }
 
-fn level_2(input: &Input) -> ACResult<isize> {
-    let mut tunnel = Tunnel::new(&input.initial_state);
+fn level_2(input: &Input) -> ACResult<i64> {
+    let mut tunnel = LineAutomaton::new(plants(&input.initial_state), 2, |pots| {
+        grow(&input.instructions, pots)
+    });
 
-    let part_iterations = 1000;
-    let total_iterations: isize = 50_000_000_000;
-
-    let mut p = 0;
-    let mut n = 0;
-    for _ in 0..=part_iterations {
-        tunnel.tick(&input.instructions);
+    tunnel.skip_to(50_000_000_000, |_, _| {})?;
+    Ok(sum(tunnel.state()))
+}
 
-        p = n;
-        n = tunnel.sum();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "initial state: #..#.#..##......###...###
+
+...## => #
+..#.. => #
+.#... => #
+.#.#. => #
+.#.## => #
+.##.. => #
+.#### => #
+#.#.# => #
+#.### => #
+##.#. => #
+##.## => #
+###.. => #
+###.# => #
+####. => #
+";
+
+    #[test]
+    fn run_level_1_examples() {
+        let input = crate::parsing::parse_all(EXAMPLE, parse).unwrap();
+        assert_eq!(level_1(&input), Ok(325));
     }
-    let sum = p + (n - p) * (total_iterations - part_iterations);
-    Ok(sum)
 }
@@ -1,11 +1,19 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::geometry::{Direction, Turn};
+use crate::utils::Field;
+use crate::visualize::{self, Cell, Color};
+use std::convert::TryFrom;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?),
-        2 => level_2(&crate::utils::read_lines(data)?),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -18,270 +26,205 @@ enum TrackType {
     Intersection,
 }
 
-#[derive(Debug, Clone)]
-enum CartDirection {
-    Left,
-    Right,
-    Up,
-    Down,
-}
-
-impl From<&char> for CartDirection {
-    fn from(s: &char) -> CartDirection {
-        match s {
-            '<' => CartDirection::Left,
-            '>' => CartDirection::Right,
-            'v' => CartDirection::Down,
-            '^' => CartDirection::Up,
-            s => panic!(format!("Invalid cart direction: {}", s)),
-        }
+fn parse_track(c: char) -> ACResult<Option<TrackType>> {
+    match c {
+        ' ' => Ok(None),
+        '-' | '>' | '<' => Ok(Some(TrackType::Horizontal)),
+        '|' | 'v' | '^' => Ok(Some(TrackType::Vertical)),
+        '/' => Ok(Some(TrackType::TopRightBottomLeft)),
+        '\\' => Ok(Some(TrackType::TopLeftBottomRight)),
+        '+' => Ok(Some(TrackType::Intersection)),
+        _ => Err(Error::Parse("a track or a cart".to_owned())),
     }
 }
 
-impl From<&char> for TrackType {
-    fn from(s: &char) -> TrackType {
-        match s {
-            '-' | '>' | '<' => TrackType::Horizontal,
-            '|' | 'v' | '^' => TrackType::Vertical,
-            '/' => TrackType::TopRightBottomLeft,
-            '\\' => TrackType::TopLeftBottomRight,
-            '+' => TrackType::Intersection,
-            s => panic!(format!("Invalid track type: {}", s)),
-        }
+fn parse_cart(c: char) -> Option<Cart> {
+    match c {
+        '<' | '>' | 'v' | '^' => Some(Cart {
+            direction: Direction::try_from(c).ok()?,
+            next_turn: Turn::Left,
+        }),
+        _ => None,
     }
 }
 
-#[derive(Clone)]
-enum TurnType {
-    Left,
-    Right,
-    Straight,
-}
-
 #[derive(Clone)]
 struct Cart {
-    direction: CartDirection,
-    next_turn_type: TurnType,
+    direction: Direction,
+    next_turn: Turn,
 }
 
 impl Cart {
     fn update_next_direction(&mut self, track: &TrackType) {
         self.direction = match track {
-            TrackType::Horizontal | TrackType::Vertical => self.direction.clone(),
-            TrackType::TopLeftBottomRight => match self.direction {
-                CartDirection::Left => CartDirection::Up,
-                CartDirection::Right => CartDirection::Down,
-                CartDirection::Up => CartDirection::Left,
-                CartDirection::Down => CartDirection::Right,
-            },
-            TrackType::TopRightBottomLeft => match self.direction {
-                CartDirection::Left => CartDirection::Down,
-                CartDirection::Right => CartDirection::Up,
-                CartDirection::Up => CartDirection::Right,
-                CartDirection::Down => CartDirection::Left,
-            },
+            TrackType::Horizontal | TrackType::Vertical => self.direction,
+            TrackType::TopLeftBottomRight => {
+                if self.direction.is_horizontal() {
+                    self.direction.turn_right()
+                } else {
+                    self.direction.turn_left()
+                }
+            }
+            TrackType::TopRightBottomLeft => {
+                if self.direction.is_horizontal() {
+                    self.direction.turn_left()
+                } else {
+                    self.direction.turn_right()
+                }
+            }
             TrackType::Intersection => {
-                let next_turn_type = self.next_turn_type.clone();
-                self.next_turn_type = match next_turn_type {
-                    TurnType::Left => TurnType::Straight,
-                    TurnType::Straight => TurnType::Right,
-                    TurnType::Right => TurnType::Left,
+                let turn = self.next_turn;
+                self.next_turn = match turn {
+                    Turn::Left => Turn::Straight,
+                    Turn::Straight => Turn::Right,
+                    Turn::Right | Turn::Back => Turn::Left,
                 };
-                match next_turn_type {
-                    TurnType::Left => match self.direction {
-                        CartDirection::Left => CartDirection::Down,
-                        CartDirection::Right => CartDirection::Up,
-                        CartDirection::Up => CartDirection::Left,
-                        CartDirection::Down => CartDirection::Right,
-                    },
-                    TurnType::Straight => self.direction.clone(),
-                    TurnType::Right => match self.direction {
-                        CartDirection::Left => CartDirection::Up,
-                        CartDirection::Right => CartDirection::Down,
-                        CartDirection::Up => CartDirection::Right,
-                        CartDirection::Down => CartDirection::Left,
-                    },
-                }
+                self.direction.turn(turn)
             }
         };
     }
 }
 
 struct Game {
-    width: usize,
-    height: usize,
-    tracks: Vec<Vec<Option<TrackType>>>,
-    carts: Vec<Vec<Option<Cart>>>,
+    tracks: Field<Option<TrackType>>,
+    carts: Field<Option<Cart>>,
 }
 
 impl Game {
-    fn new(field: &[Vec<char>]) -> Self {
-        let width = field[0].len();
-        let height = field.len();
-        let tracks = field
+    fn new(lines: &[String]) -> ACResult<Self> {
+        // Lines may lack trailing spaces, e.g. in copied examples
+        let width = lines.iter().map(|l| l.chars().count()).max();
+        let field: String = lines
             .iter()
-            .map(|l| {
-                l.iter()
-                    .map(|c| if *c == ' ' { None } else { Some(c.into()) })
-                    .collect()
-            })
+            .map(|l| format!("{:width$}\n", l, width = width.unwrap_or_default()))
             .collect();
 
-        let carts = field
-            .iter()
-            .map(|l| {
-                l.iter()
-                    .map(|c| {
-                        if *c != '<' && *c != '>' && *c != 'v' && *c != '^' {
-                            None
-                        } else {
-                            Some(Cart {
-                                direction: c.into(),
-                                next_turn_type: TurnType::Left,
-                            })
-                        }
-                    })
-                    .collect()
-            })
-            .collect();
-
-        Game {
-            width,
-            height,
-            tracks,
-            carts,
-        }
+        Ok(Game {
+            tracks: Field::parse(&field, parse_track)?,
+            carts: Field::parse(&field, |c| Ok(parse_cart(c)))?,
+        })
     }
 
-    fn tick(&mut self) -> Option<(usize, usize)> {
+    fn tick(&mut self) -> ACResult<Option<(u32, u32)>> {
         let mut carts_handled = std::collections::HashSet::new();
 
         let mut first_collision = None;
 
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if self.carts[y][x].is_none() {
-                    continue;
-                }
-                if carts_handled.contains(&(x, y)) {
-                    continue;
-                }
+        for (x, y) in self.carts.positions() {
+            let direction = match self.carts.get(x, y) {
+                Some(cart) => &cart.direction,
+                None => continue,
+            };
+            if carts_handled.contains(&(x, y)) {
+                continue;
+            }
 
-                let (new_x, new_y) =
-                    next_position(x, y, &self.carts[y][x].as_ref().unwrap().direction);
-                if self.move_cart(x, y, new_x, new_y).is_err() {
-                    if first_collision.is_none() {
-                        first_collision = Some((new_x, new_y));
-                    }
-                } else {
-                    self.carts[new_y][new_x]
-                        .as_mut()
-                        .unwrap()
-                        .update_next_direction(self.tracks[new_y][new_x].as_ref().unwrap());
-                    carts_handled.insert((new_x, new_y));
+            let offset = direction.offset();
+            let (new_x, new_y) = self
+                .carts
+                .offset(x, y, offset.x, offset.y)
+                .ok_or_else(|| Error::new(format!("Cart at {},{} left the map", x, y)))?;
+            if self.move_cart(x, y, new_x, new_y).is_err() {
+                if first_collision.is_none() {
+                    first_collision = Some((new_x, new_y));
                 }
+            } else {
+                let track = self.tracks.get(new_x, new_y).as_ref().ok_or_else(|| {
+                    Error::new(format!("Cart left the track at {},{}", new_x, new_y))
+                })?;
+                self.carts
+                    .get_mut(new_x, new_y)
+                    .as_mut()
+                    .unwrap()
+                    .update_next_direction(track);
+                carts_handled.insert((new_x, new_y));
             }
         }
 
-        first_collision
+        Ok(first_collision)
     }
 
-    fn move_cart(
-        &mut self,
-        old_x: usize,
-        old_y: usize,
-        new_x: usize,
-        new_y: usize,
-    ) -> ACResult<()> {
-        if self.carts[new_y][new_x].is_some() {
-            self.carts[old_y][old_x] = None;
-            self.carts[new_y][new_x] = None;
+    fn move_cart(&mut self, old_x: u32, old_y: u32, new_x: u32, new_y: u32) -> ACResult<()> {
+        if self.carts.get(new_x, new_y).is_some() {
+            *self.carts.get_mut(old_x, old_y) = None;
+            *self.carts.get_mut(new_x, new_y) = None;
             return Err(Error::new(format!(
                 "Not empty, removing both carts: {},{}",
                 new_x, new_y
             )));
         }
 
-        self.carts[new_y][new_x] = self.carts[old_y][old_x].clone();
-        self.carts[old_y][old_x] = None;
+        *self.carts.get_mut(new_x, new_y) = self.carts.get_mut(old_x, old_y).take();
         Ok(())
     }
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        for y in 0..self.height {
-            let line: String = (0..self.width)
-                .map(|x| {
-                    if let Some(cart) = self.carts[y][x].as_ref() {
-                        match cart.direction {
-                            CartDirection::Down => 'v',
-                            CartDirection::Up => '^',
-                            CartDirection::Left => '<',
-                            CartDirection::Right => '>',
-                        }
-                    } else if let Some(track) = self.tracks[y][x].as_ref() {
-                        match track {
-                            TrackType::Horizontal => '-',
-                            TrackType::Vertical => '|',
-                            TrackType::TopLeftBottomRight => '\\',
-                            TrackType::TopRightBottomLeft => '/',
-                            TrackType::Intersection => '+',
-                        }
-                    } else {
-                        ' '
-                    }
-                })
-                .collect();
-            println!("{}", line);
-        }
+    /// Animates the carts on the tracks with `--visualize`, following the first cart.
+    fn show(&self, tick: usize) {
+        let first_cart = self
+            .carts
+            .positions()
+            .find(|&(x, y)| self.carts.get(x, y).is_some());
+        let caption = format!("Tick {}", tick);
+        visualize::show(
+            self.tracks.width(),
+            self.tracks.height(),
+            first_cart,
+            &caption,
+            |x, y| match (self.carts.get(x, y), self.tracks.get(x, y)) {
+                (Some(cart), _) => Cell::new(
+                    match cart.direction {
+                        Direction::South => 'v',
+                        Direction::North => '^',
+                        Direction::West => '<',
+                        Direction::East => '>',
+                    },
+                    Color::Red,
+                ),
+                (None, Some(track)) => Cell::new(
+                    match track {
+                        TrackType::Horizontal => '-',
+                        TrackType::Vertical => '|',
+                        TrackType::TopLeftBottomRight => '\\',
+                        TrackType::TopRightBottomLeft => '/',
+                        TrackType::Intersection => '+',
+                    },
+                    Color::Gray,
+                ),
+                (None, None) => Cell::plain(' '),
+            },
+        );
     }
 
-    fn get_last_cart(&self) -> Option<(usize, usize)> {
-        let mut last_pos = None;
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if self.carts[y][x].is_some() {
-                    if last_pos.is_some() {
-                        return None;
-                    }
-                    last_pos = Some((x, y));
-                }
-            }
+    fn get_last_cart(&self) -> Option<(u32, u32)> {
+        let mut carts = self
+            .carts
+            .positions()
+            .filter(|&(x, y)| self.carts.get(x, y).is_some());
+        match (carts.next(), carts.next()) {
+            (Some(last), None) => Some(last),
+            _ => None,
         }
-        last_pos
-    }
-}
-
-fn next_position(x: usize, y: usize, direction: &CartDirection) -> (usize, usize) {
-    match direction {
-        CartDirection::Left => (x - 1, y),
-        CartDirection::Right => (x + 1, y),
-        CartDirection::Up => (x, y - 1),
-        CartDirection::Down => (x, y + 1),
     }
 }
 
 fn level_1(lines: &[String]) -> ACResult<String> {
-    let field = lines
-        .iter()
-        .map(|l| l.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-
-    let mut game = Game::new(&field);
+    let mut game = Game::new(lines)?;
+    let mut tick = 0;
     loop {
-        if let Some(collision) = game.tick() {
+        game.show(tick);
+        tick += 1;
+        if let Some(collision) = game.tick()? {
             return Ok(format!("{},{}", collision.0, collision.1));
         }
     }
 }
 fn level_2(lines: &[String]) -> ACResult<String> {
-    let field = lines
-        .iter()
-        .map(|l| l.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-    let mut game = Game::new(&field);
+    let mut game = Game::new(lines)?;
+    let mut tick = 0;
     loop {
-        game.tick();
+        game.show(tick);
+        tick += 1;
+        game.tick()?;
 
         let last_pos = game.get_last_cart();
         if let Some(a) = last_pos {
@@ -289,3 +232,35 @@ fn level_2(lines: &[String]) -> ACResult<String> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn run_level_1_examples() {
+        let example = r"/->-\        
+|   |  /----\
+| /-+--+-\  |
+| | |  | v  |
+\-+-/  \-+--/
+  \------/   ";
+        assert_eq!(level_1(&lines(example)), Ok("7,3".to_owned()));
+    }
+
+    #[test]
+    fn run_level_2_examples() {
+        let example = r"/>-<\  
+|   |  
+| /<+-\
+| | | v
+\>+</ |
+  |   ^
+  \<->/";
+        assert_eq!(level_2(&lines(example)), Ok("6,4".to_owned()));
+    }
+}
@@ -1,16 +1,20 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_line(data)?),
-        2 => level_2(&crate::utils::read_line(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_line(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
 fn level_1(line: &str) -> ACResult<String> {
-    let count: usize = line.parse().unwrap();
+    let count: usize = crate::parsing::parse_all(line, crate::parsing::number)?;
 
     let mut recipes: Vec<u8> = Vec::new();
     recipes.push(3);
@@ -56,13 +60,13 @@ fn level_2(line: &str) -> ACResult<usize> {
     let mut elve_positions: Vec<usize> = Vec::new();
     elve_positions.push(0);
     elve_positions.push(1);
-    let line: Vec<u8> = line
-        .chars()
-        .map(|c| c.to_string().parse().unwrap())
-        .collect();
+    let line = crate::parsing::parse_all(line, crate::parsing::digits)?;
 
     let mut next_search_i = 0;
+    let mut step = 0;
     loop {
+        step += 1;
+        crate::progress::step(step, || format!("{} recipes created", recipes.len()))?;
         let sum: u64 = elve_positions.iter().map(|i| u64::from(recipes[*i])).sum();
 
         let mut new_recipes: Vec<u8> = sum
@@ -1,17 +1,27 @@
-use crate::errors::{ACResult, Error};
+use crate::answer::Answer;
+use crate::errors::{ACResult, Error, ParseError};
+use crate::geometry::Vec2;
+use crate::search;
 use crate::utils::Field;
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use crate::visualize::{self, Cell, Color};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
+/// Only reads the map, for benchmarking.
+pub fn parse<T: BufRead>(data: T) -> ACResult<()> {
+    parse_map(&crate::utils::read_lines(data)?).map(|_| ())
+}
+
 #[derive(Clone)]
 enum FieldType {
     Wall,
@@ -25,176 +35,73 @@ struct Player {
     hit_points: u32,
 }
 
-#[derive(PartialEq, Debug, Clone, Eq)]
-struct Point {
-    x: u32,
-    y: u32,
-}
-
-impl Point {
-    fn new(x: u32, y: u32) -> Self {
-        Point { x, y }
-    }
-
-    fn above(&self) -> Option<Self> {
-        if self.y > 0 {
-            Some(Point {
-                x: self.x,
-                y: self.y - 1,
-            })
-        } else {
-            None
-        }
-    }
-
-    fn below(&self) -> Option<Self> {
-        if self.y != u32::max_value() {
-            Some(Point {
-                x: self.x,
-                y: self.y + 1,
-            })
-        } else {
-            None
-        }
-    }
-
-    fn left(&self) -> Option<Self> {
-        if self.x > 0 {
-            Some(Point {
-                x: self.x - 1,
-                y: self.y,
-            })
-        } else {
-            None
-        }
-    }
-
-    fn right(&self) -> Option<Self> {
-        if self.x != u32::max_value() {
-            Some(Point {
-                x: self.x + 1,
-                y: self.y,
-            })
-        } else {
-            None
-        }
-    }
-    fn get_adjacent_points(&self) -> Vec<Self> {
-        let mut result = Vec::new();
-        if let Some(p) = self.above() {
-            result.push(p);
-        }
-        if let Some(p) = self.left() {
-            result.push(p);
-        }
-        if let Some(p) = self.right() {
-            result.push(p);
-        }
-        if let Some(p) = self.below() {
-            result.push(p);
-        }
-        result
-    }
-}
+static START_HIT_POINTS: u32 = 200;
 
-#[derive(PartialEq, Eq)]
-struct PartialPath {
-    path_start: Point,
-    next_point: Point,
-    // path length * 4 + prio of first point
-    dist: usize,
+struct Game {
+    field: Field<FieldType>,
+    attack_power_goblin: u32,
+    attack_power_elf: u32,
 }
 
-impl PartialOrd for PartialPath {
-    fn partial_cmp(&self, other: &PartialPath) -> Option<Ordering> {
-        Some(self.cmp(&other))
+fn parse_cell(c: char) -> ACResult<FieldType> {
+    match c {
+        '#' => Ok(FieldType::Wall),
+        '.' => Ok(FieldType::Empty),
+        'G' => Ok(FieldType::Goblin(Player {
+            hit_points: START_HIT_POINTS,
+        })),
+        'E' => Ok(FieldType::Elf(Player {
+            hit_points: START_HIT_POINTS,
+        })),
+        _ => Err(Error::Parse(
+            "a wall, an open cavern, a goblin or an elf".to_owned(),
+        )),
     }
 }
 
-impl Ord for PartialPath {
-    fn cmp(&self, other: &PartialPath) -> Ordering {
-        match self.dist.cmp(&other.dist) {
-            Ordering::Greater => Ordering::Less,
-            Ordering::Less => Ordering::Greater,
-            Ordering::Equal => Ordering::Equal,
-        }
+fn parse_map(lines: &[String]) -> ACResult<Field<FieldType>> {
+    let input = lines.join("\n");
+    let field = Field::parse(&input, parse_cell)?;
+    if field.width() == 0 {
+        return Err(ParseError::at(&input, "", "a map").into());
     }
-}
-
-static START_HIT_POINTS: u32 = 200;
-
-struct Game {
-    field: Field<FieldType>,
-    attack_power_goblin: u32,
-    attack_power_elf: u32,
+    Ok(field)
 }
 
 impl Game {
-    fn new(input: &[String], attack_power_elf: u32, attack_power_goblin: u32) -> Self {
-        let height = input.len() as u32;
-        let width = input[0].len() as u32;
-        let field = input
-            .iter()
-            .flat_map(|l| {
-                l.chars()
-                    .map(|c| match c {
-                        '#' => FieldType::Wall,
-                        '.' => FieldType::Empty,
-                        'G' => FieldType::Goblin(Player {
-                            hit_points: START_HIT_POINTS,
-                        }),
-                        'E' => FieldType::Elf(Player {
-                            hit_points: START_HIT_POINTS,
-                        }),
-                        _ => panic!("Invalid input"),
-                    })
-                    .collect::<Vec<FieldType>>()
-            })
-            .collect();
+    fn new(field: Field<FieldType>, attack_power_elf: u32, attack_power_goblin: u32) -> Self {
         Game {
-            field: Field::from(field, width, height),
+            field,
             attack_power_elf,
             attack_power_goblin,
         }
     }
 
-    fn get_point(&self, point: &Point) -> &FieldType {
-        &self.field.get(point.x, point.y)
+    /// Everything outside of the map is a wall.
+    fn get_point(&self, point: &Vec2) -> &FieldType {
+        self.field.at(*point).unwrap_or(&FieldType::Wall)
     }
 
-    fn set(&mut self, point: &Point, field: FieldType) {
-        *self.field.get_mut(point.x, point.y) = field;
+    fn set(&mut self, point: &Vec2, field: FieldType) {
+        if let Some(f) = self.field.at_mut(*point) {
+            *f = field;
+        }
     }
     fn get(&self, x: u32, y: u32) -> &FieldType {
         &self.field.get(x, y)
     }
 
-    fn get_mut(&mut self, point: &Point) -> &mut FieldType {
-        self.field.get_mut(point.x, point.y)
-    }
-
-    fn get_adjacent(&self, point: &Point) -> Vec<Point> {
-        let mut result = Vec::new();
-        if let Some(p) = point.above() {
-            result.push(p);
-        }
-        if let Some(p) = point.left() {
-            result.push(p);
-        }
-        if let Some(p) = point.right() {
-            if p.y < self.field.height() {
-                result.push(p);
-            }
-        }
-        if let Some(p) = point.below() {
-            if p.y < self.field.width() {
-                result.push(p);
-            }
-        }
-        result
+    /// Adjacent points in reading order.
+    fn get_adjacent(&self, point: &Vec2) -> Vec<Vec2> {
+        point
+            .neighbours4()
+            .iter()
+            .copied()
+            .filter(|p| self.field.at(*p).is_some())
+            .collect()
     }
 
-    fn get_victim(&self, point: &Point) -> Option<Point> {
+    fn get_victim(&self, point: &Vec2) -> Option<Vec2> {
         let in_range = self.get_adjacent(point);
         in_range
             .iter()
@@ -223,7 +130,7 @@ impl Game {
             .cloned()
     }
 
-    fn attack(&mut self, victim_point: &Point) {
+    fn attack(&mut self, victim_point: &Vec2) {
         let victim = self.get_point(victim_point);
         let mut new_hit_points = 0;
         let is_dead = match victim {
@@ -245,111 +152,64 @@ impl Game {
             }
             _ => panic!("Unreachable"),
         };
-        let victim = self.get_mut(victim_point);
-        match victim {
-            FieldType::Elf(p) | FieldType::Goblin(p) => {
-                p.hit_points = new_hit_points;
+        if let Some(victim) = self.field.at_mut(*victim_point) {
+            match victim {
+                FieldType::Elf(p) | FieldType::Goblin(p) => {
+                    p.hit_points = new_hit_points;
+                }
+                _ => {}
+            }
+            if is_dead {
+                *victim = FieldType::Empty;
             }
-            _ => {}
-        }
-        if is_dead {
-            *victim = FieldType::Empty;
         }
     }
 
-    fn find_target_elves(&self) -> Vec<Point> {
+    fn find_target_elves(&self) -> Vec<Vec2> {
         let mut result = Vec::new();
         for y in 0..self.field.height() {
             for x in 0..self.field.width() {
                 if let FieldType::Elf(_) = self.get(x, y) {
-                    result.push(Point::new(x, y));
+                    result.push(Vec2::new(x.into(), y.into()));
                 }
             }
         }
         result
     }
 
-    fn find_target_goblins(&self) -> Vec<Point> {
+    fn find_target_goblins(&self) -> Vec<Vec2> {
         let mut result = Vec::new();
         for y in 0..self.field.height() {
             for x in 0..self.field.width() {
                 if let FieldType::Goblin(_) = self.get(x, y) {
-                    result.push(Point::new(x, y));
+                    result.push(Vec2::new(x.into(), y.into()));
                 }
             }
         }
         result
     }
 
-    fn get_adjacent_empty(&self, point: &Point) -> Vec<(usize, Point)> {
+    fn get_adjacent_empty(&self, point: &Vec2) -> Vec<Vec2> {
         self.get_adjacent(point)
-            .iter()
-            .enumerate()
-            .filter(|(_, f)| match self.get_point(f) {
-                FieldType::Empty => true,
-                _ => false,
-            })
-            .map(|(i, p)| (i, p.clone()))
+            .into_iter()
+            .filter(|p| matches!(self.get_point(p), FieldType::Empty))
             .collect()
     }
 
-    fn get_shortest_path(&self, from: &Point, to: &[Point]) -> Option<Point> {
-        let mut shortests: Field<Option<usize>> =
-            Field::new(self.field.width(), self.field.height());
-
-        let mut partials = BinaryHeap::new();
-
-        for (i, p) in self.get_adjacent_empty(from) {
-            partials.push(PartialPath {
-                path_start: p.clone(),
-                next_point: p,
-                dist: 4 + i,
-            });
-        }
-
-        let mut shortest = usize::max_value();
-        let mut result = None;
-
-        while let Some(last) = partials.pop() {
-            if last.dist > shortest {
-                break;
-            }
-            if let Some(s) = *shortests.get(last.next_point.x, last.next_point.y) {
-                if s <= last.dist {
-                    continue;
-                }
-            }
-            *shortests.get_mut(last.next_point.x, last.next_point.y) = Some(last.dist);
-
-            if to.contains(&last.next_point) {
-                if shortest > last.dist {
-                    shortest = last.dist;
-                    result = Some(last.path_start);
-                } else if shortest == last.dist && result != Some(last.path_start) {
-                    panic!("Invalid state");
-                }
-                continue;
-            }
-            let next_dist = last.dist + 4;
-            for (_, p) in self.get_adjacent_empty(&last.next_point) {
-                if let Some(s) = *shortests.get(p.x, p.y) {
-                    if s <= next_dist {
-                        continue;
-                    }
-                }
-
-                partials.push(PartialPath {
-                    path_start: last.path_start.clone(),
-                    next_point: p,
-                    dist: next_dist,
-                });
-            }
-        }
-
-        result
+    /// First step towards the nearest of the points, ties are broken by reading order.
+    fn get_shortest_path(&self, from: &Vec2, to: &[Vec2]) -> Option<Vec2> {
+        let neighbours = |p: &Vec2| self.get_adjacent_empty(p).into_iter().map(|p| (p, 1));
+
+        let target = *search::dijkstra(*from, neighbours, |p| to.contains(p)).goal()?;
+        let from_target = search::dijkstra(target, neighbours, |_| false);
+        self.get_adjacent_empty(from)
+            .into_iter()
+            .filter_map(|p| Some((from_target.distance(&p)?, p)))
+            .min()
+            .map(|(_, p)| p)
     }
 
-    fn handle_player(&mut self, point: &Point) -> bool {
+    fn handle_player(&mut self, point: &Vec2) -> bool {
         if let Some(victim_point) = self.get_victim(point) {
             self.attack(&victim_point);
             return false;
@@ -368,7 +228,7 @@ impl Game {
 
         let target_adjacent_points: Vec<_> = targets
             .iter()
-            .flat_map(|p| p.get_adjacent_points())
+            .flat_map(|p| self.get_adjacent(p))
             .filter(|f| match self.get_point(f) {
                 FieldType::Empty => true,
                 _ => false,
@@ -409,23 +269,14 @@ impl Game {
         count
     }
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        for y in 0..self.field.height() {
-            let line: String = (0..self.field.width())
-                .map(|x| {
-                    match self.get(x, y) {
-                        FieldType::Empty => '.',
-                        FieldType::Wall => '#',
-                        FieldType::Elf(_) => 'E',
-                        FieldType::Goblin(_) => 'G',
-                    }
-                    .to_string()
-                        + " "
-                })
-                .collect();
-            println!("{}", line);
-        }
+    /// Animates the fight with `--visualize`.
+    fn show(&self, caption: &str) {
+        visualize::show_field(&self.field, None, caption, |f| match f {
+            FieldType::Empty => Cell::new('.', Color::Gray),
+            FieldType::Wall => Cell::plain('#'),
+            FieldType::Elf(_) => Cell::new('E', Color::Green),
+            FieldType::Goblin(_) => Cell::new('G', Color::Red),
+        });
     }
 
     fn count_elves(&self) -> usize {
@@ -446,7 +297,7 @@ impl Game {
             for x in 0..self.field.width() {
                 match self.get(x, y) {
                     FieldType::Elf(_) | FieldType::Goblin(_) => {
-                        player_order.push(Point::new(x, y));
+                        player_order.push(Vec2::new(x.into(), y.into()));
                     }
                     _ => {}
                 };
@@ -468,11 +319,10 @@ impl Game {
 }
 
 fn level_1(line: &[String]) -> ACResult<u32> {
-    let mut game = Game::new(line, 3, 3);
+    let mut game = Game::new(parse_map(line)?, 3, 3);
     let mut round = 0;
     loop {
-        // println!("{}", round);
-        // game.print();
+        game.show(&format!("Round {}", round));
         if game.tick() {
             break;
         }
@@ -483,13 +333,15 @@ fn level_1(line: &[String]) -> ACResult<u32> {
 }
 
 fn level_2(line: &[String]) -> ACResult<u32> {
+    let field = parse_map(line)?;
     let goblin_attack = 3;
     let mut power = goblin_attack + 1;
     'outer: loop {
-        let mut game = Game::new(line, power, goblin_attack);
+        let mut game = Game::new(field.clone(), power, goblin_attack);
         let mut round = 0;
         let elve_count = game.count_elves();
         loop {
+            game.show(&format!("Elf attack power {}, round {}", power, round));
             let finished = game.tick();
             if game.count_elves() < elve_count {
                 power += 1;
@@ -504,3 +356,62 @@ fn level_2(line: &[String]) -> ACResult<u32> {
         return Ok(hit_power * round);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(str::to_owned).collect()
+    }
+
+    const EXAMPLE_1: &str = "#######
+#.G...#
+#...EG#
+#.#.#G#
+#..G#E#
+#.....#
+#######";
+
+    const EXAMPLE_2: &str = "#######
+#G..#E#
+#E#E.E#
+#G.##.#
+#...#E#
+#...E.#
+#######";
+
+    const EXAMPLE_3: &str = "#######
+#E..EG#
+#.#G.E#
+#E.##E#
+#G..#.#
+#..E#.#
+#######";
+
+    const EXAMPLE_4: &str = "#########
+#G......#
+#.E.#...#
+#..##..G#
+#...##..#
+#...#...#
+#.G...G.#
+#.....G.#
+#########";
+
+    #[test]
+    fn run_level_1_examples() {
+        assert_eq!(level_1(&lines(EXAMPLE_1)), Ok(27730));
+        assert_eq!(level_1(&lines(EXAMPLE_2)), Ok(36334));
+        assert_eq!(level_1(&lines(EXAMPLE_3)), Ok(39514));
+        assert_eq!(level_1(&lines(EXAMPLE_4)), Ok(18740));
+    }
+
+    #[test]
+    fn run_level_2_examples() {
+        assert_eq!(level_2(&lines(EXAMPLE_1)), Ok(4988));
+        assert_eq!(level_2(&lines(EXAMPLE_2)), Ok(29064));
+        assert_eq!(level_2(&lines(EXAMPLE_3)), Ok(31284));
+        assert_eq!(level_2(&lines(EXAMPLE_4)), Ok(1140));
+    }
+}
@@ -1,27 +1,27 @@
-use self::interpreter_utils::{Instruction, Opcode, Registers, ALL_OPCODES};
-use crate::errors::{ACResult, Error};
-use nom::{complete, do_parse, many1, many_m_n, map, named, opt, tag, take_while1, terminated};
+use crate::answer::Answer;
+use crate::elfcode::{Instruction, Opcode, Registers, ALL_OPCODES};
+use crate::errors::{ACResult, Context, Error};
+use nom::{complete, do_parse, many1, many_m_n, map_res, named, opt, tag, take_while1, terminated};
 use std::io::BufRead;
 use std::io::Read;
 
-pub mod interpreter_utils;
-
-pub fn get_result<T: Read + BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: Read + BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&parse_line(data)?).map(|r| r.to_string()),
-        2 => level_2(&parse_line(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&parse_line(data)?).map(Answer::from),
+        2 => level_2(&parse_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
 fn parse_line<T: Read>(mut data: T) -> ACResult<Input> {
     let mut contents = String::new();
     data.read_to_string(&mut contents)
-        .map_err(|_| Error::new_str("Failed to read data"))?;
+        .context("Failed to read data")?;
 
-    parse_input(&contents)
-        .map(|x| x.1)
-        .map_err(|_e| Error::new("Failed to parse input".to_owned()))
+    crate::parsing::parse_all(&contents, parse_input)
 }
 
 const REGISTER_COUNT: usize = 4;
@@ -59,11 +59,11 @@ struct Input {
 }
 
 named!(parse_number<&str, u8>,
-    complete!(map!(take_while1!(|c: char| c.is_numeric()), |c| c.to_string().parse().unwrap()))
+    complete!(map_res!(take_while1!(|c: char| c.is_numeric()), |c: &str| c.parse()))
 );
 
 named!(parse_number_u64<&str, u64>,
-    complete!(map!(take_while1!(|c: char| c.is_numeric()), |c| c.to_string().parse().unwrap()))
+    complete!(map_res!(take_while1!(|c: char| c.is_numeric()), |c: &str| c.parse()))
 );
 
 named!(parse_register_or_value<&str, u64>,
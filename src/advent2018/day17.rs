@@ -1,12 +1,20 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
-use nom::{alt, complete, do_parse, map, named, tag, take_while1};
+use crate::export;
+use crate::geometry::{Direction, Vec2};
+use crate::visualize::{self, Cell, Color};
+use nom::character::complete::digit1;
+use nom::{alt, complete, do_parse, map_res, named, tag};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -18,51 +26,24 @@ enum Type {
     WaterStill,
 }
 
-#[derive(Clone)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-impl Point {
-    fn down(&self) -> Self {
-        Point {
-            x: self.x,
-            y: self.y + 1,
-        }
-    }
-
-    fn left(&self) -> Self {
-        Point {
-            x: self.x - 1,
-            y: self.y,
-        }
-    }
-    fn right(&self) -> Self {
-        Point {
-            x: self.x + 1,
-            y: self.y,
-        }
-    }
-}
-
 struct Field {
     fields: Vec<Type>,
-    min_x: usize,
-    min_y: usize,
-    max_x: usize,
-    max_y: usize,
-    min_y_orig: usize,
-    fountain_position: Point,
+    min_x: i64,
+    min_y: i64,
+    max_x: i64,
+    max_y: i64,
+    min_y_orig: i64,
+    fountain_position: Vec2,
 }
 
 impl Field {
-    fn new(scans: &[Scan], fountain_position: &Point) -> Self {
+    fn new(scans: &[Scan], fountain_position: Vec2) -> Self {
         let min_x = scans.iter().min_by_key(|s| s.x_from).unwrap().x_from - 1;
         let min_y_orig = scans.iter().min_by_key(|s| s.y_from).unwrap().y_from;
         let min_y = if min_y_orig > 0 { 0 } else { min_y_orig };
         let max_x = scans.iter().max_by_key(|s| s.x_to).unwrap().x_to + 1;
         let max_y = scans.iter().max_by_key(|s| s.y_to).unwrap().y_to;
-        let fields = vec![Type::Sand; (max_x + 1 - min_x) * (max_y + 1 - min_y)];
+        let fields = vec![Type::Sand; ((max_x + 1 - min_x) * (max_y + 1 - min_y)) as usize];
         let mut field = Field {
             min_x,
             min_y_orig,
@@ -70,7 +51,7 @@ impl Field {
             max_x,
             max_y,
             fields,
-            fountain_position: fountain_position.clone(),
+            fountain_position,
         };
         for s in scans.iter() {
             for x in s.x_from..=s.x_to {
@@ -82,103 +63,120 @@ impl Field {
         field
     }
 
-    fn get_mut(&mut self, x: usize, y: usize) -> &mut Type {
-        let ind = (x - self.min_x) + (y - self.min_y) * (self.max_x + 1 - self.min_x);
+    fn index(&self, x: i64, y: i64) -> usize {
+        ((x - self.min_x) + (y - self.min_y) * (self.max_x + 1 - self.min_x)) as usize
+    }
+
+    fn get_mut(&mut self, x: i64, y: i64) -> &mut Type {
+        let ind = self.index(x, y);
         &mut self.fields[ind]
     }
 
-    fn get(&self, x: usize, y: usize) -> &Type {
-        &self.fields[(x - self.min_x) + (y - self.min_y) * (self.max_x + 1 - self.min_x)]
+    fn get(&self, x: i64, y: i64) -> &Type {
+        &self.fields[self.index(x, y)]
     }
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        for y in self.min_y_orig..=self.max_y {
-            let line: String = (self.min_x..=self.max_x)
-                .map(|x| {
-                    match self.get(x, y) {
-                        Type::Sand => '.',
-                        Type::Clay => '#',
-                        Type::Water => '|',
-                        Type::WaterStill => '~',
-                    }
-                    .to_string()
-                        + " "
-                })
-                .collect();
-            println!("{}", line);
-        }
+    /// Animates the flowing water with `--visualize`, following the water at the position, and
+    /// exports the frame with `--export`.
+    fn show(&self, position: Vec2) {
+        let focus = (
+            (position.x - self.min_x) as u32,
+            (position.y - self.min_y) as u32,
+        );
+        visualize::show(
+            (self.max_x + 1 - self.min_x) as u32,
+            (self.max_y + 1 - self.min_y) as u32,
+            Some(focus),
+            &format!("Water at {}", position),
+            |x, y| match self.get(i64::from(x) + self.min_x, i64::from(y) + self.min_y) {
+                Type::Sand => Cell::new('.', Color::Gray),
+                Type::Clay => Cell::new('#', Color::Yellow),
+                Type::Water => Cell::new('|', Color::Cyan),
+                Type::WaterStill => Cell::new('~', Color::Blue),
+            },
+        );
+        export::frame(
+            (self.max_x + 1 - self.min_x) as u32,
+            (self.max_y + 1 - self.min_y) as u32,
+            |x, y| match self.get(i64::from(x) + self.min_x, i64::from(y) + self.min_y) {
+                Type::Sand => [194, 178, 128],
+                Type::Clay => [139, 69, 19],
+                Type::Water => [100, 180, 255],
+                Type::WaterStill => [0, 90, 200],
+            },
+        );
     }
 
-    fn is_empty(&self, position: &Point) -> bool {
+    fn is_empty(&self, position: Vec2) -> bool {
         let t = self.get(position.x, position.y);
         *t == Type::Sand || *t == Type::Water
     }
 
-    fn is_any_water(&self, x: usize, y: usize) -> bool {
+    fn is_any_water(&self, x: i64, y: i64) -> bool {
         let t = self.get(x, y);
         *t == Type::WaterStill || *t == Type::Water
     }
 
-    fn is_still_water(&self, x: usize, y: usize) -> bool {
+    fn is_still_water(&self, x: i64, y: i64) -> bool {
         let t = self.get(x, y);
         *t == Type::WaterStill
     }
 
     fn flow_water(&mut self) {
-        self.down(&self.fountain_position.clone());
+        self.down(self.fountain_position);
     }
 
-    fn down(&mut self, pos: &Point) {
+    fn down(&mut self, pos: Vec2) {
         // Go down as far as possible
         let mut stack = Vec::new();
-        let mut current_pos = pos.clone();
+        let mut current_pos = pos;
         loop {
-            if self.is_empty(&current_pos) {
+            if self.is_empty(current_pos) {
                 *self.get_mut(current_pos.x, current_pos.y) = Type::Water;
                 if current_pos.y == self.max_y {
                     // Reached bottom, finished
                     return;
                 }
-                let next_pos = current_pos.down();
+                let next_pos = current_pos + Direction::South.offset();
                 stack.push(current_pos);
                 current_pos = next_pos;
             } else {
                 break;
             }
         }
+        self.show(current_pos);
         while let Some(pos) = stack.pop() {
             // Below this point is clay or fixed water
             let mut left_wall_x = None;
             let mut right_wall_x = None;
-            let mut current_pos = pos.clone();
+            let mut current_pos = pos;
             loop {
-                current_pos = current_pos.left();
-                if !self.is_empty(&current_pos) {
+                current_pos += Direction::West.offset();
+                if !self.is_empty(current_pos) {
                     left_wall_x = Some(current_pos.x);
                     break;
                 }
                 *self.get_mut(current_pos.x, current_pos.y) = Type::Water;
-                let below = current_pos.down();
-                if self.is_empty(&below) {
-                    self.down(&below);
-                    if self.is_empty(&below) {
+                let below = current_pos + Direction::South.offset();
+                if self.is_empty(below) {
+                    self.down(below);
+                    if self.is_empty(below) {
                         // Still empty, flowing down ...
                         break;
                     }
                 }
             }
             loop {
-                current_pos = current_pos.right();
-                if !self.is_empty(&current_pos) {
+                current_pos += Direction::East.offset();
+                if !self.is_empty(current_pos) {
                     right_wall_x = Some(current_pos.x);
                     break;
                 }
                 *self.get_mut(current_pos.x, current_pos.y) = Type::Water;
-                let below = current_pos.down();
-                if self.is_empty(&below) {
-                    self.down(&below);
-                    if self.is_empty(&below) {
+                let below = current_pos + Direction::South.offset();
+                if self.is_empty(below) {
+                    self.down(below);
+                    if self.is_empty(below) {
                         // Still empty, flowing down ...
                         break;
                     }
@@ -189,6 +187,7 @@ impl Field {
                     for x in (left_x + 1)..right_x {
                         *self.get_mut(x, pos.y) = Type::WaterStill;
                     }
+                    self.show(pos);
                     continue;
                 }
             }
@@ -221,14 +220,14 @@ impl Field {
 }
 
 struct Scan {
-    x_from: usize,
-    x_to: usize,
-    y_from: usize,
-    y_to: usize,
+    x_from: i64,
+    x_to: i64,
+    y_from: i64,
+    y_to: i64,
 }
 
-named!(parse_number<&str, usize>,
-    complete!(map!(take_while1!(|c: char| c.is_numeric()), |c| c.to_string().parse().unwrap()))
+named!(parse_number<&str, i64>,
+    map_res!(digit1, |c: &str| c.parse())
 );
 
 named!(parse_line<&str, Scan>,
@@ -256,25 +255,43 @@ named!(parse_line<&str, Scan>,
 );
 
 fn level_1(lines: &[String]) -> ACResult<usize> {
-    let scans: Vec<Scan> = lines
-        .iter()
-        .map(|l| parse_line(&(l.to_string() + "\n")).unwrap().1)
-        .collect();
-    let mut field = Field::new(&scans, &Point { x: 500, y: 0 });
-    // field.print();
+    let scans = crate::parsing::parse_lines(lines, parse_line)?;
+    let mut field = Field::new(&scans, Vec2::new(500, 0));
     field.flow_water();
-    // field.print();
     Ok(field.count_any_water())
 }
 
 fn level_2(lines: &[String]) -> ACResult<usize> {
-    let scans: Vec<Scan> = lines
-        .iter()
-        .map(|l| parse_line(&(l.to_string() + "\n")).unwrap().1)
-        .collect();
-    let mut field = Field::new(&scans, &Point { x: 500, y: 0 });
-    // field.print();
+    let scans = crate::parsing::parse_lines(lines, parse_line)?;
+    let mut field = Field::new(&scans, Vec2::new(500, 0));
     field.flow_water();
-    // field.print();
     Ok(field.count_still_water())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "x=495, y=2..7
+y=7, x=495..501
+x=501, y=3..7
+x=498, y=2..4
+x=506, y=1..2
+x=498, y=10..13
+x=504, y=10..13
+y=13, x=498..504";
+
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn run_level_1_examples() {
+        assert_eq!(level_1(&lines(EXAMPLE)), Ok(57));
+    }
+
+    #[test]
+    fn run_level_2_examples() {
+        assert_eq!(level_2(&lines(EXAMPLE)), Ok(29));
+    }
+}
@@ -1,12 +1,19 @@
+use crate::answer::Answer;
+use crate::automaton::{FieldAutomaton, Neighbourhood};
 use crate::errors::{ACResult, Error};
-use std::collections::HashMap;
+use crate::export;
+use crate::utils::Field;
+use crate::visualize::{self, Cell, Color};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_all(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_all(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -17,191 +24,97 @@ enum Type {
     Lumberyard,
 }
 
-#[derive(Hash, PartialEq, Eq, Clone, Debug)]
-struct Field {
-    field: Vec<Type>,
-    width: usize,
-    height: usize,
+fn parse_field(input: &str) -> ACResult<Field<Type>> {
+    Field::parse(input, |c| match c {
+        '.' => Ok(Type::Open),
+        '|' => Ok(Type::Tree),
+        '#' => Ok(Type::Lumberyard),
+        _ => Err(Error::Parse("an acre (`.`, `|` or `#`)".to_owned())),
+    })
 }
 
-impl Field {
-    fn empty(width: usize, height: usize) -> Field {
-        Field {
-            width,
-            height,
-            field: vec![Type::Open; width * height],
-        }
-    }
-
-    fn new(lines: &[String]) -> Field {
-        let width = lines.len();
-        let height = lines[0].len();
-
-        let field = lines
-            .iter()
-            .flat_map(|l| {
-                l.chars().map(|c| match c {
-                    '.' => Type::Open,
-                    '|' => Type::Tree,
-                    '#' => Type::Lumberyard,
-                    _ => panic!("invalid input char"),
-                })
-            })
-            .collect();
-
-        Field {
-            field,
-            width,
-            height,
-        }
-    }
-    fn get(&self, x: usize, y: usize) -> &Type {
-        &self.field[x + y * self.width]
-    }
-    fn get_mut(&mut self, x: usize, y: usize) -> &mut Type {
-        &mut self.field[x + y * self.width]
-    }
-    fn get_opt(&self, x: usize, y: usize) -> Option<&Type> {
-        if x >= self.width || y >= self.height {
-            return None;
-        }
-        self.field.get(x + y * self.width)
-    }
-
-    fn count(&self, x: usize, y: usize, tree: &mut usize, lumber: &mut usize, open: &mut usize) {
-        if let Some(f) = self.get_opt(x, y) {
-            match f {
-                Type::Lumberyard => *lumber += 1,
-                Type::Tree => *tree += 1,
-                Type::Open => *open += 1,
+fn get_next(acre: &Type, neighbours: &[&Type]) -> Type {
+    let count = |t: Type| neighbours.iter().filter(|n| ***n == t).count();
+    let tree = count(Type::Tree);
+    let lumber = count(Type::Lumberyard);
+
+    match acre {
+        Type::Open => {
+            if tree >= 3 {
+                Type::Tree
+            } else {
+                Type::Open
             }
         }
-    }
-
-    fn get_next(&self, x: usize, y: usize) -> Type {
-        let mut tree = 0;
-        let mut lumber = 0;
-        let mut open = 0;
-        self.count(x + 1, y, &mut tree, &mut lumber, &mut open);
-        self.count(x + 1, y + 1, &mut tree, &mut lumber, &mut open);
-        if x > 0 {
-            self.count(x - 1, y, &mut tree, &mut lumber, &mut open);
-            self.count(x - 1, y + 1, &mut tree, &mut lumber, &mut open);
-            if y > 0 {
-                self.count(x - 1, y - 1, &mut tree, &mut lumber, &mut open);
+        Type::Tree => {
+            if lumber >= 3 {
+                Type::Lumberyard
+            } else {
+                Type::Tree
             }
         }
-        if y > 0 {
-            self.count(x + 1, y - 1, &mut tree, &mut lumber, &mut open);
-            self.count(x, y - 1, &mut tree, &mut lumber, &mut open);
-        }
-        self.count(x, y + 1, &mut tree, &mut lumber, &mut open);
-
-        match self.get(x, y) {
-            Type::Open => {
-                if tree >= 3 {
-                    Type::Tree
-                } else {
-                    Type::Open
-                }
-            }
-            Type::Tree => {
-                if lumber >= 3 {
-                    Type::Lumberyard
-                } else {
-                    Type::Tree
-                }
-            }
-            Type::Lumberyard => {
-                if lumber >= 1 && tree >= 1 {
-                    Type::Lumberyard
-                } else {
-                    Type::Open
-                }
+        Type::Lumberyard => {
+            if lumber >= 1 && tree >= 1 {
+                Type::Lumberyard
+            } else {
+                Type::Open
             }
         }
     }
-
-    fn count_resources(&self) -> usize {
-        let lumberyard = self
-            .field
-            .iter()
-            .filter(|f| **f == Type::Lumberyard)
-            .count();
-        let lumber = self.field.iter().filter(|f| **f == Type::Tree).count();
-
-        lumberyard * lumber
-    }
-
-    #[allow(dead_code)]
-    fn print(&self) {
-        for y in 0..self.height {
-            let line: String = (0..self.width)
-                .map(|x| {
-                    match self.get(x, y) {
-                        Type::Tree => '|',
-                        Type::Open => '.',
-                        Type::Lumberyard => '#',
-                    }
-                    .to_string()
-                })
-                .collect();
-            println!("{}", line);
-        }
-    }
 }
 
-fn level_1(line: &[String]) -> ACResult<usize> {
-    let mut field = Field::new(line);
-    let mut field2 = Field::empty(field.width, field.height);
-
-    let mut c = &mut field;
-    let mut n = &mut field2;
-
-    for _ in 0..10 {
-        for x in 0..c.width {
-            for y in 0..c.height {
-                *n.get_mut(x, y) = c.get_next(x, y);
-            }
-        }
+fn count_resources(field: &Field<Type>) -> usize {
+    let count = |t: Type| field.iter().filter(|f| **f == t).count();
 
-        std::mem::swap(&mut c, &mut n)
-    }
-    // c.print();
-    Ok(c.count_resources())
+    count(Type::Lumberyard) * count(Type::Tree)
 }
 
-fn level_2(line: &[String]) -> ACResult<usize> {
-    let mut field = Field::new(line);
-    let mut field2 = Field::empty(field.width, field.height);
-
-    let mut c = &mut field;
-    let mut n = &mut field2;
+/// Animates the area with `--visualize` and exports the frame with `--export`.
+fn show(field: &Field<Type>, minute: u64) {
+    visualize::show_field(field, None, &format!("Minute {}", minute), |t| match t {
+        Type::Open => Cell::new('.', Color::Gray),
+        Type::Tree => Cell::new('|', Color::Green),
+        Type::Lumberyard => Cell::new('#', Color::Yellow),
+    });
+    export::frame_field(field, |t| match t {
+        Type::Open => [222, 205, 160],
+        Type::Tree => [34, 139, 34],
+        Type::Lumberyard => [139, 90, 43],
+    });
+}
 
-    let total_iterations: usize = 1_000_000_000;
+fn level_1(input: &str) -> ACResult<usize> {
+    let mut area = FieldAutomaton::new(parse_field(input)?, Neighbourhood::Moore, get_next);
 
-    let mut knowns = HashMap::new();
-    let mut known_counts = Vec::new();
+    area.skip_to(10, show)?;
+    Ok(count_resources(area.state()))
+}
 
-    for i in 0..total_iterations {
-        let pp = c.clone();
-        if let Some(offset) = knowns.get(&pp) {
-            let diff = i - offset;
-            let index = (total_iterations - offset) % diff + offset;
-            return Ok(known_counts[index]);
-        }
-        knowns.insert(pp, i);
-        known_counts.push(c.count_resources());
+fn level_2(input: &str) -> ACResult<usize> {
+    let mut area = FieldAutomaton::new(parse_field(input)?, Neighbourhood::Moore, get_next);
 
-        for x in 0..c.width {
-            for y in 0..c.height {
-                *n.get_mut(x, y) = c.get_next(x, y);
-            }
-        }
+    area.skip_to(1_000_000_000, show)?;
+    Ok(count_resources(area.state()))
+}
 
-        // Switch fields
-        std::mem::swap(&mut c, &mut n);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = ".#.#...|#.
+.....#|##|
+.|..|...#.
+..|#.....#
+#.#|||#|#|
+...#.||...
+.|....|...
+||...#|.#|
+|.||||..|.
+...#.|..|.
+";
+
+    #[test]
+    fn run_level_1_examples() {
+        assert_eq!(level_1(EXAMPLE), Ok(1147));
     }
-
-    Ok(c.count_resources())
 }
@@ -1,55 +1,45 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
-use nom::{alt, do_parse, many0, many1, map, named, opt, tag, take_while, take_while1};
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use crate::geometry::{Direction, Vec2};
+use crate::search::{self, Paths};
+use crate::utils::Field;
+use nom::{alt, do_parse, many0, many1, map, map_res, named, opt, tag, take_while, take_while1};
+use std::convert::TryFrom;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_line(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_line(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_line(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum Dir {
-    North,
-    East,
-    South,
-    West,
+fn parse_directions(directions: &str) -> ACResult<Vec<Direction>> {
+    directions.chars().map(Direction::try_from).collect()
 }
 
-impl From<char> for Dir {
-    fn from(c: char) -> Dir {
-        match c {
-            'N' => Dir::North,
-            'E' => Dir::East,
-            'S' => Dir::South,
-            'W' => Dir::West,
-            _ => panic!("Invalid direction"),
-        }
-    }
-}
-
-named!(parse_dir<&str, Vec<Dir>>,
-    map!(
+named!(parse_dir<&str, Vec<Direction>>,
+    map_res!(
         take_while!(|c| { c=='N' || c=='E' || c=='S' || c=='W' }),
-        |l| l.chars().map(|c| c.into()).collect()
+        parse_directions
     )
 );
 
-named!(parse_dir1<&str, Vec<Dir>>,
-    map!(
+named!(parse_dir1<&str, Vec<Direction>>,
+    map_res!(
         take_while1!(|c| { c=='N' || c=='E' || c=='S' || c=='W' }),
-        |l| l.chars().map(|c| c.into()).collect()
+        parse_directions
     )
 );
 
 enum Dirs {
     Alt(Vec<Dirs>),
     List(Vec<Dirs>),
-    Some(Vec<Dir>),
+    Some(Vec<Direction>),
 }
 
 named!(parse_alt<&str, Dirs>,
@@ -86,39 +76,8 @@ named!(parse_input<&str, Dirs>,
     )
 );
 
-#[derive(Clone, PartialEq, Eq)]
-struct Point {
-    x: isize,
-    y: isize,
-}
-
-impl Point {
-    fn down(&self) -> Self {
-        Point {
-            x: self.x,
-            y: self.y + 1,
-        }
-    }
-    fn up(&self) -> Self {
-        Point {
-            x: self.x,
-            y: self.y - 1,
-        }
-    }
-
-    fn left(&self) -> Self {
-        Point {
-            x: self.x - 1,
-            y: self.y,
-        }
-    }
-    fn right(&self) -> Self {
-        Point {
-            x: self.x + 1,
-            y: self.y,
-        }
-    }
-}
+/// Width and height of the map, the start is in the middle.
+const SIZE: i64 = 1000;
 
 #[derive(Debug, Clone, PartialEq)]
 enum Type {
@@ -128,244 +87,138 @@ enum Type {
 }
 
 #[allow(dead_code)]
-fn print_field(field: &[Vec<Type>]) {
-    for l in field.iter() {
-        let mut skip = true;
-        let res: String = l
+fn print_field(field: &Field<Type>) {
+    for row in field.rows() {
+        if row.iter().all(|c| *c == Type::Wall) {
+            continue;
+        }
+        let res: String = row
             .iter()
             .map(|c| match c {
-                Type::Wall => "#",
-                Type::Room => {
-                    skip = false;
-                    "."
-                }
-                Type::Door => {
-                    skip = false;
-                    "|"
-                }
+                Type::Wall => '#',
+                Type::Room => '.',
+                Type::Door => '|',
             })
             .collect();
-        if skip {
-            continue;
-        }
         println!("{}", res);
     }
 }
 
-#[derive(PartialEq, Eq)]
-struct PartialPath {
-    next_point: Point,
-    // shortest path to this point
-    dist: usize,
-}
-
-impl PartialOrd for PartialPath {
-    fn partial_cmp(&self, other: &PartialPath) -> Option<Ordering> {
-        Some(self.cmp(&other))
-    }
-}
-
-impl Ord for PartialPath {
-    fn cmp(&self, other: &PartialPath) -> Ordering {
-        match self.dist.cmp(&other.dist) {
-            Ordering::Greater => Ordering::Less,
-            Ordering::Less => Ordering::Greater,
-            Ordering::Equal => Ordering::Equal,
-        }
-    }
-}
-
-fn get_longest_shortest_path_room(field: &[Vec<Type>], pos: &Point) -> usize {
-    let shortests = get_shortest_paths(field, pos);
-
-    shortests
-        .iter()
-        .map(|l| {
-            l.iter()
-                .map(|x| match x {
-                    None => 0,
-                    Some(s) => *s,
-                })
-                .max()
-                .unwrap()
-        })
-        .max()
-        .unwrap()
-}
-
-fn get_shortest_paths(field: &[Vec<Type>], pos: &Point) -> Vec<Vec<Option<usize>>> {
-    let mut shortests = vec![vec![None; field.len()]; field.len()];
-
-    *get_mut(&mut shortests, pos) = Some(0);
-
-    let mut partials = BinaryHeap::new();
-    partials.push(PartialPath {
-        next_point: pos.clone(),
-        dist: 0,
-    });
-
-    while let Some(part) = partials.pop() {
-        let p = part.next_point.up();
-        let p2 = p.up();
-        if *get(field, &p) == Type::Door && get_mut(&mut shortests, &p2).is_none() {
-            let dist = part.dist + 1;
-            *get_mut(&mut shortests, &p2) = Some(dist);
-            partials.push(PartialPath {
-                next_point: p2,
-                dist,
-            })
-        }
-
-        let p = part.next_point.down();
-        let p2 = p.down();
-        if *get(field, &p) == Type::Door && get_mut(&mut shortests, &p2).is_none() {
-            let dist = part.dist + 1;
-            *get_mut(&mut shortests, &p2) = Some(dist);
-            partials.push(PartialPath {
-                next_point: p2,
-                dist,
-            })
-        }
-
-        let p = part.next_point.left();
-        let p2 = p.left();
-        if *get(field, &p) == Type::Door && get_mut(&mut shortests, &p2).is_none() {
-            let dist = part.dist + 1;
-            *get_mut(&mut shortests, &p2) = Some(dist);
-            partials.push(PartialPath {
-                next_point: p2,
-                dist,
-            })
-        }
-
-        let p = part.next_point.right();
-        let p2 = p.right();
-        if *get(field, &p) == Type::Door && get_mut(&mut shortests, &p2).is_none() {
-            let dist = part.dist + 1;
-            *get_mut(&mut shortests, &p2) = Some(dist);
-            partials.push(PartialPath {
-                next_point: p2,
-                dist,
+fn get_shortest_paths(field: &Field<Type>, pos: Vec2) -> Paths<Vec2, usize> {
+    search::bfs(
+        pos,
+        |&room| {
+            Direction::ALL.iter().filter_map(move |dir| {
+                let door = room + dir.offset();
+                if field.at(door) == Some(&Type::Door) {
+                    Some(door + dir.offset())
+                } else {
+                    None
+                }
             })
-        }
-    }
-    shortests
+        },
+        |_| false,
+    )
 }
 
-fn get_next_positions(field: &mut Vec<Vec<Option<Type>>>, pos: &Point, dirs: &Dirs) -> Vec<Point> {
+fn get_next_positions(
+    field: &mut Field<Option<Type>>,
+    pos: Vec2,
+    dirs: &Dirs,
+) -> ACResult<Vec<Vec2>> {
     match dirs {
-        Dirs::Alt(alts) => alts
-            .iter()
-            .flat_map(|alt| get_next_positions(field, pos, alt))
-            .collect(),
-        Dirs::List(list) => list.iter().fold(vec![pos.clone()], |prev, list| {
-            prev.iter()
-                .flat_map(|p| get_next_positions(field, p, list))
-                .collect()
+        Dirs::Alt(alts) => {
+            let mut positions = Vec::new();
+            for alt in alts {
+                positions.extend(get_next_positions(field, pos, alt)?);
+            }
+            Ok(positions)
+        }
+        Dirs::List(list) => list.iter().try_fold(vec![pos], |prev, list| {
+            let mut positions = Vec::new();
+            for p in prev {
+                positions.extend(get_next_positions(field, p, list)?);
+            }
+            Ok(positions)
         }),
-        Dirs::Some(dirs) => vec![get_next_position(field, pos, dirs)],
+        Dirs::Some(dirs) => Ok(vec![get_next_position(field, pos, dirs)?]),
     }
 }
 
-fn get_mut<'a, T>(field: &'a mut [Vec<T>], pos: &Point) -> &'a mut T {
-    let offset = field.len() as isize / 2;
-    &mut field[(pos.y + offset) as usize][(pos.x + offset) as usize]
+fn set(field: &mut Field<Option<Type>>, pos: Vec2, field_type: Type) -> ACResult<()> {
+    let f = field
+        .at_mut(pos)
+        .ok_or_else(|| Error::new_str("The rooms don't fit on the map"))?;
+    *f = Some(field_type);
+    Ok(())
 }
 
-fn get<'a, T>(field: &'a [Vec<T>], pos: &Point) -> &'a T {
-    let offset = field.len() as isize / 2;
-    &field[(pos.y + offset) as usize][(pos.x + offset) as usize]
-}
-
-fn get_next_position(field: &mut Vec<Vec<Option<Type>>>, pos: &Point, dirs: &[Dir]) -> Point {
-    let mut pos = pos.clone();
+fn get_next_position(
+    field: &mut Field<Option<Type>>,
+    pos: Vec2,
+    dirs: &[Direction],
+) -> ACResult<Vec2> {
+    let mut pos = pos;
     for d in dirs {
-        match d {
-            Dir::North => {
-                let next_door = pos.up();
-                let next_pos = next_door.up();
-                *get_mut(field, &next_door) = Some(Type::Door);
-                *get_mut(field, &next_pos) = Some(Type::Room);
-                pos = next_pos;
-            }
-            Dir::East => {
-                let next_door = pos.right();
-                let next_pos = next_door.right();
-                *get_mut(field, &next_door) = Some(Type::Door);
-                *get_mut(field, &next_pos) = Some(Type::Room);
-                pos = next_pos;
-            }
-            Dir::South => {
-                let next_door = pos.down();
-                let next_pos = next_door.down();
-                *get_mut(field, &next_door) = Some(Type::Door);
-                *get_mut(field, &next_pos) = Some(Type::Room);
-                pos = next_pos;
-            }
-            Dir::West => {
-                let next_door = pos.left();
-                let next_pos = next_door.left();
-                *get_mut(field, &next_door) = Some(Type::Door);
-                *get_mut(field, &next_pos) = Some(Type::Room);
-                pos = next_pos;
-            }
-        }
+        let next_door = pos + d.offset();
+        let next_pos = next_door + d.offset();
+        set(field, next_door, Type::Door)?;
+        set(field, next_pos, Type::Room)?;
+        pos = next_pos;
     }
-    pos
+    Ok(pos)
 }
 
-fn discover_map(input: &Dirs, start: &Point) -> Vec<Vec<Type>> {
-    let size = 1000;
-    let mut field = vec![vec![None; size]; size];
+fn discover_map(input: &Dirs, start: Vec2) -> ACResult<Field<Type>> {
+    let mut field = Field::new(SIZE as u32, SIZE as u32);
 
-    *get_mut(&mut field, &start) = Some(Type::Room);
+    set(&mut field, start, Type::Room)?;
 
-    let _end_points = get_next_positions(&mut field, &start, &input);
+    let _end_points = get_next_positions(&mut field, start, &input)?;
 
     // Make all unknown fields into a wall
-    field
-        .into_iter()
-        .map(|l| {
-            l.into_iter()
-                .map(|c| match c {
-                    None => Type::Wall,
-                    Some(x) => x,
-                })
-                .collect()
-        })
-        .collect()
+    Ok(field.map(|c| c.clone().unwrap_or(Type::Wall)))
 }
 
 fn level_1(line: &str) -> ACResult<usize> {
-    let input = parse_input(line).unwrap().1;
-    let start = Point { x: 0, y: 0 };
+    let input = crate::parsing::parse_all(line, parse_input)?;
+    let start = Vec2::new(SIZE / 2, SIZE / 2);
 
-    let field = discover_map(&input, &start);
+    let field = discover_map(&input, start)?;
 
-    let dist = get_longest_shortest_path_room(&field, &start);
+    let dist = get_shortest_paths(&field, start)
+        .distances()
+        .map(|(_, dist)| dist)
+        .max()
+        .unwrap_or(0);
 
     Ok(dist)
 }
 
 fn level_2(line: &str) -> ACResult<usize> {
-    let input = parse_input(line).unwrap().1;
-    let start = Point { x: 0, y: 0 };
+    let input = crate::parsing::parse_all(line, parse_input)?;
+    let start = Vec2::new(SIZE / 2, SIZE / 2);
 
-    let field = discover_map(&input, &start);
+    let field = discover_map(&input, start)?;
 
-    let shortests = get_shortest_paths(&field, &start);
+    let shortests = get_shortest_paths(&field, start);
 
-    let sum: usize = shortests
-        .iter()
-        .map(|l| {
-            l.iter()
-                .filter(|x| match x {
-                    None => false,
-                    Some(s) => *s >= 1000,
-                })
-                .count()
-        })
-        .sum();
+    let sum = shortests
+        .distances()
+        .filter(|(_, dist)| *dist >= 1000)
+        .count();
 
     Ok(sum)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_level_1_examples() {
+        assert_eq!(level_1("^WNE$"), Ok(3));
+        assert_eq!(level_1("^ENWWW(NEEE|SSE(EE|N))$"), Ok(10));
+        assert_eq!(level_1("^ENNWSWW(NEWS|)SSSEEN(WNSE|)EE(SWEN|)NNN$"), Ok(18));
+    }
+}
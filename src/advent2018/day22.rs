@@ -1,58 +1,54 @@
-use crate::errors::{ACResult, Error};
+use crate::answer::Answer;
+use crate::errors::{ACResult, Context, Error};
+use crate::export;
+use crate::geometry::Vec2;
+use crate::search;
 use crate::utils::Field;
-use nom::{complete, do_parse, map, named, preceded, tag, take_while1};
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
-use std::collections::HashMap;
+use nom::{complete, do_parse, map_res, named, preceded, tag, take_while1};
+use std::collections::HashSet;
 use std::io::BufRead;
 use std::io::Read;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&parse_line(data)?).map(|r| r.to_string()),
-        2 => level_2(&parse_line(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&parse_line(data)?).map(Answer::from),
+        2 => level_2(&parse_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
+/// Only parses the input, for benchmarking.
+pub fn parse<T: BufRead>(data: T) -> ACResult<()> {
+    parse_line(data).map(|_| ())
+}
+
 fn parse_line<T: Read>(mut data: T) -> ACResult<Input> {
     let mut contents = String::new();
     data.read_to_string(&mut contents)
-        .map_err(|_| Error::new_str("Failed to read data"))?;
+        .context("Failed to read data")?;
 
-    parse_input(&contents)
-        .map(|x| x.1)
-        .map_err(|_e| Error::new("Failed to parse input".to_owned()))
+    crate::parsing::parse_all(&contents, parse_input)
 }
 
 #[derive(Debug)]
 struct Input {
     depth: u32,
-    target: Point,
-}
-
-#[derive(Debug, PartialEq, Eq, Clone)]
-struct Point {
-    x: u32,
-    y: u32,
-}
-
-impl Point {
-    fn new(x: u32, y: u32) -> Self {
-        Point { x, y }
-    }
+    target: Vec2,
 }
 
 named!(parse_number_u32<&str, u32>,
-    complete!(map!(take_while1!(|c: char| c.is_numeric()), |c| c.to_string().parse().unwrap()))
+    complete!(map_res!(take_while1!(|c: char| c.is_numeric()), |c: &str| c.parse()))
 );
 
-named!(parse_point<&str, Point>,
+named!(parse_point<&str, Vec2>,
   do_parse!(
     x: parse_number_u32 >>
     tag!(",") >>
     y: parse_number_u32 >>
-    (Point {x, y})
+    (Vec2::new(x.into(), y.into()))
   )
 );
 
@@ -65,55 +61,22 @@ named!(parse_input<&str, Input>,
     )
 );
 
-impl Field<Type> {
-    #[allow(dead_code)]
-    fn print(&self, max_x: u32, max_y: u32) {
-        for y in 0..=max_y {
-            let line: String = (0..=max_x)
-                .map(|x| {
-                    "  ".to_owned()
-                        + &match self.get(x, y) {
-                            Type::Rocky => '.',
-                            Type::Narrow => '|',
-                            Type::Wet => '=',
-                            Type::Mouth => 'M',
-                            Type::Target => 'T',
-                        }
-                        .to_string()
-                })
-                .collect();
-            println!("{}", line);
+/// Exports the cave up to the corner with `--export`, with the route drawn over it.
+fn export(field: &Field<Type>, corner: Vec2, route: &[Vec2]) {
+    let width = field.width().min(corner.x as u32 + 1);
+    let height = field.height().min(corner.y as u32 + 1);
+    let route: HashSet<&Vec2> = route.iter().collect();
+    export::frame(width, height, |x, y| {
+        let position = Vec2::new(x.into(), y.into());
+        match field.get(x, y) {
+            Type::Mouth => [255, 215, 0],
+            Type::Target => [220, 20, 60],
+            _ if route.contains(&position) => [255, 255, 255],
+            Type::Rocky => [128, 128, 128],
+            Type::Wet => [70, 130, 180],
+            Type::Narrow => [90, 60, 40],
         }
-    }
-}
-
-impl Field<HashMap<Tool, u32>> {
-    #[allow(dead_code)]
-    fn print(&self, max_x: u32, max_y: u32) {
-        for y in 0..=max_y {
-            let line: String = (0..=max_x)
-                .map(|x| {
-                    format!(
-                        "{: >7}",
-                        self.get(x, y)
-                            .iter()
-                            .map(|(t, d)| format!(
-                                "{}{}",
-                                match t {
-                                    Tool::Gear => "G",
-                                    Tool::Neither => "N",
-                                    Tool::Torch => "T",
-                                },
-                                d
-                            ))
-                            .collect::<String>()
-                    )
-                    .to_string()
-                })
-                .collect();
-            println!("{}", line);
-        }
-    }
+    });
 }
 
 #[derive(Eq, PartialEq, Clone)]
@@ -143,29 +106,32 @@ impl Type {
     }
 }
 
-fn build_field(start: &Point, target: &Point, depth: u32) -> Field<Type> {
+fn build_field(start: Vec2, target: Vec2, depth: u32) -> ACResult<Field<Type>> {
     let mut field_geologic = Field::<u32>::new(depth, depth);
     let mut field = Field::new(depth, depth);
     *field.get_mut(0, 0) = Some(Type::Mouth);
-    *field.get_mut(target.x, target.y) = Some(Type::Target);
+    *field
+        .at_mut(target)
+        .ok_or_else(|| Error::new(format!("Target {} is deeper than the cave", target)))? =
+        Some(Type::Target);
     for y in 0..depth {
         for x in 0..depth {
-            let geologic_index =
-                if (x == start.x && y == start.y) || (x == target.x && y == target.y) {
-                    0
-                } else if y == 0 {
-                    x * 16807
-                } else if x == 0 {
-                    y * 48271
-                } else {
-                    *field_geologic.get(x - 1, y) * *field_geologic.get(x, y - 1)
-                };
+            let position = Vec2::new(x.into(), y.into());
+            let geologic_index = if position == start || position == target {
+                0
+            } else if y == 0 {
+                x * 16807
+            } else if x == 0 {
+                y * 48271
+            } else {
+                *field_geologic.get(x - 1, y) * *field_geologic.get(x, y - 1)
+            };
 
             let erosion_level = (geologic_index + depth) % 20183;
 
             *field_geologic.get_mut(x, y) = erosion_level;
 
-            if (x == start.x && y == start.y) || (x == target.x && y == target.y) {
+            if position == start || position == target {
                 continue;
             }
             let field_type = match erosion_level % 3 {
@@ -178,203 +144,109 @@ fn build_field(start: &Point, target: &Point, depth: u32) -> Field<Type> {
         }
     }
 
-    field.into()
+    Ok(field.into())
 }
 
-#[derive(PartialEq, Eq, Debug)]
-struct PartialPath {
-    next_point: Point,
-    dist: u32,
-    tool: Tool,
-}
-
-impl PartialOrd for PartialPath {
-    fn partial_cmp(&self, other: &PartialPath) -> Option<Ordering> {
-        Some(self.cmp(&other))
-    }
-}
-
-impl Ord for PartialPath {
-    fn cmp(&self, other: &PartialPath) -> Ordering {
-        match self.dist.cmp(&other.dist) {
-            Ordering::Greater => Ordering::Less,
-            Ordering::Less => Ordering::Greater,
-            Ordering::Equal => Ordering::Equal,
-        }
-    }
-}
-
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
 enum Tool {
     Gear,
     Torch,
     Neither,
 }
 
-struct Game {
-    field: Field<Type>,
-    shortests: Field<HashMap<Tool, u32>>,
-    next: BinaryHeap<PartialPath>,
-    target: Point,
-}
-
-impl Game {
-    fn new(field: Field<Type>, start: &Point, target: &Point) -> Self {
-        let mut next = BinaryHeap::new();
-        next.push(PartialPath {
-            next_point: start.clone(),
-            tool: Tool::Torch,
-            dist: 0,
-        });
-
-        let shortests = Field::new(field.width(), field.height());
-
-        Game {
-            field,
-            shortests,
-            next,
-            target: target.clone(),
-        }
+fn get_other_tool(field_type: &Type, tool: &Tool) -> Tool {
+    let tools = get_necessary_tool(field_type);
+    if tools[0] == *tool {
+        tools[1].clone()
+    } else {
+        tools[0].clone()
     }
+}
 
-    fn get_shortest_minutes(mut self) -> u32 {
-        while let Some(next) = self.next.pop() {
-            if let Some(dist) = self
-                .shortests
-                .get(self.target.x, self.target.y)
-                .get(&Tool::Torch)
-            {
-                if *dist < next.dist {
-                    // self.shortests.print(self.target.x + 100, self.target.y + 100);
-                    return *dist;
-                }
-            }
-
-            let other_tool = Self::get_other_tool(
-                self.field.get(next.next_point.x, next.next_point.y),
-                &next.tool,
-            );
-            let tools =
-            // if *self.field.get(next.next_point.x, next.next_point.y) == Type::Mouth {
-            //     vec![Tool::Torch, Tool::Gear, Tool::Neither]
-            // } else {
-                vec![next.tool.clone(), other_tool.clone()]
-            // }
-            ;
-            for tool in &tools {
-                let next_dist = if *tool == next.tool {
-                    next.dist
-                } else {
-                    next.dist + 7
-                };
-
-                let shortest = self.shortests.get(next.next_point.x, next.next_point.y);
-                if let Some(dist) = shortest.get(tool) {
-                    if *dist <= next_dist {
-                        continue;
-                    }
-                }
-
-                self.shortests
-                    .get_mut(next.next_point.x, next.next_point.y)
-                    .insert((*tool).clone(), next_dist);
-
-                if next.next_point.x == self.target.x && next.next_point.y == self.target.y {
-                    continue;
-                }
-
-                if next.next_point.x > 0 {
-                    self.add_if_shorter(
-                        Point::new(next.next_point.x - 1, next.next_point.y),
-                        &tool,
-                        next_dist,
-                    );
-                }
-                if next.next_point.y > 0 {
-                    self.add_if_shorter(
-                        Point::new(next.next_point.x, next.next_point.y - 1),
-                        &tool,
-                        next_dist,
-                    );
-                }
-                self.add_if_shorter(
-                    Point::new(next.next_point.x + 1, next.next_point.y),
-                    &tool,
-                    next_dist,
-                );
-                self.add_if_shorter(
-                    Point::new(next.next_point.x, next.next_point.y + 1),
-                    &tool,
-                    next_dist,
-                );
-            }
-        }
-        panic!("Shouldn't happen")
+fn get_necessary_tool(field_type: &Type) -> [Tool; 2] {
+    match field_type {
+        Type::Narrow => [Tool::Neither, Tool::Torch],
+        Type::Wet => [Tool::Neither, Tool::Gear],
+        Type::Rocky | Type::Mouth | Type::Target => [Tool::Gear, Tool::Torch],
     }
+}
 
-    fn add_if_shorter(&mut self, point: Point, tool: &Tool, dist: u32) {
-        let f = self.field.get(point.x, point.y);
-        let tools = Self::get_necessary_tool(f);
-        if !tools.contains(tool) {
-            return;
-        }
-
-        let shortest = self.shortests.get(point.x, point.y);
-
-        let new_dist = 1 + dist;
-        if let Some(dist) = shortest.get(&tool) {
-            if *dist <= new_dist {
-                return;
+/// Switching the tool takes 7 minutes, moving to a region that allows the tool 1 minute.
+fn get_next_steps(field: &Field<Type>, (point, tool): &(Vec2, Tool)) -> Vec<((Vec2, Tool), u32)> {
+    let region = match field.at(*point) {
+        Some(region) => region,
+        None => return Vec::new(),
+    };
+    let mut steps = vec![((*point, get_other_tool(region, tool)), 7)];
+    for next in point.neighbours4().iter() {
+        if let Some(next_region) = field.at(*next) {
+            if get_necessary_tool(next_region).contains(tool) {
+                steps.push(((*next, tool.clone()), 1));
             }
         }
-        self.next.push(PartialPath {
-            next_point: point,
-            dist: new_dist,
-            tool: tool.clone(),
-        })
-    }
-
-    fn get_other_tool(field_type: &Type, tool: &Tool) -> Tool {
-        let tools = Self::get_necessary_tool(field_type);
-        if tools[0] == *tool {
-            tools[1].clone()
-        } else {
-            tools[0].clone()
-        }
     }
-
-    fn get_necessary_tool(field_type: &Type) -> [Tool; 2] {
-        match field_type {
-            Type::Narrow => [Tool::Neither, Tool::Torch],
-            Type::Wet => [Tool::Neither, Tool::Gear],
-            Type::Rocky | Type::Mouth | Type::Target => [Tool::Gear, Tool::Torch],
+    steps
+}
+
+/// The minutes to reach the target and the regions on the way.
+fn get_shortest_route(
+    field: &Field<Type>,
+    start: Vec2,
+    target: Vec2,
+) -> ACResult<(u32, Vec<Vec2>)> {
+    let goal = (target, Tool::Torch);
+    let paths = search::dijkstra(
+        (start, Tool::Torch),
+        |state| get_next_steps(field, state),
+        |state| *state == goal,
+    );
+    match (paths.goal_distance(), paths.path(&goal)) {
+        (Some(minutes), Some(path)) => {
+            let mut route: Vec<Vec2> = path.into_iter().map(|(point, _)| point).collect();
+            route.dedup();
+            Ok((minutes, route))
         }
+        _ => Err(Error::new_str("Target not reachable")),
     }
 }
 
 fn level_1(input: &Input) -> ACResult<u32> {
-    let start = Point::new(0, 0);
-
-    let field = build_field(&start, &input.target, input.depth);
-
-    // field.print(input.target.x, input.target.y);
+    let field = build_field(Vec2::ZERO, input.target, input.depth)?;
+    export(&field, input.target, &[]);
 
     let mut risk_level = 0;
     for y in 0..=input.target.y {
         for x in 0..=input.target.x {
-            risk_level += field.get(x, y).get_risk();
+            risk_level += field.at(Vec2::new(x, y)).map_or(0, Type::get_risk);
         }
     }
     Ok(risk_level)
 }
 
 fn level_2(input: &Input) -> ACResult<u32> {
-    let start = Point::new(0, 0);
+    let field = build_field(Vec2::ZERO, input.target, input.depth)?;
+
+    let (minutes, route) = get_shortest_route(&field, Vec2::ZERO, input.target)?;
+    let corner = route.iter().fold(input.target, |corner, p| {
+        Vec2::new(corner.x.max(p.x), corner.y.max(p.y))
+    });
+    export(&field, corner + Vec2::new(5, 5), &route);
+    Ok(minutes)
+}
 
-    let field = build_field(&start, &input.target, input.depth);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // field.print(input.target.x + 5, input.target.y + 5);
-    let game = Game::new(field, &start, &input.target);
+    const EXAMPLE: &str = "depth: 510\ntarget: 10,10\n";
 
-    Ok(game.get_shortest_minutes())
+    #[test]
+    fn run_level_1_examples() {
+        assert_eq!(level_1(&parse_line(EXAMPLE.as_bytes()).unwrap()), Ok(114));
+    }
+
+    #[test]
+    fn run_level_2_examples() {
+        assert_eq!(level_2(&parse_line(EXAMPLE.as_bytes()).unwrap()), Ok(45));
+    }
 }
@@ -1,53 +1,38 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
-use nom::{bytes::complete::take_while, combinator::map, IResult};
+use crate::geometry::Vec3;
+use nom::{bytes::complete::take_while, combinator::map_res, IResult};
 use nom::{do_parse, named, tag};
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Hash)]
-struct Position {
-    x: i64,
-    y: i64,
-    z: i64,
-}
-
-impl Position {
-    fn dist(&self, p: &Position) -> i64 {
-        (self.x - p.x).abs() + (self.y - p.y).abs() + (self.z - p.z).abs()
-    }
-
-    fn dist_i(&self, x: i64, y: i64, z: i64) -> i64 {
-        (self.x - x).abs() + (self.y - y).abs() + (self.z - z).abs()
-    }
-
-    fn div(&self, div: i64) -> Position {
-        Position {
-            x: self.x / div,
-            y: self.y / div,
-            z: self.z / div,
-        }
-    }
+/// Only parses the bots, for benchmarking.
+pub fn parse<T: BufRead>(data: T) -> ACResult<()> {
+    crate::parsing::parse_lines(&crate::utils::read_lines(data)?, parse_bot).map(|_| ())
 }
 
 #[derive(Debug)]
 struct Bot {
-    position: Position,
+    position: Vec3,
     range: i64,
 }
 
 fn number(input: &str) -> IResult<&str, i64> {
-    map(
+    map_res(
         take_while(|c: char| c == '-' || c == ' ' || c.is_digit(10)),
-        |s: &str| s.trim().parse().unwrap(),
+        |s: &str| s.trim().parse(),
     )(input)
 }
 
@@ -64,34 +49,34 @@ named!(
             >> tag!(">, r=")
             >> range: number
             >> (Bot {
-                position: Position { x, y, z },
+                position: Vec3::new(x, y, z),
                 range
             })
     )
 );
 
 fn level_1(lines: &[String]) -> ACResult<usize> {
-    let bots: Vec<_> = lines.iter().map(|l| parse_bot(l).unwrap().1).collect();
+    let bots = crate::parsing::parse_lines(lines, parse_bot)?;
 
     let max_bot = bots.iter().max_by_key(|b| b.range).unwrap();
 
     let bots_in_range = bots
         .iter()
-        .filter(|bot| max_bot.position.dist(&bot.position) <= max_bot.range)
+        .filter(|bot| max_bot.position.manhattan(bot.position) <= max_bot.range)
         .count();
 
     Ok(bots_in_range)
 }
 
 fn level_2(lines: &[String]) -> ACResult<i64> {
-    let bots: Vec<_> = lines.iter().map(|l| parse_bot(l).unwrap().1).collect();
+    let bots = crate::parsing::parse_lines(lines, parse_bot)?;
 
-    let min = Position {
+    let min = Vec3 {
         x: bots.iter().min_by_key(|b| b.position.x).unwrap().position.x,
         y: bots.iter().min_by_key(|b| b.position.y).unwrap().position.y,
         z: bots.iter().max_by_key(|b| b.position.z).unwrap().position.z,
     };
-    let max = Position {
+    let max = Vec3 {
         x: bots.iter().max_by_key(|b| b.position.x).unwrap().position.x,
         y: bots.iter().max_by_key(|b| b.position.y).unwrap().position.y,
         z: bots.iter().max_by_key(|b| b.position.z).unwrap().position.z,
@@ -104,8 +89,8 @@ fn level_2(lines: &[String]) -> ACResult<i64> {
             div *= 2;
         }
         heap.push(Block {
-            min: min.div(div),
-            max: max.div(div),
+            min: min / div,
+            max: max / div,
             overlap_count: bots.len(),
             div,
         });
@@ -127,7 +112,7 @@ fn level_2(lines: &[String]) -> ACResult<i64> {
         let bots: Vec<_> = bots
             .iter()
             .map(|b| Bot {
-                position: b.position.div(block.div),
+                position: b.position / block.div,
                 range: b.range / block.div,
             })
             .collect();
@@ -136,28 +121,20 @@ fn level_2(lines: &[String]) -> ACResult<i64> {
                 for z in block.min.z..=block.max.z {
                     let overlap_count = bots
                         .iter()
-                        .filter(|b| b.position.dist_i(x, y, z) <= b.range)
+                        .filter(|b| b.position.manhattan(Vec3::new(x, y, z)) <= b.range)
                         .count();
 
                     if block.div == 1 {
                         heap.push(Block {
-                            min: Position { x, y, z },
-                            max: Position { x, y, z },
+                            min: Vec3::new(x, y, z),
+                            max: Vec3::new(x, y, z),
                             overlap_count,
                             div: 0,
                         })
                     } else {
                         heap.push(Block {
-                            min: Position {
-                                x: (x - 1) * 2,
-                                y: (y - 1) * 2,
-                                z: (z - 1) * 2,
-                            },
-                            max: Position {
-                                x: (x + 1) * 2,
-                                y: (y + 1) * 2,
-                                z: (z + 1) * 2,
-                            },
+                            min: (Vec3::new(x, y, z) - Vec3::new(1, 1, 1)) * 2,
+                            max: (Vec3::new(x, y, z) + Vec3::new(1, 1, 1)) * 2,
                             overlap_count,
                             div: block.div / 2,
                         })
@@ -168,16 +145,16 @@ fn level_2(lines: &[String]) -> ACResult<i64> {
     }
     let result = largest_points
         .iter()
-        .min_by_key(|p| p.dist_i(0, 0, 0))
+        .min_by_key(|p| p.manhattan(Vec3::ZERO))
         .unwrap();
 
-    Ok(result.dist_i(0, 0, 0))
+    Ok(result.manhattan(Vec3::ZERO))
 }
 
 #[derive(PartialEq, Eq, Debug)]
 struct Block {
-    min: Position,
-    max: Position,
+    min: Vec3,
+    max: Vec3,
     overlap_count: usize,
     div: i64,
 }
@@ -193,3 +170,37 @@ impl Ord for Block {
         self.overlap_count.cmp(&other.overlap_count)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn run_level_1_examples() {
+        let example = "pos=<0,0,0>, r=4
+pos=<1,0,0>, r=1
+pos=<4,0,0>, r=3
+pos=<0,2,0>, r=1
+pos=<0,5,0>, r=3
+pos=<0,0,3>, r=1
+pos=<1,1,1>, r=1
+pos=<1,1,2>, r=1
+pos=<1,3,1>, r=1";
+        assert_eq!(level_1(&lines(example)), Ok(7));
+    }
+
+    #[test]
+    fn run_level_2_examples() {
+        let example = "pos=<10,12,12>, r=2
+pos=<12,14,12>, r=2
+pos=<16,12,12>, r=4
+pos=<14,14,14>, r=6
+pos=<50,50,50>, r=200
+pos=<10,10,10>, r=5";
+        assert_eq!(level_2(&lines(example)), Ok(36));
+    }
+}
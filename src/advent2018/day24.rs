@@ -1,28 +1,30 @@
-use crate::errors::{ACResult, Error};
+use crate::answer::Answer;
+use crate::errors::{ACResult, Context, Error};
 use nom::{
-    alt, complete, delimited, do_parse, many1, map, named, opt, preceded, tag, take_while1,
-    terminated,
+    alt, complete, delimited, do_parse, many1, map, map_res, named, opt, preceded, tag,
+    take_while1, terminated,
 };
 use std::cmp::Ordering;
 use std::io::BufRead;
 use std::io::Read;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&parse_line(data)?).map(|r| r.to_string()),
-        2 => level_2(&parse_line(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&parse_line(data)?).map(Answer::from),
+        2 => level_2(&parse_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
 fn parse_line<T: Read>(mut data: T) -> ACResult<Input> {
     let mut contents = String::new();
     data.read_to_string(&mut contents)
-        .map_err(|_| Error::new_str("Failed to read data"))?;
+        .context("Failed to read data")?;
 
-    parse_input(&contents)
-        .map(|x| x.1)
-        .map_err(|_e| Error::new("Failed to parse input".to_owned()))
+    crate::parsing::parse_all(&contents, parse_input)
 }
 
 #[derive(Debug)]
@@ -64,7 +66,7 @@ enum Attack {
 }
 
 named!(parse_number_u32<&str, u32>,
-    complete!(map!(take_while1!(|c: char| c.is_numeric()), |c| c.to_string().parse().unwrap()))
+    complete!(map_res!(take_while1!(|c: char| c.is_numeric()), |c: &str| c.parse()))
 );
 
 named!(parse_i_w<&str, (Vec<Attack>, Vec<Attack>)>,
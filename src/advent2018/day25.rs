@@ -1,41 +1,32 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
-use nom::{bytes::complete::take_while, combinator::map, IResult};
+use crate::geometry::Vec4;
+use crate::regions::UnionFind;
+use nom::{bytes::complete::take_while, combinator::map_res, IResult};
 use nom::{do_parse, named, tag};
-use std::collections::HashSet;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2().map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
-    }
-}
-
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-struct Position {
-    x: i64,
-    y: i64,
-    z: i64,
-    a: i64,
-}
-
-impl Position {
-    fn dist(&self, p: &Position) -> i64 {
-        (self.x - p.x).abs() + (self.y - p.y).abs() + (self.z - p.z).abs() + (self.a - p.a).abs()
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2().map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
 fn number(input: &str) -> IResult<&str, i64> {
-    map(
+    map_res(
         take_while(|c: char| c == '-' || c == ' ' || c.is_digit(10)),
-        |s: &str| s.trim().parse().unwrap(),
+        |s: &str| s.trim().parse(),
     )(input)
 }
 
 // 9,-1,5,3
 named!(
-    parse_pos<&str, Position>,
+    parse_pos<&str, Vec4>,
     do_parse!(
         x: number
             >> tag!(",")
@@ -43,70 +34,70 @@ named!(
             >> tag!(",")
             >> z: number
             >> tag!(",")
-            >> a: number
-            >> (Position { x, y, z, a })
+            >> w: number
+            >> (Vec4::new(x, y, z, w))
     )
 );
 
 fn level_1(lines: &[String]) -> ACResult<usize> {
-    let positions: Vec<_> = lines.iter().map(|l| parse_pos(l).unwrap().1).collect();
+    let positions = crate::parsing::parse_lines(lines, parse_pos)?;
 
-    let mut constellations: Vec<Vec<Position>> = Vec::new();
-    let mut used = HashSet::new();
+    let mut constellations = UnionFind::new(positions.len());
     for (i, pos) in positions.iter().enumerate() {
-        if used.contains(&i) {
-            continue;
-        }
-
-        let mut constellation = Vec::new();
-        constellation.push(pos.clone());
-        used.insert(i);
-
-        for (j, pos2) in positions.iter().enumerate() {
-            if used.contains(&j) {
-                continue;
-            }
-            if pos.dist(pos2) <= 3 {
-                constellation.push(pos2.clone());
-                used.insert(j);
+        for (j, pos2) in positions.iter().enumerate().skip(i + 1) {
+            if pos.manhattan(*pos2) <= 3 {
+                constellations.union(i, j);
             }
         }
-
-        let existing_constellation = constellations.iter_mut().find(|c| {
-            c.iter()
-                .any(|p| constellation.iter().any(|p2| p.dist(p2) <= 3))
-        });
-        if let Some(c2) = existing_constellation {
-            c2.append(&mut constellation);
-        } else {
-            constellations.push(constellation);
-        };
     }
 
-    loop {
-        let mut dup = None;
-        'outer: for (i, c1) in constellations.iter().enumerate() {
-            for (j, c2) in constellations.iter().enumerate() {
-                if j <= i {
-                    continue;
-                }
-                if c1.iter().any(|p1| c2.iter().any(|p2| p1.dist(p2) <= 3)) {
-                    dup = Some((i, j));
-                    break 'outer;
-                }
-            }
-        }
-        if let Some((i, j)) = dup {
-            let mut c = constellations.remove(j);
-            constellations[i].append(&mut c);
-        } else {
-            break;
-        }
-    }
-
-    Ok(constellations.len())
+    Ok(constellations.sets())
 }
 
 fn level_2() -> ACResult<i64> {
     Ok(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn run_level_1_examples() {
+        let example = " 0,0,0,0
+ 3,0,0,0
+ 0,3,0,0
+ 0,0,3,0
+ 0,0,0,3
+ 0,0,0,6
+ 9,0,0,0
+12,0,0,0";
+        assert_eq!(level_1(&lines(example)), Ok(2));
+        let example = "-1,2,2,0
+0,0,2,-2
+0,0,0,-2
+-1,2,0,0
+-2,-2,-2,2
+3,0,2,-1
+-1,3,2,2
+-1,0,-1,0
+0,2,1,-2
+3,0,0,0";
+        assert_eq!(level_1(&lines(example)), Ok(4));
+        let example = "1,-1,-1,-2
+-2,-2,0,1
+0,2,1,3
+-2,3,-2,1
+0,2,3,-2
+-1,-1,1,-2
+0,-2,-1,0
+-2,2,3,-1
+1,2,2,0
+-1,-2,0,-2";
+        assert_eq!(level_1(&lines(example)), Ok(8));
+    }
+}
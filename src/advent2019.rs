@@ -1,3 +1,4 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
 use std::io::{BufRead, Read};
 
@@ -13,7 +14,7 @@ mod day09;
 mod day10;
 mod day11;
 
-pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<String> {
+pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<Answer> {
     match day {
         1 => day01::get_result(data, level),
         2 => day02::get_result(data, level),
@@ -26,6 +27,6 @@ pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<St
         9 => day09::get_result(data, level),
         10 => day10::get_result(data, level),
         11 => day11::get_result(data, level),
-        _ => Err(Error::new(format!("Day {} not implemented", day))),
+        _ => Err(Error::Unimplemented(format!("Day {} not implemented", day))),
     }
 }
@@ -1,31 +1,31 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
-use itertools::Itertools;
+use crate::parsing::number;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
 fn level_1(line: &[String]) -> ACResult<u64> {
-    line.iter()
-        .map(|l| {
-            l.parse::<u64>()
-                .map_err(|_e| Error::new_str("Failed to parse mass input"))
-        })
-        .fold_results(0, |fuel, mass| fuel + (mass / 3 - 2))
+    Ok(crate::parsing::parse_lines(line, number::<u64>)?
+        .into_iter()
+        .map(|mass| (mass / 3).saturating_sub(2))
+        .sum())
 }
 
 fn level_2(line: &[String]) -> ACResult<u64> {
-    line.iter()
-        .map(|l| {
-            l.parse::<u64>()
-                .map_err(|_e| Error::new_str("Failed to parse mass input"))
-        })
-        .fold_results(0, |fuel, mass| fuel + compute_fuel(mass))
+    Ok(crate::parsing::parse_lines(line, number::<u64>)?
+        .into_iter()
+        .map(compute_fuel)
+        .sum())
 }
 
 fn compute_fuel(mass: u64) -> u64 {
@@ -1,93 +1,38 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::intcode::{parse_intcode, IntCodeComputer};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_line(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_line(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_line(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
-struct IntCodeComputer {
-    memory: Vec<usize>,
-    pos: usize,
+/// Runs the program with the noun and verb at address 1 and 2 and returns address 0.
+fn run_program(ops: &[i64], noun: i64, verb: i64) -> ACResult<i64> {
+    let mut computer = IntCodeComputer::new(ops);
+    computer.write_memory(1, noun);
+    computer.write_memory(2, verb);
+    computer.compute(&[])?;
+    Ok(computer.read_memory(0))
 }
 
-impl IntCodeComputer {
-    #[allow(dead_code)]
-    fn new(memory: &[usize]) -> Self {
-        IntCodeComputer {
-            memory: Vec::from(memory),
-            pos: 0,
-        }
-    }
-
-    fn new_fixed(memory: &[usize], noun: usize, verb: usize) -> Self {
-        let mut memory = Vec::from(memory);
-        memory[1] = noun;
-        memory[2] = verb;
-        IntCodeComputer { memory, pos: 0 }
-    }
-
-    fn compute(mut self) -> ACResult<usize> {
-        loop {
-            match self.memory[self.pos] {
-                1 => {
-                    let (i1, i2, o) = (
-                        self.memory[self.pos + 1],
-                        self.memory[self.pos + 2],
-                        self.memory[self.pos + 3],
-                    );
-                    self.memory[o] = self.memory[i1] + self.memory[i2];
-                    self.pos += 4;
-                }
-                2 => {
-                    let (i1, i2, o) = (
-                        self.memory[self.pos + 1],
-                        self.memory[self.pos + 2],
-                        self.memory[self.pos + 3],
-                    );
-                    self.memory[o] = self.memory[i1] * self.memory[i2];
-                    self.pos += 4;
-                }
-                99 => {
-                    break;
-                }
-                value => {
-                    return Err(Error::new(format!(
-                        "Invalid machine state at pos {}: {}",
-                        self.pos, value
-                    )))
-                }
-            }
-        }
-        Ok(self.memory[0])
-    }
-}
-
-fn parse_intcode(input: &str) -> ACResult<Vec<usize>> {
-    input
-        .split(',')
-        .map(|c| {
-            c.parse::<usize>()
-                .map_err(|e| Error::new(format!("Invalid opcode: {}", e)))
-        })
-        .collect()
-}
-
-fn level_1(line: &str) -> ACResult<usize> {
+fn level_1(line: &str) -> ACResult<i64> {
     let ops = parse_intcode(line)?;
-    let computer = IntCodeComputer::new_fixed(&ops, 12, 2);
-    computer.compute()
+    run_program(&ops, 12, 2)
 }
 
-fn level_2(line: &str) -> ACResult<usize> {
+fn level_2(line: &str) -> ACResult<i64> {
     let ops = parse_intcode(line)?;
     for i in 0..100 {
         for j in 0..100 {
-            let computer = IntCodeComputer::new_fixed(&ops, i, j);
-            let result = computer.compute()?;
+            let result = run_program(&ops, i, j)?;
             if result == 19_690_720 {
                 return Ok(100 * i + j);
             }
@@ -100,28 +45,17 @@ fn level_2(line: &str) -> ACResult<usize> {
 mod tests {
     use super::*;
 
+    fn run(program: &str) -> ACResult<i64> {
+        let ops = parse_intcode(program)?;
+        run_program(&ops, ops[1], ops[2])
+    }
+
     #[test]
     fn run_int_code_examples() {
-        assert_eq!(
-            IntCodeComputer::new(&parse_intcode("1,9,10,3,2,3,11,0,99,30,40,50").unwrap())
-                .compute(),
-            Ok(3500)
-        );
-        assert_eq!(
-            IntCodeComputer::new(&parse_intcode("1,0,0,0,99").unwrap()).compute(),
-            Ok(2)
-        );
-        assert_eq!(
-            IntCodeComputer::new(&parse_intcode("2,3,0,3,99").unwrap()).compute(),
-            Ok(2)
-        );
-        assert_eq!(
-            IntCodeComputer::new(&parse_intcode("2,4,4,5,99,0").unwrap()).compute(),
-            Ok(2)
-        );
-        assert_eq!(
-            IntCodeComputer::new(&parse_intcode("1,1,1,4,99,5,6,0,99").unwrap()).compute(),
-            Ok(30)
-        );
+        assert_eq!(run("1,9,10,3,2,3,11,0,99,30,40,50"), Ok(3500));
+        assert_eq!(run("1,0,0,0,99"), Ok(2));
+        assert_eq!(run("2,3,0,3,99"), Ok(2));
+        assert_eq!(run("2,4,4,5,99,0"), Ok(2));
+        assert_eq!(run("1,1,1,4,99,5,6,0,99"), Ok(30));
     }
 }
@@ -1,158 +1,100 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::geometry::{Direction, Vec2};
+use crate::parsing::number;
+use nom::character::complete::{char, one_of};
+use nom::combinator::{cut, map_opt};
+use nom::multi::separated_nonempty_list;
+use nom::IResult;
 use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
-enum Direction {
-    UP,
-    DOWN,
-    LEFT,
-    RIGHT,
-}
-
 struct Instruction {
     direction: Direction,
     count: usize,
 }
 
-fn parse_instruction(input: &str) -> Instruction {
-    let d = match input.chars().nth(0).unwrap() {
-        'R' => Direction::RIGHT,
-        'L' => Direction::LEFT,
-        'U' => Direction::UP,
-        'D' => Direction::DOWN,
-        _ => panic!("invalid direction"),
-    };
-    let count = input
-        .chars()
-        .skip(1)
-        .collect::<String>()
-        .parse::<usize>()
-        .unwrap();
-    Instruction {
-        direction: d,
-        count,
+fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, direction) = map_opt(one_of("URDL"), |c| Direction::try_from(c).ok())(input)?;
+    let (input, count) = number(input)?;
+    Ok((input, Instruction { direction, count }))
+}
+
+fn parse_wire(input: &str) -> IResult<&str, Vec<Instruction>> {
+    separated_nonempty_list(char(','), cut(parse_instruction))(input)
+}
+
+/// All positions the wire passes in order, without the central port.
+fn trace_wire(instructions: &[Instruction]) -> Vec<Vec2> {
+    let mut position = Vec2::ZERO;
+    let mut wire = Vec::new();
+    for instruction in instructions.iter() {
+        for _ in 0..instruction.count {
+            position += instruction.direction.offset();
+            wire.push(position);
+        }
     }
+    wire
 }
 
-fn level_1(lines: &[String]) -> ACResult<usize> {
+fn level_1(lines: &[String]) -> ACResult<i64> {
     let mut wires = Vec::new();
-    for l in lines.iter() {
-        let (mut x, mut y): (isize, isize) = (0, 0);
-        let mut wire = HashSet::new();
-        for s in l.split(',') {
-            let s = parse_instruction(s);
-            match s.direction {
-                Direction::RIGHT => {
-                    for _px in 0..s.count {
-                        x += 1;
-                        wire.insert((x, y));
-                    }
-                }
-                Direction::LEFT => {
-                    for _px in 0..s.count {
-                        x -= 1;
-                        wire.insert((x, y));
-                    }
-                }
-                Direction::UP => {
-                    for _py in 0..s.count {
-                        y -= 1;
-                        wire.insert((x, y));
-                    }
-                }
-                Direction::DOWN => {
-                    for _py in 0..s.count {
-                        y += 1;
-                        wire.insert((x, y));
-                    }
-                }
-            }
-        }
-        wires.push(wire);
+    for instructions in crate::parsing::parse_lines(lines, parse_wire)? {
+        wires.push(
+            trace_wire(&instructions)
+                .into_iter()
+                .collect::<HashSet<_>>(),
+        );
     }
     let mut smallest = None;
     for (i, w1) in wires.iter().enumerate() {
         for w2 in wires.iter().skip(i + 1) {
             for i in w1.intersection(w2) {
-                let dist = i.0.abs() + i.1.abs();
+                let dist = i.manhattan(Vec2::ZERO);
                 if smallest.is_none() || smallest.unwrap() > dist {
                     smallest = Some(dist);
                 }
             }
         }
     }
-    Ok(smallest.unwrap().abs() as usize)
+    smallest.ok_or_else(|| Error::new_str("The wires don't cross"))
 }
 
 fn level_2(lines: &[String]) -> ACResult<usize> {
     let mut wires = Vec::new();
-    for l in lines.iter() {
-        let (mut x, mut y): (isize, isize) = (0, 0);
+    for instructions in crate::parsing::parse_lines(lines, parse_wire)? {
         let mut wire = HashMap::new();
-        let mut i = 0usize;
-        for s in l.split(',') {
-            let s = parse_instruction(s);
-            match s.direction {
-                Direction::RIGHT => {
-                    for _px in 0..s.count {
-                        x += 1;
-                        i += 1;
-                        wire.entry((x, y)).or_insert(i);
-                    }
-                }
-                Direction::LEFT => {
-                    for _px in 0..s.count {
-                        x -= 1;
-                        i += 1;
-                        wire.entry((x, y)).or_insert(i);
-                    }
-                }
-                Direction::UP => {
-                    for _py in 0..s.count {
-                        y -= 1;
-                        i += 1;
-                        wire.entry((x, y)).or_insert(i);
-                    }
-                }
-                Direction::DOWN => {
-                    for _py in 0..s.count {
-                        y += 1;
-                        i += 1;
-                        wire.entry((x, y)).or_insert(i);
-                    }
-                }
-            }
+        for (i, position) in trace_wire(&instructions).into_iter().enumerate() {
+            wire.entry(position).or_insert(i + 1);
         }
         wires.push(wire);
     }
     let mut smallest = None;
-    for (i, w1m) in wires.iter().enumerate() {
-        let mut w1 = HashSet::new();
-        w1m.keys().for_each(|k| {
-            w1.insert(k);
-        });
-        for w2m in wires.iter().skip(i + 1) {
-            let mut w2 = HashSet::new();
-            w2m.keys().for_each(|k| {
-                w2.insert(k);
-            });
-            for i in w1.intersection(&w2) {
-                let dist = w1m.get(i).unwrap() + w2m.get(i).unwrap();
-                if smallest.is_none() || smallest.unwrap() > dist {
-                    smallest = Some(dist);
+    for (i, w1) in wires.iter().enumerate() {
+        for w2 in wires.iter().skip(i + 1) {
+            for (position, steps1) in w1.iter() {
+                if let Some(steps2) = w2.get(position) {
+                    let dist = steps1 + steps2;
+                    if smallest.is_none() || smallest.unwrap() > dist {
+                        smallest = Some(dist);
+                    }
                 }
             }
         }
     }
-    Ok(smallest.unwrap())
+    smallest.ok_or_else(|| Error::new_str("The wires don't cross"))
 }
 
 #[cfg(test)]
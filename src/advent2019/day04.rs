@@ -1,11 +1,17 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::parsing::number;
+use nom::{character::complete::char, sequence::separated_pair, IResult};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_line(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_line(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_line(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
@@ -36,12 +42,12 @@ fn check1(i: usize) -> bool {
     same && !decreased
 }
 
+fn parse_range(i: &str) -> IResult<&str, (usize, usize)> {
+    separated_pair(number, char('-'), number)(i)
+}
+
 fn level_1(line: &str) -> ACResult<usize> {
-    let parts = line
-        .split('-')
-        .map(|l| l.parse::<usize>().unwrap())
-        .collect::<Vec<_>>();
-    let (from, to) = (parts[0], parts[1]);
+    let (from, to) = crate::parsing::parse_all(line, parse_range)?;
 
     Ok((from..=to).filter(|i| check1(*i)).count())
 }
@@ -73,11 +79,7 @@ fn check2(i: usize) -> bool {
 }
 
 fn level_2(line: &str) -> ACResult<usize> {
-    let parts = line
-        .split('-')
-        .map(|l| l.parse::<usize>().unwrap())
-        .collect::<Vec<_>>();
-    let (from, to) = (parts[0], parts[1]);
+    let (from, to) = crate::parsing::parse_all(line, parse_range)?;
 
     Ok((from..=to).filter(|i| check2(*i)).count())
 }
@@ -1,105 +1,66 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
-use std::collections::HashSet;
+use crate::search;
+use nom::character::complete::{alphanumeric1, char};
+use nom::sequence::separated_pair;
+use nom::IResult;
+use std::collections::HashMap;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
-fn level_1(lines: &[String]) -> ACResult<u32> {
-    let orbits = lines
-        .iter()
-        .map(|l| l.split(')').collect::<Vec<_>>())
-        .map(|l| (l[0], l[1]))
-        .collect::<Vec<_>>();
-
-    let mut next = vec!["COM".to_owned()];
-    let mut orbit_count = 0;
-    let mut level = 0;
-    while !next.is_empty() {
-        let list = next;
-        level += 1;
-        next = Vec::new();
-        for current in list.iter() {
-            for (f, t) in orbits.iter() {
-                if **f == *current {
-                    next.push(t.to_string());
-                    orbit_count += level;
-                }
-            }
-        }
-    }
-    Ok(orbit_count)
+fn parse_orbit(input: &str) -> IResult<&str, (&str, &str)> {
+    separated_pair(alphanumeric1, char(')'), alphanumeric1)(input)
 }
 
-#[derive(PartialEq, Eq)]
-struct StepNaive {
-    level: u32,
-    name: String,
-    visited: HashSet<String>,
+fn parse_orbits(lines: &[String]) -> ACResult<Vec<(&str, &str)>> {
+    crate::parsing::parse_lines(lines, parse_orbit)
 }
 
-impl PartialOrd for StepNaive {
-    fn partial_cmp(&self, other: &StepNaive) -> Option<Ordering> {
-        Some(self.cmp(&other))
-    }
-}
+fn level_1(lines: &[String]) -> ACResult<usize> {
+    let orbits = parse_orbits(lines)?;
 
-impl Ord for StepNaive {
-    fn cmp(&self, other: &StepNaive) -> Ordering {
-        match self.level.cmp(&other.level) {
-            Ordering::Greater => Ordering::Less,
-            Ordering::Less => Ordering::Greater,
-            Ordering::Equal => Ordering::Equal,
-        }
-    }
+    let paths = search::bfs(
+        "COM",
+        |current| {
+            orbits
+                .iter()
+                .filter(move |(f, _)| f == current)
+                .map(|(_, t)| *t)
+                .collect::<Vec<_>>()
+        },
+        |_| false,
+    );
+    Ok(paths.distances().map(|(_, level)| level).sum())
 }
 
-fn level_2(lines: &[String]) -> ACResult<u32> {
-    let orbits = lines
-        .iter()
-        .map(|l| l.split(')').collect::<Vec<_>>())
-        .map(|l| (l[0], l[1]))
-        .collect::<Vec<_>>();
+fn level_2(lines: &[String]) -> ACResult<usize> {
+    let orbits = parse_orbits(lines)?;
 
-    let mut next = BinaryHeap::new();
-    next.push(StepNaive {
-        level: 0,
-        name: "SAN".to_owned(),
-        visited: HashSet::new(),
-    });
-    while let Some(current) = next.pop() {
-        for (f, t) in orbits.iter() {
-            let mut visited = current.visited.clone();
-            visited.insert(current.name.clone());
-            if **f == current.name && !visited.contains(&t.to_string()) {
-                next.push(StepNaive {
-                    level: current.level + 1,
-                    name: t.to_string(),
-                    visited,
-                });
-                if *t == "YOU" {
-                    return Ok(current.level - 1);
-                }
-            } else if **t == current.name && !visited.contains(&f.to_string()) {
-                next.push(StepNaive {
-                    level: current.level + 1,
-                    name: f.to_string(),
-                    visited,
-                });
-                if *f == "YOU" {
-                    return Ok(current.level - 1);
-                }
-            }
-        }
+    let mut neighbours = HashMap::new();
+    for (f, t) in orbits.iter() {
+        neighbours.entry(*f).or_insert_with(Vec::new).push(*t);
+        neighbours.entry(*t).or_insert_with(Vec::new).push(*f);
+    }
+    let paths = search::bfs(
+        "YOU",
+        |current| neighbours.get(current).cloned().unwrap_or_default(),
+        |current| *current == "SAN",
+    );
+    // Transfers are counted between the objects YOU and SAN orbit
+    match paths.goal_distance() {
+        Some(distance) if distance >= 2 => Ok(distance - 2),
+        _ => Err(Error::new_str("Not found")),
     }
-    Err(Error::new_str("Not found"))
 }
 
 #[cfg(test)]
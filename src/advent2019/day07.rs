@@ -1,296 +1,24 @@
+use crate::answer::Answer;
 use crate::errors::{ACResult, Error};
+use crate::intcode::{parse_intcode, IntCodeComputer, Io};
 use std::io::BufRead;
 use std::sync::mpsc::channel;
 use std::thread;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_line(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_line(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_line(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_line(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
-#[derive(Debug)]
-enum Opcode {
-    Add(isize, isize, usize),
-    Multiply(isize, isize, usize),
-    Input(usize),
-    Output(isize),
-    JumpIfTrue(isize, usize),
-    JumpIfFalse(isize, usize),
-    LessThan(isize, isize, usize),
-    Equals(isize, isize, usize),
-    Break,
-}
-
-struct Command {
-    opcode: Opcode,
-    command_length: usize,
-}
-struct IntCodeComputer {
-    memory: Vec<isize>,
-    pos: usize,
-}
-
-impl IntCodeComputer {
-    #[allow(dead_code)]
-    fn new(memory: &[isize]) -> Self {
-        IntCodeComputer {
-            memory: Vec::from(memory),
-            pos: 0,
-        }
-    }
-
-    fn parse_command(&self) -> ACResult<Command> {
-        let params = self.memory[self.pos];
-        let opcode = self.memory[self.pos] % 100;
-        match opcode {
-            1 => {
-                let (i1, i2, o) = (
-                    self.memory[self.pos + 1],
-                    self.memory[self.pos + 2],
-                    self.memory[self.pos + 3] as usize,
-                );
-                let (i1, i2) = (
-                    if params / 100 % 10 == 0 {
-                        self.memory[i1 as usize]
-                    } else {
-                        i1
-                    },
-                    if params / 1000 % 10 == 0 {
-                        self.memory[i2 as usize]
-                    } else {
-                        i2
-                    },
-                );
-                Ok(Command {
-                    opcode: Opcode::Add(i1, i2, o),
-                    command_length: 4,
-                })
-            }
-            2 => {
-                let (i1, i2, o) = (
-                    self.memory[self.pos + 1],
-                    self.memory[self.pos + 2],
-                    self.memory[self.pos + 3] as usize,
-                );
-                let (i1, i2) = (
-                    if params / 100 % 10 == 0 {
-                        self.memory[i1 as usize]
-                    } else {
-                        i1
-                    },
-                    if params / 1000 % 10 == 0 {
-                        self.memory[i2 as usize]
-                    } else {
-                        i2
-                    },
-                );
-                Ok(Command {
-                    opcode: Opcode::Multiply(i1, i2, o),
-                    command_length: 4,
-                })
-            }
-            3 => {
-                let o = self.memory[self.pos + 1] as usize;
-                Ok(Command {
-                    opcode: Opcode::Input(o),
-                    command_length: 2,
-                })
-            }
-            4 => {
-                let i = self.memory[self.pos + 1];
-                let i = if params / 100 % 10 == 0 {
-                    self.memory[i as usize]
-                } else {
-                    i
-                };
-                Ok(Command {
-                    opcode: Opcode::Output(i),
-                    command_length: 2,
-                })
-            }
-            5 => {
-                let (i1, i2) = (self.memory[self.pos + 1], self.memory[self.pos + 2]);
-                let (i1, i2) = (
-                    if params / 100 % 10 == 0 {
-                        self.memory[i1 as usize]
-                    } else {
-                        i1
-                    },
-                    if params / 1000 % 10 == 0 {
-                        self.memory[i2 as usize] as usize
-                    } else {
-                        i2 as usize
-                    },
-                );
-                Ok(Command {
-                    opcode: Opcode::JumpIfTrue(i1, i2),
-                    command_length: 3,
-                })
-            }
-            6 => {
-                let (i1, i2) = (self.memory[self.pos + 1], self.memory[self.pos + 2]);
-                let (i1, i2) = (
-                    if params / 100 % 10 == 0 {
-                        self.memory[i1 as usize]
-                    } else {
-                        i1
-                    },
-                    if params / 1000 % 10 == 0 {
-                        self.memory[i2 as usize] as usize
-                    } else {
-                        i2 as usize
-                    },
-                );
-                Ok(Command {
-                    opcode: Opcode::JumpIfFalse(i1, i2),
-                    command_length: 3,
-                })
-            }
-            7 => {
-                let (i1, i2, o) = (
-                    self.memory[self.pos + 1],
-                    self.memory[self.pos + 2],
-                    self.memory[self.pos + 3] as usize,
-                );
-                let (i1, i2) = (
-                    if params / 100 % 10 == 0 {
-                        self.memory[i1 as usize]
-                    } else {
-                        i1
-                    },
-                    if params / 1000 % 10 == 0 {
-                        self.memory[i2 as usize]
-                    } else {
-                        i2
-                    },
-                );
-                Ok(Command {
-                    opcode: Opcode::LessThan(i1, i2, o),
-                    command_length: 4,
-                })
-            }
-            8 => {
-                // equals
-                let (i1, i2, o) = (
-                    self.memory[self.pos + 1],
-                    self.memory[self.pos + 2],
-                    self.memory[self.pos + 3] as usize,
-                );
-                let (i1, i2) = (
-                    if params / 100 % 10 == 0 {
-                        self.memory[i1 as usize]
-                    } else {
-                        i1
-                    },
-                    if params / 1000 % 10 == 0 {
-                        self.memory[i2 as usize]
-                    } else {
-                        i2
-                    },
-                );
-                Ok(Command {
-                    opcode: Opcode::Equals(i1, i2, o),
-                    command_length: 4,
-                })
-            }
-            99 => Ok(Command {
-                opcode: Opcode::Break,
-                command_length: 1,
-            }),
-            value => Err(Error::new(format!(
-                "Invalid machine state at pos {}: {}",
-                self.pos, value
-            ))),
-        }
-    }
-
-    fn compute(&mut self, inputs: &[isize]) -> ACResult<Vec<isize>> {
-        let mut outputs = Vec::new();
-        let mut input_index = 0;
-        self.compute_thread(
-            &mut || {
-                let i = inputs[input_index];
-                input_index += 1;
-                i
-            },
-            &mut |o| outputs.push(o),
-        )?;
-        Ok(outputs)
-    }
-
-    fn compute_thread(
-        &mut self,
-        input: &mut dyn FnMut() -> isize,
-        output: &mut dyn FnMut(isize) -> (),
-    ) -> ACResult<()> {
-        loop {
-            let Command {
-                opcode,
-                command_length,
-            } = self.parse_command()?;
-            match opcode {
-                Opcode::Add(i1, i2, o) => {
-                    self.memory[o] = i1 + i2;
-                    self.pos += command_length;
-                }
-                Opcode::Multiply(i1, i2, o) => {
-                    self.memory[o] = i1 * i2;
-                    self.pos += command_length;
-                }
-                Opcode::Input(o) => {
-                    self.memory[o] = input();
-                    self.pos += command_length;
-                }
-                Opcode::Output(i) => {
-                    output(i);
-                    self.pos += command_length;
-                }
-                Opcode::JumpIfTrue(i1, i2) => {
-                    if i1 != 0 {
-                        self.pos = i2;
-                    } else {
-                        self.pos += command_length;
-                    }
-                }
-                Opcode::JumpIfFalse(i1, i2) => {
-                    if i1 == 0 {
-                        self.pos = i2;
-                    } else {
-                        self.pos += command_length;
-                    }
-                }
-                Opcode::LessThan(i1, i2, o) => {
-                    self.memory[o] = if i1 < i2 { 1 } else { 0 };
-                    self.pos += command_length;
-                }
-                Opcode::Equals(i1, i2, o) => {
-                    self.memory[o] = if i1 == i2 { 1 } else { 0 };
-                    self.pos += command_length;
-                }
-                Opcode::Break => {
-                    break;
-                }
-            }
-        }
-        Ok(())
-    }
-}
-
-fn parse_intcode(input: &str) -> ACResult<Vec<isize>> {
-    input
-        .split(',')
-        .map(|c| {
-            c.parse::<isize>()
-                .map_err(|e| Error::new(format!("Invalid opcode: {}", e)))
-        })
-        .collect()
-}
 
-fn level_1(line: &str) -> ACResult<isize> {
+fn level_1(line: &str) -> ACResult<i64> {
     let ops = parse_intcode(line)?;
-    let mut permutations = Vec::<(isize, isize, isize, isize, isize)>::new();
+    let mut permutations = Vec::<(i64, i64, i64, i64, i64)>::new();
 
     for a1 in 0..5 {
         for a2 in 0..5 {
@@ -338,9 +66,9 @@ fn level_1(line: &str) -> ACResult<isize> {
     Ok(largest.unwrap())
 }
 
-fn level_2(line: &str) -> ACResult<isize> {
+fn level_2(line: &str) -> ACResult<i64> {
     let ops = parse_intcode(line)?;
-    let mut permutations = Vec::<(isize, isize, isize, isize, isize)>::new();
+    let mut permutations = Vec::<(i64, i64, i64, i64, i64)>::new();
 
     for a1 in 5..10 {
         for a2 in 5..10 {
@@ -396,9 +124,16 @@ fn level_2(line: &str) -> ACResult<isize> {
             let ops = ops.clone();
             let thread = thread::spawn(move || {
                 let mut computer = IntCodeComputer::new(&ops);
-                computer
-                    .compute_thread(&mut || rx.recv().unwrap(), &mut |o| tx.send(o).unwrap())
-                    .unwrap();
+                computer.compute_thread(&mut |io| match io {
+                    Io::Input => rx
+                        .recv()
+                        .map_err(|_| Error::new_str("Amplifier input was closed")),
+                    Io::Output(o) => {
+                        tx.send(o)
+                            .map_err(|_| Error::new_str("Amplifier output was closed"))?;
+                        Ok(0)
+                    }
+                })
             });
             threads.push(thread);
         }
@@ -411,7 +146,8 @@ fn level_2(line: &str) -> ACResult<isize> {
         }
 
         for t in threads.into_iter() {
-            t.join().unwrap();
+            t.join()
+                .map_err(|_| Error::new_str("Amplifier panicked"))??;
         }
 
         if let Some(l) = largest {
@@ -1,15 +1,38 @@
-use crate::errors::{ACResult, Error};
+use crate::answer::Answer;
+use crate::errors::{ACResult, Error, ParseError};
+use crate::utils::Field;
+use nom::{character::complete::digit1, IResult};
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_line(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_line(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_line(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_line(data)?).map(Answer::from_picture),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
+fn parse_image(i: &str) -> IResult<&str, &str> {
+    digit1(i)
+}
+
+/// The digits of the image, which must consist of complete layers.
+fn read_image(input: &str, width: u32, height: u32) -> ACResult<&str> {
+    let digits = crate::parsing::parse_all(input, parse_image)?;
+    let layer = (width * height) as usize;
+    if digits.len() % layer != 0 {
+        let last_layer = digits.len() - digits.len() % layer;
+        let expected = format!("a complete layer of {}x{} digits", width, height);
+        return Err(ParseError::at(input, &input[last_layer..], expected).into());
+    }
+    Ok(digits)
+}
+
 fn check_image(input: &str, width: u32, height: u32) -> ACResult<u32> {
+    let input = read_image(input, width, height)?;
     let mut i = 0;
     let mut count0 = 0;
     let mut count1 = 0;
@@ -41,14 +64,15 @@ fn check_image(input: &str, width: u32, height: u32) -> ACResult<u32> {
             count2 = 0;
         }
     }
-    Ok(rest.unwrap())
+    rest.ok_or_else(|| Error::new_str("The image has no layers"))
 }
 
 fn level_1(line: &str) -> ACResult<u32> {
     check_image(line, 25, 6)
 }
 
-fn compute_image(input: &str, width: usize, height: usize) -> ACResult<String> {
+fn compute_image(input: &str, width: usize, height: usize) -> ACResult<Field<bool>> {
+    let input = read_image(input, width as u32, height as u32)?;
     let mut matrix = vec!['2'; width * height];
     let mut i = 0;
     for c in input.chars() {
@@ -60,22 +84,11 @@ fn compute_image(input: &str, width: usize, height: usize) -> ACResult<String> {
             i = 0;
         }
     }
-    let mut result = String::with_capacity(width * height + height);
-    for i in 0..height {
-        for j in 0..width {
-            let c = match matrix[(i * width + j) as usize] {
-                '0' => ' ',
-                '1' => 'X',
-                c => c,
-            };
-            result.push(c);
-        }
-        result.push('\n');
-    }
-    Ok(result)
+    let pixels = matrix.iter().map(|&c| c == '1').collect();
+    Ok(Field::from(pixels, width as u32, height as u32))
 }
 
-fn level_2(line: &str) -> ACResult<String> {
+fn level_2(line: &str) -> ACResult<Field<bool>> {
     compute_image(line, 25, 6)
 }
 
@@ -92,7 +105,7 @@ mod tests {
     fn run_level_2_examples() {
         assert_eq!(
             compute_image("0222112222120000", 2, 2),
-            Ok(" X\nX \n".to_owned())
+            Ok(Field::from(vec![false, true, true, false], 2, 2))
         );
     }
 }
@@ -1,25 +1,38 @@
-use crate::errors::{ACResult, Error};
+use crate::answer::Answer;
+use crate::errors::{ACResult, Error, ParseError};
+use nom::character::complete::one_of;
+use nom::multi::many1;
 use std::f64::consts::PI;
 use std::io::BufRead;
 
-pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<String> {
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
     match level {
-        1 => level_1(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        2 => level_2(&crate::utils::read_lines(data)?).map(|r| r.to_string()),
-        _ => Err(Error::new(format!("Level {} not implemented", level))),
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            "Level {} not implemented",
+            level
+        ))),
     }
 }
 
-fn parse_asteroids(lines: &[String]) -> Vec<(i32, i32)> {
-    let mut asteroids = Vec::new();
-    for (y, l) in lines.iter().enumerate() {
-        for (x, v) in l.chars().enumerate() {
-            if v == '#' {
-                asteroids.push((x as i32, y as i32));
-            }
-        }
+fn parse_asteroids(lines: &[String]) -> ACResult<Vec<(i32, i32)>> {
+    let rows = crate::parsing::parse_lines(lines, many1(one_of(".#")))?;
+    let asteroids: Vec<_> = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, c)| **c == '#')
+                .map(move |(x, _)| (x as i32, y as i32))
+        })
+        .collect();
+    if asteroids.is_empty() {
+        let input = lines.join("\n");
+        return Err(ParseError::at(&input, "", "an asteroid").into());
     }
-    asteroids
+    Ok(asteroids)
 }
 
 fn find_best_asteroid(asteroids: &[(i32, i32)]) -> Option<(u32, (i32, i32))> {
@@ -57,14 +70,16 @@ fn find_best_asteroid(asteroids: &[(i32, i32)]) -> Option<(u32, (i32, i32))> {
 }
 
 fn level_1(lines: &[String]) -> ACResult<u32> {
-    let asteroids = parse_asteroids(lines);
-    let (largest, _point) = find_best_asteroid(&asteroids).unwrap();
+    let asteroids = parse_asteroids(lines)?;
+    let (largest, _point) =
+        find_best_asteroid(&asteroids).ok_or_else(|| Error::new_str("No asteroid found"))?;
     Ok(largest)
 }
 
 fn level_2(lines: &[String]) -> ACResult<i32> {
-    let asteroids = parse_asteroids(lines);
-    let (_largest, base) = find_best_asteroid(&asteroids).unwrap();
+    let asteroids = parse_asteroids(lines)?;
+    let (_largest, base) =
+        find_best_asteroid(&asteroids).ok_or_else(|| Error::new_str("No asteroid found"))?;
     let mut asteroids = asteroids
         .iter()
         .filter(|a| **a != base)
@@ -82,7 +97,7 @@ fn level_2(lines: &[String]) -> ACResult<i32> {
     let mut start_angle = 1.5 * PI;
     let mut blocked_angle = None;
     let mut shot_down = 0;
-    loop {
+    while !asteroids.is_empty() {
         let mut next = None;
         let mut index = 0;
         for (i, a) in asteroids.iter().enumerate() {
@@ -107,11 +122,15 @@ fn level_2(lines: &[String]) -> ACResult<i32> {
                 index = i;
             }
         }
-        if next.is_none() {
-            start_angle = 0.0;
-            continue;
-        }
-        let next = next.unwrap();
+        let next = match next {
+            Some(next) => next,
+            None => {
+                // A new rotation, the laser reaches the asteroids behind the last one again
+                start_angle = 0.0;
+                blocked_angle = None;
+                continue;
+            }
+        };
         start_angle = next.2;
         blocked_angle = Some(next.2);
         asteroids.remove(index);
@@ -119,9 +138,6 @@ fn level_2(lines: &[String]) -> ACResult<i32> {
         if shot_down == 200 {
             return Ok((next.0).0 * 100 + (next.0).1);
         }
-        if asteroids.is_empty() {
-            break;
-        }
     }
     Err(Error::new_str("Failed"))
 }
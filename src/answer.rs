@@ -0,0 +1,113 @@
+use crate::utils::Field;
+use std::fmt;
+
+/// Result of solving a puzzle level.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer {
+    Integer(i128),
+    Text(String),
+    /// Pixels that show letters, the actual answer has to be read from the picture
+    Picture(Field<bool>),
+}
+
+impl Answer {
+    /// Reads the letters in the picture, the picture is kept as the answer if a letter isn't
+    /// recognized.
+    pub fn from_picture(picture: Field<bool>) -> Self {
+        match crate::ocr::recognize(&picture) {
+            Some(text) => Answer::Text(text),
+            None => Answer::Picture(picture),
+        }
+    }
+
+    /// The form in which the answer is recorded and submitted.
+    pub fn canonical(&self) -> String {
+        match self {
+            Answer::Integer(i) => i.to_string(),
+            Answer::Text(t) => t.trim().to_owned(),
+            Answer::Picture(p) => render(p, '#', '.'),
+        }
+    }
+
+    /// Compares the answer with an answer given as text, e.g. a recorded answer.
+    ///
+    /// Integers are compared numerically, so formatting differences like leading zeros don't
+    /// matter.
+    pub fn matches(&self, other: &str) -> bool {
+        match self {
+            Answer::Integer(i) => other.trim().parse::<i128>() == Ok(*i),
+            Answer::Text(_) | Answer::Picture(_) => self.canonical() == other.trim(),
+        }
+    }
+}
+
+fn render(picture: &Field<bool>, on: char, off: char) -> String {
+    picture
+        .display(|pixel| if *pixel { on } else { off })
+        .to_string()
+}
+
+/// Pictures are rendered with block characters, so the letters are easy to read in a terminal.
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Integer(i) => fmt::Display::fmt(i, f),
+            Answer::Text(t) => fmt::Display::fmt(t, f),
+            Answer::Picture(p) => f.write_str(&render(p, '█', ' ')),
+        }
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(i: $t) -> Self {
+                    Answer::Integer(i as i128)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, i128);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_owned())
+    }
+}
+
+impl From<Field<bool>> for Answer {
+    fn from(picture: Field<bool>) -> Self {
+        Answer::Picture(picture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_answers() {
+        assert!(Answer::from(42u64).matches("042\n"));
+        assert!(!Answer::from(42u64).matches("43"));
+        assert!(Answer::from("ABC\n").matches("ABC"));
+        assert!(!Answer::from("ABC").matches("42"));
+    }
+
+    #[test]
+    fn render_picture() {
+        let picture = Field::from(vec![true, false, false, true], 2, 2);
+        let answer = Answer::from(picture);
+        assert_eq!(answer.canonical(), "#.\n.#");
+        assert_eq!(answer.to_string(), "█ \n █");
+        assert!(answer.matches("#.\n.#\n"));
+    }
+}
@@ -0,0 +1,37 @@
+use crate::cache::Cache;
+use crate::config::Profile;
+use advent_of_code_solver::errors::{ACResult, Context};
+
+/// Returns the answer that was recorded as correct for this profile.
+pub fn load_answer(
+    cache: &Cache,
+    profile: &Profile,
+    year: i32,
+    day: u8,
+    level: u8,
+) -> ACResult<Option<String>> {
+    match cache.find_file(profile.answer_file_name(year, day, level)) {
+        Some(path) => std::fs::read_to_string(path)
+            .map(|answer| Some(answer.trim_end().to_owned()))
+            .context("Failed to load answer file"),
+        None => Ok(None),
+    }
+}
+
+pub fn store_answer(
+    cache: &Cache,
+    profile: &Profile,
+    year: i32,
+    day: u8,
+    level: u8,
+    answer: &str,
+    verbose: bool,
+) -> ACResult<()> {
+    let path = cache
+        .place_file(profile.answer_file_name(year, day, level))
+        .context("Failed to store answer file")?;
+    if verbose {
+        eprintln!("Storing answer to: {:?}", &path);
+    }
+    std::fs::write(path, format!("{}\n", answer)).context("Failed to store answer file")
+}
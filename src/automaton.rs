@@ -0,0 +1,373 @@
+use crate::cycle::Cycle;
+use crate::errors::ACResult;
+use crate::progress;
+pub use crate::utils::Neighbourhood;
+use crate::utils::{Field, SparseLine};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Cellular automaton on a field, the rule gets a cell and its neighbours inside the field and
+/// returns the cell of the next generation.
+///
+/// The next generation is written into a second field that is swapped with the current one, so
+/// no state is cloned while simulating.
+pub struct FieldAutomaton<T, R> {
+    current: Field<T>,
+    next: Field<T>,
+    neighbourhood: Neighbourhood,
+    rule: R,
+    generation: u64,
+}
+
+impl<T: Clone, R: Fn(&T, &[&T]) -> T> FieldAutomaton<T, R> {
+    pub fn new(field: Field<T>, neighbourhood: Neighbourhood, rule: R) -> Self {
+        FieldAutomaton {
+            next: field.clone(),
+            current: field,
+            neighbourhood,
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn state(&self) -> &Field<T> {
+        &self.current
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn step(&mut self) {
+        next_field(
+            &self.current,
+            &mut self.next,
+            self.neighbourhood,
+            &self.rule,
+        );
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.generation += 1;
+    }
+
+    /// Simulates the steps from the start state without changing the automaton.
+    fn replay(&self, start: &Field<T>, steps: u64) -> Field<T> {
+        let mut current = start.clone();
+        let mut next = start.clone();
+        for _ in 0..steps {
+            next_field(&current, &mut next, self.neighbourhood, &self.rule);
+            std::mem::swap(&mut current, &mut next);
+        }
+        current
+    }
+
+    /// Simulates until the generation, calling `observe` with every simulated state before its
+    /// next step.
+    ///
+    /// Once a state repeats, the remaining full cycles are skipped. Only a hash of every state is
+    /// kept, a repeated hash is confirmed by simulating the earlier state again from the first
+    /// one. Returns the found cycle, fails if the run was cancelled.
+    pub fn skip_to<F>(&mut self, generation: u64, mut observe: F) -> ACResult<Option<Cycle>>
+    where
+        T: Hash + Eq,
+        F: FnMut(&Field<T>, u64),
+    {
+        let start = self.current.clone();
+        let first_generation = self.generation;
+        let mut detector = FingerprintDetector::new();
+        let mut cycle = None;
+        while self.generation < generation {
+            observe(&self.current, self.generation);
+            if cycle.is_none() {
+                let current = &self.current;
+                let found = detector.insert(current, self.generation, |earlier| {
+                    self.replay(&start, earlier - first_generation) == *current
+                });
+                if let Some(found) = found {
+                    self.generation = generation - (generation - self.generation) % found.period;
+                    cycle = Some(found);
+                    continue;
+                }
+            }
+            self.step();
+            progress::step(self.generation, || {
+                format!("{} generations simulated", self.generation)
+            })?;
+        }
+        observe(&self.current, self.generation);
+        Ok(cycle)
+    }
+}
+
+fn next_field<T, R: Fn(&T, &[&T]) -> T>(
+    current: &Field<T>,
+    next: &mut Field<T>,
+    neighbourhood: Neighbourhood,
+    rule: &R,
+) {
+    let mut neighbours = Vec::with_capacity(8);
+    for (x, y) in current.positions() {
+        neighbours.clear();
+        neighbours.extend(
+            current
+                .neighbours(x, y, neighbourhood)
+                .map(|(x, y)| current.get(x, y)),
+        );
+        *next.get_mut(x, y) = rule(current.get(x, y), &neighbours);
+    }
+}
+
+/// Cellular automaton on an infinite line, missing positions are empty.
+///
+/// The rule gets the window of `2 * radius + 1` positions around a position and returns its
+/// value in the next generation. Empty windows must stay empty.
+pub struct LineAutomaton<T, R> {
+    current: SparseLine<T>,
+    next: SparseLine<T>,
+    radius: usize,
+    rule: R,
+    generation: u64,
+}
+
+impl<T, R: Fn(&[Option<&T>]) -> Option<T>> LineAutomaton<T, R> {
+    pub fn new(line: SparseLine<T>, radius: usize, rule: R) -> Self {
+        LineAutomaton {
+            current: line,
+            next: SparseLine::new(),
+            radius,
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn state(&self) -> &SparseLine<T> {
+        &self.current
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn step(&mut self) {
+        next_line(&self.current, &mut self.next, self.radius, &self.rule);
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.generation += 1;
+    }
+
+    /// Simulates the steps from the start state without changing the automaton.
+    fn replay(&self, start: &SparseLine<T>, steps: u64) -> SparseLine<T>
+    where
+        T: Clone,
+    {
+        let mut current = start.clone();
+        let mut next = SparseLine::new();
+        for _ in 0..steps {
+            next_line(&current, &mut next, self.radius, &self.rule);
+            std::mem::swap(&mut current, &mut next);
+        }
+        current
+    }
+
+    /// Simulates until the generation, calling `observe` with every simulated state before its
+    /// next step.
+    ///
+    /// Once a pattern repeats, possibly at another position, the remaining full cycles are skipped
+    /// by moving the pattern. Only a hash and the position of every pattern are kept, a repeated
+    /// hash is confirmed by simulating the earlier pattern again from the first one. Returns the
+    /// found cycle with the distance the pattern moves in a period, fails if the run was
+    /// cancelled.
+    pub fn skip_to<F>(&mut self, generation: u64, mut observe: F) -> ACResult<Option<(Cycle, i64)>>
+    where
+        T: Hash + Eq + Clone,
+        F: FnMut(&SparseLine<T>, u64),
+    {
+        let start = self.current.clone();
+        let first_generation = self.generation;
+        let mut detector = FingerprintDetector::new();
+        let mut offsets = Vec::new();
+        let mut cycle = None;
+        while self.generation < generation {
+            observe(&self.current, self.generation);
+            if cycle.is_none() {
+                let (offset, relative) = pattern(&self.current);
+                offsets.push(offset);
+                let found = detector.insert(&relative, self.generation, |earlier| {
+                    pattern(&self.replay(&start, earlier - first_generation)).1 == relative
+                });
+                if let Some(found) = found {
+                    let shift = offset - offsets[(found.start - first_generation) as usize];
+                    let cycles = (generation - self.generation) / found.period;
+                    self.current.shift(shift * cycles as i64);
+                    self.generation += cycles * found.period;
+                    cycle = Some((found, shift));
+                    continue;
+                }
+            }
+            self.step();
+            progress::step(self.generation, || {
+                format!("{} generations simulated", self.generation)
+            })?;
+        }
+        observe(&self.current, self.generation);
+        Ok(cycle)
+    }
+}
+
+fn next_line<T, R: Fn(&[Option<&T>]) -> Option<T>>(
+    current: &SparseLine<T>,
+    next: &mut SparseLine<T>,
+    radius: usize,
+    rule: &R,
+) {
+    next.clear();
+    if let Some((min, max)) = current.bounds() {
+        let radius = radius as i64;
+        let mut window = Vec::with_capacity(2 * radius as usize + 1);
+        for i in min - radius..=max + radius {
+            window.clear();
+            window.extend((i - radius..=i + radius).map(|j| current.get(j)));
+            if let Some(value) = rule(&window) {
+                next.insert(i, value);
+            }
+        }
+    }
+}
+
+/// The position of the line and its values relative to it, which is the same for a moved line.
+fn pattern<T: Clone>(line: &SparseLine<T>) -> (i64, Vec<(i64, T)>) {
+    let offset = line.bounds().map_or(0, |(min, _)| min);
+    let pattern = line.iter().map(|(i, v)| (i - offset, v.clone())).collect();
+    (offset, pattern)
+}
+
+/// Finds cycles by remembering only a hash of every state, for states that are too large to keep.
+#[derive(Debug, Clone, Default)]
+struct FingerprintDetector {
+    seen: HashMap<u64, Vec<u64>>,
+}
+
+impl FingerprintDetector {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the hash of the state and returns the cycle if an earlier step had the same state.
+    ///
+    /// Hashes can collide, so an earlier step with the same hash is only a candidate: `matches`
+    /// gets its step and confirms that its state equals this one, e.g. by recomputing it.
+    fn insert<S, F>(&mut self, state: &S, step: u64, mut matches: F) -> Option<Cycle>
+    where
+        S: Hash + ?Sized,
+        F: FnMut(u64) -> bool,
+    {
+        let mut hasher = DefaultHasher::new();
+        state.hash(&mut hasher);
+        let steps = self.seen.entry(hasher.finish()).or_default();
+        match steps.iter().find(|start| matches(**start)) {
+            Some(&start) => Some(Cycle {
+                start,
+                period: step - start,
+            }),
+            None => {
+                steps.push(step);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn life(cell: &bool, neighbours: &[&bool]) -> bool {
+        let alive = neighbours.iter().filter(|n| ***n).count();
+        alive == 3 || (*cell && alive == 2)
+    }
+
+    #[test]
+    fn blinker_cycle() {
+        let field = Field::parse(".....\n..#..\n..#..\n..#..\n.....\n", |c| Ok(c == '#')).unwrap();
+        let mut automaton = FieldAutomaton::new(field.clone(), Neighbourhood::Moore, life);
+        automaton.step();
+        assert_eq!(automaton.state().row(2).iter().filter(|c| **c).count(), 3);
+        let mut observed = 0;
+        let cycle = automaton.skip_to(1_000_001, |_, _| observed += 1).unwrap();
+        assert_eq!(
+            cycle,
+            Some(Cycle {
+                start: 1,
+                period: 2
+            })
+        );
+        assert_eq!(automaton.generation(), 1_000_001);
+        assert_eq!(automaton.state().row(2).iter().filter(|c| **c).count(), 3);
+        assert!(observed < 10);
+        automaton.skip_to(1_000_002, |_, _| {}).unwrap();
+        assert_eq!(automaton.state(), &field);
+    }
+
+    #[test]
+    fn von_neumann_growth() {
+        let field = Field::parse("...\n.#.\n...\n", |c| Ok(c == '#')).unwrap();
+        let mut automaton = FieldAutomaton::new(field, Neighbourhood::VonNeumann, |c, n| {
+            *c || n.iter().any(|n| **n)
+        });
+        automaton.step();
+        assert_eq!(
+            automaton
+                .state()
+                .display(|c| if *c { '#' } else { '.' })
+                .to_string(),
+            ".#.\n###\n.#."
+        );
+    }
+
+    #[test]
+    fn glider_on_line() {
+        let mut line = SparseLine::new();
+        line.insert(0, ());
+        line.insert(1, ());
+        // Everything moves one position to the right
+        let mut automaton = LineAutomaton::new(line, 1, |window| window[0].copied());
+        let cycle = automaton.skip_to(1_000_000, |_, _| {}).unwrap();
+        assert_eq!(
+            cycle,
+            Some((
+                Cycle {
+                    start: 0,
+                    period: 1
+                },
+                1
+            ))
+        );
+        assert_eq!(automaton.state().bounds(), Some((1_000_000, 1_000_001)));
+    }
+    #[test]
+    fn fingerprint_detector_confirms_matches() {
+        let values = ["a", "b", "c", "b"];
+        let mut detector = FingerprintDetector::new();
+        let cycle = values.iter().enumerate().find_map(|(step, value)| {
+            detector.insert(value, step as u64, |start| values[start as usize] == *value)
+        });
+        assert_eq!(
+            cycle,
+            Some(Cycle {
+                start: 1,
+                period: 2
+            })
+        );
+
+        // A collision that isn't confirmed is recorded as a new candidate
+        let mut detector = FingerprintDetector::new();
+        assert_eq!(detector.insert("a", 0, |_| false), None);
+        assert_eq!(detector.insert("a", 1, |_| false), None);
+        assert_eq!(
+            detector.insert("a", 2, |start| start == 1),
+            Some(Cycle {
+                start: 1,
+                period: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::cache::Cache;
+use advent_of_code_solver::errors::{ACResult, Context, Error};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Benchmark results of earlier runs, one JSON object per line.
+const HISTORY_FILE: &str = "bench/history.jsonl";
+
+/// Timing statistics in seconds.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: f64,
+    pub median: f64,
+    pub p95: f64,
+}
+
+impl Stats {
+    /// Returns `None` if there are no samples.
+    pub fn from_samples(samples: &[Duration]) -> Option<Stats> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut seconds: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        seconds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let percentile = |p: usize| seconds[(seconds.len() * p).div_ceil(100).max(1) - 1];
+        Some(Stats {
+            min: seconds[0],
+            median: percentile(50),
+            p95: percentile(95),
+        })
+    }
+}
+
+/// Timing of one puzzle level, also stored in the history file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub timestamp: String,
+    pub year: i32,
+    pub day: u8,
+    pub level: u8,
+    pub profile: String,
+    pub runs: usize,
+    /// Parsing and solving together
+    pub total: Stats,
+    /// Only set for days that expose their input parsing
+    pub parse: Option<Stats>,
+    /// Change of the median compared to the previous run, e.g. 0.25 for 25% slower
+    #[serde(skip_deserializing)]
+    pub change: Option<f64>,
+}
+
+impl BenchResult {
+    pub fn is_same_puzzle(&self, other: &BenchResult) -> bool {
+        (self.year, self.day, self.level, &self.profile)
+            == (other.year, other.day, other.level, &other.profile)
+    }
+
+    /// A regression is a median that got slower by more than the threshold, e.g. 0.1 for 10%.
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        matches!(self.change, Some(change) if change > threshold)
+    }
+}
+
+/// Runs the operation once to warm up, then measures the given number of runs.
+pub fn measure<T>(
+    runs: usize,
+    mut operation: impl FnMut() -> ACResult<T>,
+) -> ACResult<Vec<Duration>> {
+    operation()?;
+    let mut samples = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let start = Instant::now();
+        operation()?;
+        samples.push(start.elapsed());
+    }
+    Ok(samples)
+}
+
+pub fn load_history(cache: &Cache) -> ACResult<Vec<BenchResult>> {
+    let path = match cache.find_file(HISTORY_FILE) {
+        Some(path) => path,
+        None => return Ok(Vec::new()),
+    };
+    let history = std::fs::read_to_string(path).context("Failed to load benchmark history")?;
+    history
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .map_err(|e| Error::Parse(format!("Invalid benchmark history entry: {}", e)))
+        })
+        .collect()
+}
+
+/// Sets the change compared to the latest matching result of the history.
+pub fn compare_with_history(result: &mut BenchResult, history: &[BenchResult]) {
+    result.change = history
+        .iter()
+        .rev()
+        .find(|previous| previous.is_same_puzzle(result))
+        .filter(|previous| previous.total.median > 0.0)
+        .map(|previous| result.total.median / previous.total.median - 1.0);
+}
+
+pub fn append_history(cache: &Cache, results: &[BenchResult]) -> ACResult<()> {
+    let path = cache
+        .place_file(HISTORY_FILE)
+        .context("Failed to store benchmark history")?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context("Failed to store benchmark history")?;
+    for result in results {
+        writeln!(file, "{}", crate::report::to_json(result)?)
+            .context("Failed to store benchmark history")?;
+    }
+    Ok(())
+}
+
+pub fn print_table(results: &[BenchResult], threshold: f64) {
+    println!(
+        "{:<12} {:>5} {:>10} {:>10} {:>10} {:>10} {:>8}",
+        "puzzle", "runs", "min", "median", "p95", "parse", "change"
+    );
+    for result in results {
+        let change = match result.change {
+            Some(change) => format!("{:+.1}%", change * 100.0),
+            None => "-".to_owned(),
+        };
+        println!(
+            "{:<12} {:>5} {:>10} {:>10} {:>10} {:>10} {:>8}{}",
+            format!("{} {:>2}/{}", result.year, result.day, result.level),
+            result.runs,
+            format_seconds(result.total.min),
+            format_seconds(result.total.median),
+            format_seconds(result.total.p95),
+            result
+                .parse
+                .map_or("-".to_owned(), |parse| format_seconds(parse.median)),
+            change,
+            if result.is_regression(threshold) {
+                "  REGRESSION"
+            } else {
+                ""
+            }
+        );
+    }
+}
+
+fn format_seconds(seconds: f64) -> String {
+    if seconds >= 1.0 {
+        format!("{:.2}s", seconds)
+    } else if seconds >= 1e-3 {
+        format!("{:.2}ms", seconds * 1e3)
+    } else {
+        format!("{:.1}µs", seconds * 1e6)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(median: f64) -> BenchResult {
+        BenchResult {
+            timestamp: String::new(),
+            year: 2018,
+            day: 15,
+            level: 1,
+            profile: "default".to_owned(),
+            runs: 3,
+            total: Stats {
+                min: median,
+                median,
+                p95: median,
+            },
+            parse: None,
+            change: None,
+        }
+    }
+
+    #[test]
+    fn stats_of_samples() {
+        let samples: Vec<Duration> = (1..=20).rev().map(Duration::from_secs).collect();
+        assert_eq!(
+            Stats::from_samples(&samples),
+            Some(Stats {
+                min: 1.0,
+                median: 10.0,
+                p95: 19.0
+            })
+        );
+        assert_eq!(
+            Stats::from_samples(&[Duration::from_secs(2)]),
+            Some(Stats {
+                min: 2.0,
+                median: 2.0,
+                p95: 2.0
+            })
+        );
+        assert_eq!(Stats::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn regression_against_previous_run() {
+        let mut other_level = result(1.0);
+        other_level.level = 2;
+        let history = vec![result(4.0), result(2.0), other_level];
+        let mut current = result(2.5);
+        compare_with_history(&mut current, &history);
+        assert_eq!(current.change, Some(0.25));
+        assert!(current.is_regression(0.1));
+        assert!(!current.is_regression(0.5));
+        let mut first = result(1.0);
+        first.year = 2019;
+        compare_with_history(&mut first, &history);
+        assert_eq!(first.change, None);
+        assert!(!first.is_regression(0.1));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::config::Config;
+use crate::http::LAST_REQUEST_FILE;
+use advent_of_code_solver::errors::{ACResult, Context, Error};
+use std::path::{Path, PathBuf};
+
+/// Overrides the data directory from the config file.
+pub const DATA_DIR_ENV: &str = "ADVENT_OF_CODE_SOLVER_DATA_DIR";
+
+/// Directory with the downloaded inputs and descriptions, recorded answers and examples.
+///
+/// The directory can be shared, e.g. by a team that wants to use the same input cache.
+pub struct Cache {
+    dir: PathBuf,
+    /// Missing files are never downloaded in offline mode
+    pub offline: bool,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf, offline: bool) -> Self {
+        Cache { dir, offline }
+    }
+
+    /// Uses the directory from the environment variable, the config file or the XDG data
+    /// directory, in that order.
+    pub fn load(xdg_dirs: &xdg::BaseDirectories, config: &Config, offline: bool) -> Self {
+        let dir = std::env::var_os(DATA_DIR_ENV)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| config.data_dir.clone())
+            .unwrap_or_else(|| xdg_dirs.get_data_home());
+        Cache::new(dir, offline)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn find_file<P: AsRef<Path>>(&self, name: P) -> Option<PathBuf> {
+        let path = self.dir.join(name);
+        if path.is_file() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the path for a new file, creating the parent directories.
+    pub fn place_file<P: AsRef<Path>>(&self, name: P) -> std::io::Result<PathBuf> {
+        let path = self.dir.join(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(path)
+    }
+
+    /// Fails with a description of the missing file in offline mode.
+    pub fn check_online(&self, missing: &str) -> ACResult<()> {
+        if self.offline {
+            Err(Error::Config(format!(
+                "{} is not cached and downloading is disabled in offline mode",
+                missing
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Lists the cached files relative to the cache directory, optionally only those of one
+    /// year or puzzle.
+    pub fn files(&self, year: Option<i32>, day: Option<u8>) -> ACResult<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        if self.dir.is_dir() {
+            collect_files(&self.dir, Path::new(""), &mut files)
+                .context("Failed to list cache directory")?;
+        }
+        files.retain(|file| {
+            if file == Path::new(LAST_REQUEST_FILE) {
+                return false;
+            }
+            let puzzle = puzzle_of(file);
+            (year.is_none() || puzzle.map(|p| p.0) == year)
+                && (day.is_none() || puzzle.map(|p| p.1) == day)
+        });
+        files.sort();
+        Ok(files)
+    }
+
+    /// Copies the cached files into another directory, e.g. to share them.
+    pub fn export(&self, files: &[PathBuf], target: &Path) -> ACResult<()> {
+        for file in files {
+            let target_path = target.join(file);
+            if let Some(parent) = target_path.parent() {
+                std::fs::create_dir_all(parent).context("Failed to export cache")?;
+            }
+            std::fs::copy(self.dir.join(file), &target_path)
+                .with_context(|| format!("Failed to export {}", file.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Copies the files of an exported cache directory into the cache.
+    ///
+    /// Existing files are kept, the names of the skipped files that differ are returned.
+    pub fn import(&self, source: &Path) -> ACResult<(Vec<PathBuf>, Vec<PathBuf>)> {
+        let mut files = Vec::new();
+        collect_files(source, Path::new(""), &mut files).context("Failed to read import dir")?;
+        files.sort();
+        let mut imported = Vec::new();
+        let mut conflicts = Vec::new();
+        for file in files {
+            let content = std::fs::read(source.join(&file))
+                .with_context(|| format!("Failed to import {}", file.display()))?;
+            match self.find_file(&file) {
+                Some(existing) => {
+                    if std::fs::read(existing).ok().as_ref() != Some(&content) {
+                        conflicts.push(file);
+                    }
+                }
+                None => {
+                    let path = self.place_file(&file).context("Failed to import cache")?;
+                    std::fs::write(path, content)
+                        .with_context(|| format!("Failed to import {}", file.display()))?;
+                    imported.push(file);
+                }
+            }
+        }
+        Ok((imported, conflicts))
+    }
+
+    /// Finds files left behind by failed downloads: empty files and inputs that contain an
+    /// error page instead of the puzzle input.
+    pub fn invalid_files(&self, files: &[PathBuf]) -> Vec<PathBuf> {
+        files
+            .iter()
+            .filter(|file| {
+                let content = std::fs::read_to_string(self.dir.join(file)).unwrap_or_default();
+                content.trim().is_empty()
+                    || (is_input(file)
+                        && (content.starts_with("Puzzle inputs differ by user")
+                            || content.starts_with("Please don't repeatedly request")
+                            || content.starts_with("<!DOCTYPE html>")))
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn remove(&self, file: &Path) -> ACResult<()> {
+        std::fs::remove_file(self.dir.join(file))
+            .with_context(|| format!("Failed to remove {}", file.display()))
+    }
+}
+
+fn collect_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Strips the profile namespace, e.g. `profiles/alice/input/2019/3` becomes `input/2019/3`.
+fn without_profile(file: &Path) -> Vec<String> {
+    let components: Vec<String> = file
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if components.first().map(String::as_str) == Some("profiles") {
+        components.into_iter().skip(2).collect()
+    } else {
+        components
+    }
+}
+
+fn is_input(file: &Path) -> bool {
+    without_profile(file).first().map(String::as_str) == Some("input")
+}
+
+/// Cached files are stored as `kind/year/day...`.
+fn puzzle_of(file: &Path) -> Option<(i32, u8)> {
+    let components = without_profile(file);
+    let year = components.get(1)?.parse().ok()?;
+    let day = components.get(2)?.split('.').next()?.parse().ok()?;
+    Some((year, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn puzzle_of_cached_files() {
+        assert_eq!(puzzle_of(Path::new("input/2019/3")), Some((2019, 3)));
+        assert_eq!(
+            puzzle_of(Path::new("profiles/alice/answers/2018/12/2")),
+            Some((2018, 12))
+        );
+        assert_eq!(
+            puzzle_of(Path::new("description/2017/25.html")),
+            Some((2017, 25))
+        );
+        assert_eq!(puzzle_of(Path::new("last_request")), None);
+        assert!(is_input(Path::new("profiles/alice/input/2019/3")));
+        assert!(!is_input(Path::new("description/2017/25.html")));
+    }
+
+    #[test]
+    fn export_and_import() {
+        let base = std::env::temp_dir().join(format!("aoc_cache_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let source = Cache::new(base.join("source"), true);
+        let target = Cache::new(base.join("target"), true);
+        for (name, content) in &[("input/2019/3", "R8,U5\n"), ("answers/2019/3/1", "6\n")] {
+            std::fs::write(source.place_file(name).unwrap(), content).unwrap();
+        }
+        std::fs::write(target.place_file("answers/2019/3/1").unwrap(), "7\n").unwrap();
+
+        let files = source.files(Some(2019), None).unwrap();
+        source.export(&files, &base.join("export")).unwrap();
+        let (imported, conflicts) = target.import(&base.join("export")).unwrap();
+        assert_eq!(imported, vec![PathBuf::from("input/2019/3")]);
+        assert_eq!(conflicts, vec![PathBuf::from("answers/2019/3/1")]);
+        assert!(target.find_file("input/2019/3").is_some());
+        assert!(target.files(Some(2018), None).unwrap().is_empty());
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+use advent_of_code_solver::errors::{ACResult, Context, Error};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Content of the `config.toml` file.
+///
+/// The top level `session_token` belongs to the default profile, additional accounts can be
+/// configured as named profiles:
+///
+/// ```toml
+/// session_token = "..."
+///
+/// [profiles.alice]
+/// session_token = "..."
+/// ```
+///
+/// The cached inputs and answers are stored in the XDG data directory, unless `data_dir` is set.
+#[derive(Deserialize, Default)]
+pub struct Config {
+    pub data_dir: Option<PathBuf>,
+    /// Contact address sent in the User-Agent header, so the site owner can reach out about
+    /// the requests
+    pub contact: Option<String>,
+    session_token: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, ProfileConfig>,
+}
+
+#[derive(Deserialize, Clone)]
+struct ProfileConfig {
+    session_token: Option<String>,
+}
+
+/// An account with its own session token and its own namespace for cached inputs and answers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: Option<String>,
+    pub session_token: Option<String>,
+}
+
+impl Config {
+    pub fn load(xdg_dirs: &xdg::BaseDirectories) -> ACResult<Config> {
+        let config_path = match xdg_dirs.find_config_file("config.toml") {
+            Some(config_path) => config_path,
+            None => return Ok(Config::default()),
+        };
+        let config_string =
+            std::fs::read_to_string(config_path).context("Failed to load config file")?;
+        let config: Config =
+            toml::from_str(&config_string).context("Failed to parse config file")?;
+        for name in config.profiles.keys() {
+            check_profile_name(name)?;
+        }
+        Ok(config)
+    }
+
+    /// Returns the named profile or the default profile if no name is given.
+    pub fn profile(&self, name: Option<&str>) -> ACResult<Profile> {
+        match name {
+            None => Ok(Profile {
+                name: None,
+                session_token: self.session_token.clone(),
+            }),
+            Some(name) => check_profile_name(name).and_then(|_| {
+                self.profiles
+                    .get(name)
+                    .map(|p| Profile {
+                        name: Some(name.to_owned()),
+                        session_token: p.session_token.clone(),
+                    })
+                    .ok_or_else(|| Error::Config(format!("Profile {} not found in config", name)))
+            }),
+        }
+    }
+
+    /// Returns all configured profiles, starting with the default profile if it has a session
+    /// token.
+    pub fn profiles(&self) -> Vec<Profile> {
+        let default = Profile {
+            name: None,
+            session_token: self.session_token.clone(),
+        };
+        let named = self.profiles.iter().map(|(name, p)| Profile {
+            name: Some(name.clone()),
+            session_token: p.session_token.clone(),
+        });
+        Some(default)
+            .filter(|p| p.session_token.is_some())
+            .into_iter()
+            .chain(named)
+            .collect()
+    }
+}
+
+/// Profile names become a directory in the data directory, so they must not reach outside of it.
+fn check_profile_name(name: &str) -> ACResult<()> {
+    if name.is_empty() || name.contains('/') || name.contains('\\') || name.contains("..") {
+        return Err(Error::Config(format!("Invalid profile name {:?}", name)));
+    }
+    Ok(())
+}
+
+impl Profile {
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("default")
+    }
+
+    /// The default profile uses the top level of the data directory, so existing caches keep
+    /// working.
+    fn namespace(&self) -> String {
+        match &self.name {
+            None => String::new(),
+            Some(name) => format!("profiles/{}/", name),
+        }
+    }
+
+    pub fn input_file_name(&self, year: i32, day: u8) -> String {
+        format!("{}input/{}/{}", self.namespace(), year, day)
+    }
+
+    pub fn answer_file_name(&self, year: i32, day: u8, level: u8) -> String {
+        format!("{}answers/{}/{}/{}", self.namespace(), year, day, level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "session_token = \"abc\"
+
+[profiles.alice]
+session_token = \"def\"
+
+[profiles.bob]
+";
+
+    #[test]
+    fn select_profiles() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        assert_eq!(
+            config.profile(None).map(|p| p.session_token),
+            Ok(Some("abc".to_owned()))
+        );
+        assert_eq!(
+            config.profile(Some("alice")).map(|p| p.session_token),
+            Ok(Some("def".to_owned()))
+        );
+        assert!(config.profile(Some("carol")).is_err());
+        assert_eq!(
+            config
+                .profiles()
+                .iter()
+                .map(|p| p.display_name())
+                .collect::<Vec<_>>(),
+            vec!["default", "alice", "bob"]
+        );
+    }
+
+    #[test]
+    fn reject_path_like_profile_names() {
+        for name in &["", "../x", "a/b", "a\\b", ".."] {
+            assert!(check_profile_name(name).is_err());
+        }
+        let config: Config = toml::from_str("[profiles.\"../x\"]\n").unwrap();
+        assert!(config.profile(Some("../x")).is_err());
+    }
+
+    #[test]
+    fn profile_namespaces() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        let default = config.profile(None).unwrap();
+        let alice = config.profile(Some("alice")).unwrap();
+        assert_eq!(default.input_file_name(2019, 3), "input/2019/3");
+        assert_eq!(
+            alice.input_file_name(2019, 3),
+            "profiles/alice/input/2019/3"
+        );
+        assert_eq!(
+            alice.answer_file_name(2019, 3, 2),
+            "profiles/alice/answers/2019/3/2"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use crate::errors::ACResult;
+use crate::progress;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Repetition in a sequence of states: the state of step `start + period` is the state of step
+/// `start` again. Also known as mu and lambda.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: u64,
+    pub period: u64,
+}
+
+impl Cycle {
+    /// The first step with the same state as step `n`, it's before the end of the first cycle.
+    pub fn project(&self, n: u64) -> u64 {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+
+    /// The first step with a repeated state, all states up to it are distinct.
+    pub fn end(&self) -> u64 {
+        self.start + self.period
+    }
+}
+
+/// Finds cycles by remembering every state, for loops that produce their states one by one.
+#[derive(Debug, Clone)]
+pub struct HashDetector<S> {
+    seen: HashMap<S, u64>,
+}
+
+impl<S: Hash + Eq> Default for HashDetector<S> {
+    fn default() -> Self {
+        HashDetector {
+            seen: HashMap::new(),
+        }
+    }
+}
+
+impl<S: Hash + Eq> HashDetector<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the state of the step and returns the cycle if the state was seen before.
+    pub fn insert(&mut self, state: S, step: u64) -> Option<Cycle> {
+        self.seen.insert(state, step).map(|start| Cycle {
+            start,
+            period: step - start,
+        })
+    }
+}
+
+/// Finds the cycle of the states produced by repeatedly applying `next` to the start state, by
+/// remembering every state.
+///
+/// Fails if the run was cancelled, e.g. because there is no cycle.
+pub fn find_with_hash<S, F>(start: S, mut next: F) -> ACResult<Cycle>
+where
+    S: Hash + Eq + Clone,
+    F: FnMut(&S) -> S,
+{
+    let mut detector = HashDetector::new();
+    let mut state = start;
+    let mut step = 0;
+    loop {
+        let following = next(&state);
+        if let Some(cycle) = detector.insert(state, step) {
+            return Ok(cycle);
+        }
+        state = following;
+        step += 1;
+        progress::step(step, || format!("{} states without cycle", step))?;
+    }
+}
+
+/// Finds the cycle of the states produced by repeatedly applying `next` to the start state with
+/// Brent's algorithm, which only keeps two states at a time but applies `next` more often.
+///
+/// Fails if the run was cancelled, e.g. because there is no cycle.
+pub fn brent<S, F>(start: S, mut next: F) -> ACResult<Cycle>
+where
+    S: Eq + Clone,
+    F: FnMut(&S) -> S,
+{
+    // Find the period by moving the tortoise to the hare at every power of two
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = next(&start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = next(&hare);
+        period += 1;
+        progress::step(period, || {
+            format!("{} states without cycle", power + period)
+        })?;
+    }
+
+    // Find the start with a hare that is one period ahead
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..period {
+        hare = next(&hare);
+    }
+    let mut cycle_start = 0;
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        cycle_start += 1;
+    }
+    Ok(Cycle {
+        start: cycle_start,
+        period,
+    })
+}
+
+/// The state of step `n`, computed by only simulating until the projected step of the cycle.
+pub fn nth<S, F>(start: S, mut next: F, cycle: &Cycle, n: u64) -> S
+where
+    F: FnMut(&S) -> S,
+{
+    let mut state = start;
+    for _ in 0..cycle.project(n) {
+        state = next(&state);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0, 1, 2, 3, 4, 5, 6, 7, 3, 4, ...
+    fn next(x: &u32) -> u32 {
+        if *x == 7 {
+            3
+        } else {
+            x + 1
+        }
+    }
+
+    #[test]
+    fn detectors_agree() {
+        let cycle = Cycle {
+            start: 3,
+            period: 5,
+        };
+        assert_eq!(find_with_hash(0, next), Ok(cycle));
+        assert_eq!(brent(0, next), Ok(cycle));
+        assert_eq!(
+            brent(5, next),
+            Ok(Cycle {
+                start: 0,
+                period: 5
+            })
+        );
+        assert_eq!(cycle.end(), 8);
+    }
+
+    #[test]
+    fn projection() {
+        let cycle = Cycle {
+            start: 3,
+            period: 5,
+        };
+        assert_eq!(cycle.project(2), 2);
+        assert_eq!(cycle.project(8), 3);
+        assert_eq!(cycle.project(1_000_000_000_000), 5);
+        assert_eq!(nth(0, next, &cycle, 1_000_000_000_000), 5);
+    }
+
+    #[test]
+    fn hash_detector_in_loop() {
+        let mut detector = HashDetector::new();
+        let values = ["a", "b", "c", "b"];
+        let cycle = values
+            .iter()
+            .enumerate()
+            .find_map(|(step, value)| detector.insert(*value, step as u64));
+        assert_eq!(
+            cycle,
+            Some(Cycle {
+                start: 1,
+                period: 2
+            })
+        );
+    }
+}
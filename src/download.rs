@@ -0,0 +1,89 @@
+use crate::cache::Cache;
+use crate::config::Profile;
+use crate::http::Client;
+use advent_of_code_solver::errors::{ACResult, Context, Error};
+use std::io::Read;
+
+pub fn get_input_file(
+    cache: &Cache,
+    client: &Client,
+    profile: &Profile,
+    year: i32,
+    day: u8,
+    verbose: bool,
+) -> ACResult<std::fs::File> {
+    let input_file_name = profile.input_file_name(year, day);
+    if let Some(input_file_path) = cache.find_file(&input_file_name) {
+        return std::fs::File::open(input_file_path).context("Failed to load input file");
+    }
+    cache.check_online(&format!("Input of {} day {}", year, day))?;
+
+    let session = profile.session_token.as_deref().ok_or_else(|| {
+        Error::Config(format!(
+            "No session cookie to download input for profile {}",
+            profile.display_name()
+        ))
+    })?;
+    let request_url = format!("https://adventofcode.com/{}/day/{}/input", year, day);
+    let mut body = client
+        .get(&request_url, Some(session))
+        .context("Failed to download input file")?;
+
+    let input_file_path = cache
+        .place_file(&input_file_name)
+        .context("Failed to store downloaded input file")?;
+    if verbose {
+        eprintln!("Storing input file to: {:?}", &input_file_path);
+    }
+    {
+        let mut input_file = std::fs::File::create(&input_file_path)
+            .context("Failed to store downloaded input file")?;
+        body.copy_to(&mut input_file)
+            .context("Failed to store downloaded input file")?;
+    }
+
+    std::fs::File::open(&input_file_path).context("Failed to load input file")
+}
+
+/// Returns the html page of the puzzle description.
+///
+/// The second part of the puzzle is only included if a session cookie is available and the first
+/// part has already been solved. So a cached description without a second part is downloaded
+/// again, unless in offline mode.
+pub fn get_description(
+    cache: &Cache,
+    client: &Client,
+    session: Option<&str>,
+    year: i32,
+    day: u8,
+    verbose: bool,
+) -> ACResult<String> {
+    let description_file_name = format!("description/{}/{}.html", year, day);
+    if let Some(description_file_path) = cache.find_file(&description_file_name) {
+        let description = std::fs::read_to_string(description_file_path)
+            .context("Failed to load description file")?;
+        if session.is_none() || cache.offline || crate::examples::count_parts(&description) >= 2 {
+            return Ok(description);
+        }
+    }
+    cache.check_online(&format!("Description of {} day {}", year, day))?;
+
+    let request_url = format!("https://adventofcode.com/{}/day/{}", year, day);
+    let mut body = client
+        .get(&request_url, session)
+        .context("Failed to download puzzle description")?;
+    let mut description = String::new();
+    body.read_to_string(&mut description)
+        .context("Failed to download puzzle description")?;
+
+    let description_file_path = cache
+        .place_file(&description_file_name)
+        .context("Failed to store description file")?;
+    if verbose {
+        eprintln!("Storing description file to: {:?}", &description_file_path);
+    }
+    std::fs::write(&description_file_path, &description)
+        .context("Failed to store description file")?;
+
+    Ok(description)
+}
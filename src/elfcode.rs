@@ -1,3 +1,5 @@
+use crate::errors::{ACResult, Error};
+use nom::{complete, do_parse, many1, map_res, named, preceded, tag, take_while1, terminated};
 use std::fmt::Display;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -47,6 +49,41 @@ pub enum Opcode {
     Eqrr,
 }
 
+impl std::str::FromStr for Opcode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> ACResult<Opcode> {
+        ALL_OPCODES
+            .iter()
+            .find(|opcode| opcode.name() == s)
+            .cloned()
+            .ok_or_else(|| Error::Parse(format!("Invalid opcode {}", s)))
+    }
+}
+
+impl Opcode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Opcode::Addr => "addr",
+            Opcode::Addi => "addi",
+            Opcode::Mulr => "mulr",
+            Opcode::Muli => "muli",
+            Opcode::Banr => "banr",
+            Opcode::Bani => "bani",
+            Opcode::Borr => "borr",
+            Opcode::Bori => "bori",
+            Opcode::Setr => "setr",
+            Opcode::Seti => "seti",
+            Opcode::Gtir => "gtir",
+            Opcode::Gtri => "gtri",
+            Opcode::Gtrr => "gtrr",
+            Opcode::Eqir => "eqir",
+            Opcode::Eqri => "eqri",
+            Opcode::Eqrr => "eqrr",
+        }
+    }
+}
+
 pub const ALL_OPCODES: [Opcode; 16] = [
     Opcode::Addr,
     Opcode::Addi,
@@ -91,54 +128,50 @@ impl Instruction {
                     reg.get(self.input_a as u8) + reg.get(self.input_b as u8);
             }
             Opcode::Addi => {
-                *reg.get_mut(self.output_register) =
-                    reg.get(self.input_a as u8) + self.input_b as u64;
+                *reg.get_mut(self.output_register) = reg.get(self.input_a as u8) + self.input_b;
             }
             Opcode::Mulr => {
                 *reg.get_mut(self.output_register) =
                     reg.get(self.input_a as u8) * reg.get(self.input_b as u8);
             }
             Opcode::Muli => {
-                *reg.get_mut(self.output_register) =
-                    reg.get(self.input_a as u8) * self.input_b as u64;
+                *reg.get_mut(self.output_register) = reg.get(self.input_a as u8) * self.input_b;
             }
             Opcode::Banr => {
                 *reg.get_mut(self.output_register) =
                     reg.get(self.input_a as u8) & reg.get(self.input_b as u8);
             }
             Opcode::Bani => {
-                *reg.get_mut(self.output_register) =
-                    reg.get(self.input_a as u8) & self.input_b as u64;
+                *reg.get_mut(self.output_register) = reg.get(self.input_a as u8) & self.input_b;
             }
             Opcode::Borr => {
                 *reg.get_mut(self.output_register) =
                     reg.get(self.input_a as u8) | reg.get(self.input_b as u8);
             }
             Opcode::Bori => {
-                *reg.get_mut(self.output_register) =
-                    reg.get(self.input_a as u8) | self.input_b as u64;
+                *reg.get_mut(self.output_register) = reg.get(self.input_a as u8) | self.input_b;
             }
             Opcode::Setr => {
                 *reg.get_mut(self.output_register) = *reg.get(self.input_a as u8);
             }
             Opcode::Seti => {
-                *reg.get_mut(self.output_register) = self.input_a as u64;
+                *reg.get_mut(self.output_register) = self.input_a;
             }
             Opcode::Gtir => {
-                *reg.get_mut(self.output_register) =
-                    if self.input_a as u64 > *reg.get(self.input_b as u8) {
-                        1
-                    } else {
-                        0
-                    };
+                *reg.get_mut(self.output_register) = if self.input_a > *reg.get(self.input_b as u8)
+                {
+                    1
+                } else {
+                    0
+                };
             }
             Opcode::Gtri => {
-                *reg.get_mut(self.output_register) =
-                    if *reg.get(self.input_a as u8) > self.input_b as u64 {
-                        1
-                    } else {
-                        0
-                    };
+                *reg.get_mut(self.output_register) = if *reg.get(self.input_a as u8) > self.input_b
+                {
+                    1
+                } else {
+                    0
+                };
             }
             Opcode::Gtrr => {
                 *reg.get_mut(self.output_register) =
@@ -149,20 +182,20 @@ impl Instruction {
                     };
             }
             Opcode::Eqir => {
-                *reg.get_mut(self.output_register) =
-                    if self.input_a as u64 == *reg.get(self.input_b as u8) {
-                        1
-                    } else {
-                        0
-                    };
+                *reg.get_mut(self.output_register) = if self.input_a == *reg.get(self.input_b as u8)
+                {
+                    1
+                } else {
+                    0
+                };
             }
             Opcode::Eqri => {
-                *reg.get_mut(self.output_register) =
-                    if *reg.get(self.input_a as u8) == self.input_b as u64 {
-                        1
-                    } else {
-                        0
-                    };
+                *reg.get_mut(self.output_register) = if *reg.get(self.input_a as u8) == self.input_b
+                {
+                    1
+                } else {
+                    0
+                };
             }
             Opcode::Eqrr => {
                 *reg.get_mut(self.output_register) =
@@ -204,7 +237,7 @@ impl Display for Instruction {
             ),
             Opcode::Muli => write!(
                 f,
-                "r{} = r{} + {}",
+                "r{} = r{} * {}",
                 self.output_register, self.input_a, self.input_b
             ),
             Opcode::Banr => write!(
@@ -262,3 +295,83 @@ impl Display for Instruction {
         }
     }
 }
+
+/// A program with the register that is bound to the instruction pointer.
+#[derive(Debug)]
+pub struct Program {
+    pub ip_register: u8,
+    pub instructions: Vec<Instruction>,
+}
+
+impl Program {
+    /// Executes the instruction at `ip` and returns the next instruction pointer.
+    ///
+    /// The result is out of range when the program halts.
+    pub fn step(&self, ip: usize, registers: &mut Registers) -> usize {
+        *registers.get_mut(self.ip_register) = ip as u64;
+        self.instructions[ip].execute_instruction(registers);
+        *registers.get(self.ip_register) as usize + 1
+    }
+}
+
+named!(parse_number<&str, u8>,
+    complete!(map_res!(take_while1!(|c: char| c.is_numeric()), |c: &str| c.parse()))
+);
+
+named!(parse_number_u64<&str, u64>,
+    complete!(map_res!(take_while1!(|c: char| c.is_numeric()), |c: &str| c.parse()))
+);
+
+named!(
+    parse_instruction<&str, Instruction>,
+    do_parse!(
+        opcode: map_res!(take_while1!(|c:char| c != ' '), str::parse) >>
+        tag!(" ") >>
+        input_a: parse_number_u64 >>
+        tag!(" ") >>
+        input_b: parse_number_u64 >>
+        tag!(" ") >>
+        output_register: parse_number >>
+        (Instruction::new(
+            opcode,
+            input_a,
+            input_b,
+            output_register
+        ))
+    )
+);
+
+named!(parse_program_lines<&str, Program>,
+    do_parse!(
+        ip_register: preceded!(tag!("#ip ") , parse_number) >>
+        tag!("\n") >>
+        instructions: many1!(complete!(terminated!(parse_instruction, tag!("\n")))) >>
+        (Program { ip_register, instructions })
+    )
+);
+
+/// Parses a program that starts with the `#ip` declaration.
+pub fn parse_program(input: &str) -> ACResult<Program> {
+    crate::parsing::parse_all(input, parse_program_lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_program() {
+        let program = parse_program(
+            "#ip 0\nseti 5 0 1\nseti 6 0 2\naddi 0 1 0\naddr 1 2 3\nsetr 1 0 0\nseti 8 0 4\nseti 9 0 5\n",
+        )
+        .unwrap();
+        let mut registers = Registers::empty(6);
+        let mut ip = 0;
+        while ip < program.instructions.len() {
+            ip = program.step(ip, &mut registers);
+        }
+        assert_eq!(registers, Registers::new(&[6, 5, 6, 0, 0, 9]));
+        assert_eq!(program.instructions[2].to_string(), "r0 = r0 + 1");
+        assert!(parse_program("#ip 0\nmove 1 2 3\n").is_err());
+    }
+}
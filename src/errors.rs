@@ -1,41 +1,257 @@
 use std::fmt;
-use std::fmt::Debug;
 
-#[derive(PartialEq)]
-pub struct Error {
-    repr: String,
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file or stream failed
+    Io(std::io::Error),
+    /// A request to adventofcode.com failed or returned an error status
+    Http(reqwest::Error),
+    /// adventofcode.com rejected the session cookie
+    Unauthorized(String),
+    /// The configuration is invalid or missing required values
+    Config(String),
+    /// The puzzle input couldn't be parsed
+    Parse(String),
+    /// The puzzle input couldn't be parsed at a known location
+    Syntax(ParseError),
+    /// There is no solver for the requested puzzle
+    Unimplemented(String),
+    /// The solver failed to compute a result
+    Solver(String),
+    /// The solver didn't finish within the timeout
+    Timeout(String),
+    /// An error with a description of the operation that failed
+    Context(String, Box<Error>),
 }
 
+pub const EXIT_SOLVER: i32 = 1;
+pub const EXIT_CONFIG: i32 = 2;
+pub const EXIT_PARSE: i32 = 3;
+pub const EXIT_IO: i32 = 4;
+pub const EXIT_HTTP: i32 = 5;
+pub const EXIT_UNIMPLEMENTED: i32 = 6;
+pub const EXIT_TIMEOUT: i32 = 7;
+
 impl Error {
     pub fn new(message: String) -> Self {
-        Error { repr: message }
+        Error::Solver(message)
     }
 
     pub fn new_str(message: &str) -> Self {
-        Error {
-            repr: message.to_string(),
+        Error::Solver(message.to_string())
+    }
+
+    /// Returns the original error, without any added context.
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context(_, e) => e.root(),
+            e => e,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self.root() {
+            Error::Io(_) => EXIT_IO,
+            Error::Http(_) | Error::Unauthorized(_) => EXIT_HTTP,
+            Error::Config(_) => EXIT_CONFIG,
+            Error::Parse(_) | Error::Syntax(_) => EXIT_PARSE,
+            Error::Unimplemented(_) => EXIT_UNIMPLEMENTED,
+            Error::Timeout(_) => EXIT_TIMEOUT,
+            Error::Solver(_) | Error::Context(_, _) => EXIT_SOLVER,
         }
     }
 }
 
-impl fmt::Debug for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(&self.repr, f)
+impl PartialEq for Error {
+    fn eq(&self, other: &Error) -> bool {
+        match (self, other) {
+            (Error::Io(a), Error::Io(b)) => a.kind() == b.kind() && a.to_string() == b.to_string(),
+            (Error::Http(a), Error::Http(b)) => a.to_string() == b.to_string(),
+            (Error::Unauthorized(a), Error::Unauthorized(b))
+            | (Error::Config(a), Error::Config(b))
+            | (Error::Parse(a), Error::Parse(b))
+            | (Error::Unimplemented(a), Error::Unimplemented(b))
+            | (Error::Solver(a), Error::Solver(b))
+            | (Error::Timeout(a), Error::Timeout(b)) => a == b,
+            (Error::Syntax(a), Error::Syntax(b)) => a == b,
+            (Error::Context(a, a_source), Error::Context(b, b_source)) => {
+                a == b && a_source == b_source
+            }
+            _ => false,
+        }
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(&self.repr, f)
+        match self {
+            Error::Io(e) => fmt::Display::fmt(e, f),
+            Error::Http(e) => fmt::Display::fmt(e, f),
+            Error::Unauthorized(message)
+            | Error::Config(message)
+            | Error::Parse(message)
+            | Error::Unimplemented(message)
+            | Error::Solver(message)
+            | Error::Timeout(message) => fmt::Display::fmt(message, f),
+            Error::Syntax(e) => fmt::Display::fmt(e, f),
+            Error::Context(context, e) => write!(f, "{}: {}", context, e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Http(e) => Some(e),
+            Error::Context(_, e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
     }
 }
 
-impl std::error::Error for Error {}
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::Config(e.to_string())
+    }
+}
 
-impl<E: Debug> From<nom::Err<E>> for Error {
-    fn from(e: nom::Err<E>) -> Self {
-        Error::new(format!("Failed to parse: {:?}", e))
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Syntax(e)
+    }
+}
+
+/// Location and description of a syntax error in the puzzle input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Line number, starting at 1
+    pub line: usize,
+    /// Column in characters, starting at 1
+    pub column: usize,
+    pub source_line: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    /// Locates the error from the complete input and the remaining input at which parsing failed.
+    pub fn at<S: Into<String>>(input: &str, remaining: &str, expected: S) -> Self {
+        let offset = input.len().saturating_sub(remaining.len());
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            source_line: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_owned(),
+            expected: expected.into(),
+        }
+    }
+}
+
+/// Renders the error like a compiler diagnostic, with a marker below the offending column.
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(f, "expected {}", self.expected)?;
+        writeln!(
+            f,
+            "{}--> line {}, column {}",
+            gutter, self.line, self.column
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(f, "{} | {:>2$}", gutter, "^", self.column)
+    }
+}
+
+/// Adds a description of the failed operation to an error.
+pub trait Context<T> {
+    fn context<C: Into<String>>(self, context: C) -> ACResult<T>;
+
+    fn with_context<C: Into<String>, F: FnOnce() -> C>(self, f: F) -> ACResult<T>;
+}
+
+impl<T, E: Into<Error>> Context<T> for Result<T, E> {
+    fn context<C: Into<String>>(self, context: C) -> ACResult<T> {
+        self.map_err(|e| Error::Context(context.into(), Box::new(e.into())))
+    }
+
+    fn with_context<C: Into<String>, F: FnOnce() -> C>(self, f: F) -> ACResult<T> {
+        self.map_err(|e| Error::Context(f().into(), Box::new(e.into())))
     }
 }
 
 pub type ACResult<T> = Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_chain() {
+        let result: Result<(), std::io::Error> = Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "file missing",
+        ));
+        let e = result
+            .context("Failed to load input file")
+            .context("Failed to solve")
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Failed to solve: Failed to load input file: file missing"
+        );
+        assert_eq!(e.exit_code(), EXIT_IO);
+    }
+
+    #[test]
+    fn distinct_exit_codes() {
+        assert_eq!(Error::new_str("Not found").exit_code(), EXIT_SOLVER);
+        assert_eq!(
+            Error::Unimplemented("Day 26 not implemented".to_owned()).exit_code(),
+            EXIT_UNIMPLEMENTED
+        );
+        assert_ne!(EXIT_UNIMPLEMENTED, EXIT_HTTP);
+        assert_eq!(
+            Error::Unauthorized("Session expired".to_owned()).exit_code(),
+            EXIT_HTTP
+        );
+        assert_eq!(
+            Error::Timeout("Solver timed out".to_owned()).exit_code(),
+            EXIT_TIMEOUT
+        );
+    }
+
+    #[test]
+    fn parse_error_location() {
+        let input = "pos=<1, 2>\npos=<3, x>\npos=<5, 6>\n";
+        let e = ParseError::at(input, &input[19..], "a number");
+        assert_eq!((e.line, e.column), (2, 9));
+        assert_eq!(e.source_line, "pos=<3, x>");
+        assert_eq!(
+            Error::from(e).to_string(),
+            "expected a number
+ --> line 2, column 9
+  |
+2 | pos=<3, x>
+  |         ^"
+        );
+    }
+}
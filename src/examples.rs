@@ -0,0 +1,345 @@
+use crate::cache::Cache;
+use advent_of_code_solver::errors::{ACResult, Context, Error};
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+
+const ARTICLE_START: &str = "<article class=\"day-desc\">";
+
+/// A text snippet of the puzzle description with the puzzle part it appeared in.
+#[derive(Debug, PartialEq)]
+pub struct Snippet {
+    pub part: u8,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Example {
+    pub level: u8,
+    pub input: String,
+    pub answer: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct ExampleFile {
+    #[serde(default)]
+    example: Vec<Example>,
+}
+
+pub fn count_parts(html: &str) -> usize {
+    html.matches(ARTICLE_START).count()
+}
+
+/// Returns the content of all `<pre><code>` blocks.
+pub fn extract_code_blocks(html: &str) -> Vec<Snippet> {
+    extract_between(html, "<pre><code>", "</code></pre>")
+}
+
+/// Returns all emphasized code snippets, which usually contain the example answers.
+pub fn extract_answers(html: &str) -> Vec<Snippet> {
+    extract_any_between(
+        html,
+        &[
+            ("<code><em>", "</em></code>"),
+            ("<em><code>", "</code></em>"),
+        ],
+    )
+}
+
+fn extract_between(html: &str, start_tag: &str, end_tag: &str) -> Vec<Snippet> {
+    extract_any_between(html, &[(start_tag, end_tag)])
+}
+
+/// Returns the snippets between any of the pairs of tags in the order they appear.
+fn extract_any_between(html: &str, tags: &[(&str, &str)]) -> Vec<Snippet> {
+    let mut result = Vec::new();
+    for (part, article) in html.split(ARTICLE_START).skip(1).enumerate() {
+        let article = article.split("</article>").next().unwrap_or("");
+        let mut rest = article;
+        while let Some((start, (start_tag, end_tag))) = tags
+            .iter()
+            .filter_map(|tags| rest.find(tags.0).map(|start| (start, tags)))
+            .min_by_key(|(start, _)| *start)
+        {
+            rest = &rest[start + start_tag.len()..];
+            let end = match rest.find(end_tag) {
+                Some(end) => end,
+                None => continue,
+            };
+            result.push(Snippet {
+                part: part as u8 + 1,
+                text: decode_html(&strip_tags(&rest[..end])),
+            });
+            rest = &rest[end + end_tag.len()..];
+        }
+    }
+    result
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+fn decode_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn preview(text: &str) -> String {
+    let first_line = text.lines().next().unwrap_or("");
+    let first_line: String = first_line.chars().take(40).collect();
+    let line_count = text.lines().count();
+    if line_count > 1 {
+        format!("{:?} ({} lines)", first_line, line_count)
+    } else {
+        format!("{:?}", first_line)
+    }
+}
+
+/// Lets the user choose pairs of example input and expected answer on the terminal.
+pub fn select_examples<T: BufRead>(html: &str, mut input: T) -> ACResult<Vec<Example>> {
+    let blocks = extract_code_blocks(html);
+    let answers = extract_answers(html);
+    if blocks.is_empty() || answers.is_empty() {
+        return Err(Error::Parse(
+            "No examples found in puzzle description".to_owned(),
+        ));
+    }
+
+    eprintln!("Code blocks:");
+    for (i, b) in blocks.iter().enumerate() {
+        eprintln!("  [{}] (part {}) {}", i + 1, b.part, preview(&b.text));
+    }
+    eprintln!("Emphasized answers:");
+    for (i, a) in answers.iter().enumerate() {
+        eprintln!("  [{}] (part {}) {}", i + 1, a.part, preview(&a.text));
+    }
+    eprintln!("Enter examples as \"BLOCK ANSWER [LEVEL]\", an empty line finishes:");
+
+    let mut examples = Vec::new();
+    loop {
+        let mut line = String::new();
+        input
+            .read_line(&mut line)
+            .context("Failed to read selection")?;
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        match parse_selection(line, &blocks, &answers) {
+            Ok(example) => examples.push(example),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    Ok(examples)
+}
+
+fn parse_selection(line: &str, blocks: &[Snippet], answers: &[Snippet]) -> ACResult<Example> {
+    let numbers = line
+        .split_whitespace()
+        .map(|n| n.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| Error::Parse(format!("Invalid selection: {}", line)))?;
+    if numbers.len() < 2 || numbers.len() > 3 {
+        return Err(Error::Parse(format!("Invalid selection: {}", line)));
+    }
+    let block = numbers[0]
+        .checked_sub(1)
+        .and_then(|i| blocks.get(i))
+        .ok_or_else(|| Error::Parse(format!("Invalid code block: {}", numbers[0])))?;
+    let answer = numbers[1]
+        .checked_sub(1)
+        .and_then(|i| answers.get(i))
+        .ok_or_else(|| Error::Parse(format!("Invalid answer: {}", numbers[1])))?;
+    let level = match numbers.get(2) {
+        None => answer.part,
+        Some(1) => 1,
+        Some(2) => 2,
+        Some(level) => return Err(Error::Parse(format!("Invalid level: {}", level))),
+    };
+    Ok(Example {
+        level,
+        input: block.text.clone(),
+        answer: answer.text.clone(),
+    })
+}
+
+fn examples_file_name(year: i32, day: u8) -> String {
+    format!("examples/{}/{}.toml", year, day)
+}
+
+pub fn load_examples(cache: &Cache, year: i32, day: u8) -> ACResult<Vec<Example>> {
+    let path = match cache.find_file(examples_file_name(year, day)) {
+        Some(path) => path,
+        None => return Ok(Vec::new()),
+    };
+    let content = std::fs::read_to_string(path).context("Failed to load examples file")?;
+    let file: ExampleFile = toml::from_str(&content)
+        .map_err(|e| Error::Parse(format!("Failed to parse examples file: {}", e)))?;
+    Ok(file.example)
+}
+
+pub fn store_examples(
+    cache: &Cache,
+    year: i32,
+    day: u8,
+    examples: &[Example],
+    verbose: bool,
+) -> ACResult<()> {
+    let path = cache
+        .place_file(examples_file_name(year, day))
+        .context("Failed to store examples file")?;
+    if verbose {
+        eprintln!("Storing examples to: {:?}", &path);
+    }
+    let content = toml::to_string_pretty(&ExampleFile {
+        example: examples.to_vec(),
+    })
+    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    .context("Failed to serialize examples")?;
+    std::fs::write(path, content).context("Failed to store examples file")
+}
+
+/// Feeds all examples through the solver of the given day and compares the results with the
+/// expected answers.
+pub fn run_examples(
+    examples: &[Example],
+    year: i32,
+    day: u8,
+    level: Option<u8>,
+    runner: &advent_of_code_solver::progress::Runner,
+) -> ACResult<()> {
+    let mut failed = 0;
+    let mut count = 0;
+    // Keeps the kind of the first error, e.g. for an example that couldn't be parsed
+    let mut first_error = None;
+    for (i, example) in examples.iter().enumerate() {
+        if level.is_some_and(|l| l != example.level) {
+            continue;
+        }
+        count += 1;
+        match advent_of_code_solver::solve(runner, &example.input, year, day, example.level) {
+            Ok(ref result) if result.matches(&example.answer) => {
+                println!("Example {} (level {}): ok", i + 1, example.level);
+            }
+            Ok(result) => {
+                failed += 1;
+                println!(
+                    "Example {} (level {}): FAILED, expected {}, got {}",
+                    i + 1,
+                    example.level,
+                    example.answer,
+                    result.canonical()
+                );
+            }
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "Example {} (level {}): FAILED with error: {}",
+                    i + 1,
+                    example.level,
+                    e
+                );
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if count == 0 {
+        return Err(Error::Config(format!(
+            "No examples stored for {} day {}",
+            year, day
+        )));
+    }
+    if failed > 0 {
+        let message = format!("{} of {} examples failed", failed, count);
+        return Err(match first_error {
+            Some(e) => Error::Context(message, Box::new(e)),
+            // Only wrong answers
+            None => Error::new(message),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTML: &str = "<main>\n<article class=\"day-desc\"><h2>--- Day 1 ---</h2>\
+        <p>For example:</p>\n<pre><code>1\n&lt;2&gt;\n</code></pre>\n\
+        <p>After <em><code>2</code></em> the result is <code><em>3</em></code>.</p>\n</article>\n\
+        <article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2>\
+        <pre><code><em>4</em>\n</code></pre><p>Now it is <em><code>12</code></em>.</p>\n\
+        </article>\n</main>";
+
+    #[test]
+    fn extract_snippets() {
+        assert_eq!(count_parts(HTML), 2);
+        assert_eq!(
+            extract_code_blocks(HTML),
+            vec![
+                Snippet {
+                    part: 1,
+                    text: "1\n<2>\n".to_owned()
+                },
+                Snippet {
+                    part: 2,
+                    text: "4\n".to_owned()
+                }
+            ]
+        );
+        assert_eq!(
+            extract_answers(HTML),
+            vec![
+                Snippet {
+                    part: 1,
+                    text: "2".to_owned()
+                },
+                Snippet {
+                    part: 1,
+                    text: "3".to_owned()
+                },
+                Snippet {
+                    part: 2,
+                    text: "12".to_owned()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn select_pairs() {
+        let examples = select_examples(HTML, "1 2\n2 3 2\n\n".as_bytes());
+        assert_eq!(
+            examples,
+            Ok(vec![
+                Example {
+                    level: 1,
+                    input: "1\n<2>\n".to_owned(),
+                    answer: "3".to_owned()
+                },
+                Example {
+                    level: 2,
+                    input: "4\n".to_owned(),
+                    answer: "12".to_owned()
+                }
+            ])
+        );
+        let blocks = extract_code_blocks(HTML);
+        let answers = extract_answers(HTML);
+        assert!(parse_selection("1 1 257", &blocks, &answers).is_err());
+        assert!(parse_selection("1 1 3", &blocks, &answers).is_err());
+    }
+}
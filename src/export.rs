@@ -0,0 +1,372 @@
+use crate::errors::{ACResult, Context, Error};
+use crate::utils::Field;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The running export, `None` while exporting is off.
+static EXPORTER: Mutex<Option<Exporter>> = Mutex::new(None);
+
+/// Colour of a pixel as red, green and blue.
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One uncompressed image per frame
+    Ppm,
+    /// One image per frame
+    Png,
+    /// All frames in one animation
+    Gif,
+}
+
+impl std::str::FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> ACResult<Format> {
+        match s {
+            "ppm" => Ok(Format::Ppm),
+            "png" => Ok(Format::Png),
+            "gif" => Ok(Format::Gif),
+            _ => Err(Error::Parse(format!("Unknown image format {}", s))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Directory for the images, created if missing
+    pub dir: PathBuf,
+    pub format: Format,
+    /// Width and height of the square of pixels for one cell
+    pub scale: u32,
+    /// Time between two frames of an animation
+    pub delay: Duration,
+}
+
+/// An RGB image, e.g. one frame of a simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// Draws every cell as a square of `scale` pixels in the colour from the palette.
+    ///
+    /// Fails if the image would be too large.
+    pub fn new<F: Fn(u32, u32) -> Rgb>(
+        width: u32,
+        height: u32,
+        scale: u32,
+        palette: F,
+    ) -> ACResult<Self> {
+        let too_large = || {
+            Error::Config(format!(
+                "An image of {}x{} cells with scale {} is too large",
+                width, height, scale
+            ))
+        };
+        let image_width = width.checked_mul(scale).ok_or_else(too_large)?;
+        let image_height = height.checked_mul(scale).ok_or_else(too_large)?;
+        let len = (image_width as usize)
+            .checked_mul(image_height as usize)
+            .ok_or_else(too_large)?;
+        let mut pixels = Vec::with_capacity(len);
+        for y in 0..image_height {
+            for x in 0..image_width {
+                pixels.push(palette(x / scale, y / scale));
+            }
+        }
+        Ok(Image {
+            width: image_width,
+            height: image_height,
+            pixels,
+        })
+    }
+
+    pub fn from_field<T, F: Fn(&T) -> Rgb>(
+        field: &Field<T>,
+        scale: u32,
+        palette: F,
+    ) -> ACResult<Self> {
+        Image::new(field.width(), field.height(), scale, |x, y| {
+            palette(field.get(x, y))
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Rgb {
+        self.pixels[(x + y * self.width) as usize]
+    }
+
+    /// Writes the image in the binary PPM format.
+    pub fn write_ppm<W: Write>(&self, mut w: W) -> ACResult<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height).context("Failed to write image")?;
+        w.write_all(&self.pixels.concat())
+            .context("Failed to write image")
+    }
+
+    pub fn write_png<W: Write>(&self, w: W) -> ACResult<()> {
+        let mut encoder = png::Encoder::new(w, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels.concat()))
+            .map_err(std::io::Error::other)
+            .context("Failed to write PNG image")
+    }
+}
+
+/// Writes a looping GIF animation frame by frame, so the frames don't need to be kept in memory.
+///
+/// All frames must have the size of the first one and each may use at most 256 colours.
+pub struct GifWriter<W: Write> {
+    encoder: gif::Encoder<W>,
+    width: u16,
+    height: u16,
+    /// In hundredths of a second
+    delay: u16,
+}
+
+fn gif_error(e: gif::EncodingError) -> Error {
+    Error::Context(
+        "Failed to write GIF".to_owned(),
+        Box::new(Error::Io(std::io::Error::other(e))),
+    )
+}
+
+impl<W: Write> GifWriter<W> {
+    pub fn new(w: W, width: u32, height: u32, delay: Duration) -> ACResult<Self> {
+        let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => {
+                return Err(Error::Config(
+                    "The frames are too large for a GIF, try a smaller scale or PNG".to_owned(),
+                ))
+            }
+        };
+        let mut encoder = gif::Encoder::new(w, width, height, &[]).map_err(gif_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(gif_error)?;
+        Ok(GifWriter {
+            encoder,
+            width,
+            height,
+            delay: (delay.as_millis() / 10).min(u128::from(u16::MAX)) as u16,
+        })
+    }
+
+    /// Appends the frame with its own palette.
+    pub fn add(&mut self, image: &Image) -> ACResult<()> {
+        if (image.width, image.height) != (u32::from(self.width), u32::from(self.height)) {
+            return Err(Error::Config(
+                "All frames of an animation need the same size".to_owned(),
+            ));
+        }
+        let mut palette: Vec<Rgb> = Vec::new();
+        let mut indices = Vec::with_capacity(image.pixels.len());
+        for pixel in image.pixels.iter() {
+            let index = match palette.iter().position(|c| c == pixel) {
+                Some(index) => index,
+                None => {
+                    palette.push(*pixel);
+                    palette.len() - 1
+                }
+            };
+            indices.push(u8::try_from(index).map_err(|_| {
+                Error::Config("A GIF frame can't have more than 256 colours, try PNG".to_owned())
+            })?);
+        }
+        let mut frame = gif::Frame::from_indexed_pixels(self.width, self.height, indices, None);
+        frame.palette = Some(palette.concat());
+        frame.delay = self.delay;
+        self.encoder.write_frame(&frame).map_err(gif_error)
+    }
+
+    /// Ends the animation and returns the writer.
+    pub fn finish(self) -> ACResult<W> {
+        self.encoder
+            .into_inner()
+            .context("Failed to write end of GIF")
+    }
+}
+
+/// Writes the frames as a looping GIF animation, see `GifWriter`.
+pub fn write_gif<W: Write>(frames: &[Image], delay: Duration, w: W) -> ACResult<()> {
+    let first = frames
+        .first()
+        .ok_or_else(|| Error::Config("No frames to animate".to_owned()))?;
+    let mut writer = GifWriter::new(w, first.width, first.height, delay)?;
+    for frame in frames {
+        writer.add(frame)?;
+    }
+    writer.finish().map(|_| ())
+}
+
+struct Exporter {
+    settings: Settings,
+    frame_count: usize,
+    /// The animation, started with its first frame
+    gif: Option<GifWriter<BufWriter<File>>>,
+    /// The first failure, exporting stops after it
+    error: Option<Error>,
+}
+
+impl Exporter {
+    fn add(&mut self, image: Image) -> ACResult<()> {
+        let name = format!("frame-{:05}", self.frame_count);
+        self.frame_count += 1;
+        match self.settings.format {
+            Format::Ppm => image.write_ppm(create(&self.settings.dir, &name, "ppm")?),
+            Format::Png => image.write_png(create(&self.settings.dir, &name, "png")?),
+            Format::Gif => {
+                if self.gif.is_none() {
+                    let file = create(&self.settings.dir, "animation", "gif")?;
+                    self.gif = Some(GifWriter::new(
+                        file,
+                        image.width,
+                        image.height,
+                        self.settings.delay,
+                    )?);
+                }
+                match &mut self.gif {
+                    Some(gif) => gif.add(&image),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+fn create(dir: &Path, name: &str, extension: &str) -> ACResult<BufWriter<File>> {
+    std::fs::create_dir_all(dir).context("Failed to create export directory")?;
+    let path = dir.join(name).with_extension(extension);
+    let file =
+        File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+    Ok(BufWriter::new(file))
+}
+
+pub fn is_enabled() -> bool {
+    EXPORTER.lock().unwrap().is_some()
+}
+
+/// Starts exporting the frames of simulations that support it.
+pub fn enable(settings: Settings) {
+    *EXPORTER.lock().unwrap() = Some(Exporter {
+        settings,
+        frame_count: 0,
+        gif: None,
+        error: None,
+    });
+}
+
+/// Exports a frame of a simulation if exporting is enabled.
+///
+/// Failures don't stop the solver, they are reported by `finish`.
+pub fn frame<F: Fn(u32, u32) -> Rgb>(width: u32, height: u32, palette: F) {
+    let mut exporter = EXPORTER.lock().unwrap();
+    let exporter = match &mut *exporter {
+        Some(exporter) if exporter.error.is_none() => exporter,
+        _ => return,
+    };
+    let added = Image::new(width, height, exporter.settings.scale, palette)
+        .and_then(|image| exporter.add(image));
+    if let Err(e) = added {
+        exporter.error = Some(e);
+    }
+}
+
+/// Exports a frame of the field, see `frame`.
+pub fn frame_field<T, F: Fn(&T) -> Rgb>(field: &Field<T>, palette: F) {
+    frame(field.width(), field.height(), |x, y| {
+        palette(field.get(x, y))
+    })
+}
+
+/// Stops exporting, writes the animation and returns the number of exported frames.
+pub fn finish() -> ACResult<usize> {
+    let exporter = match EXPORTER.lock().unwrap().take() {
+        Some(exporter) => exporter,
+        None => return Ok(0),
+    };
+    if let Some(e) = exporter.error {
+        return Err(e);
+    }
+    if let Some(gif) = exporter.gif {
+        gif.finish()?
+            .flush()
+            .context("Failed to write end of GIF")?;
+    }
+    Ok(exporter.frame_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Rgb = [0, 0, 0];
+    const WHITE: Rgb = [255, 255, 255];
+
+    fn checkers(scale: u32) -> Image {
+        Image::new(2, 1, scale, |x, _| if x == 0 { BLACK } else { WHITE }).unwrap()
+    }
+
+    #[test]
+    fn scaled_image() {
+        let image = checkers(2);
+        assert_eq!((image.width(), image.height()), (4, 2));
+        assert_eq!(image.get(1, 1), BLACK);
+        assert_eq!(image.get(2, 0), WHITE);
+    }
+
+    #[test]
+    fn image_formats() {
+        let image = checkers(1);
+        let mut ppm = Vec::new();
+        image.write_ppm(&mut ppm).unwrap();
+        assert_eq!(ppm, b"P6\n2 1\n255\n\x00\x00\x00\xff\xff\xff".to_vec());
+
+        let mut png = Vec::new();
+        image.write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let mut gif = Vec::new();
+        write_gif(
+            &[image.clone(), image],
+            Duration::from_millis(100),
+            &mut gif,
+        )
+        .unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+        assert_eq!(gif.last(), Some(&b';'));
+        let mismatch = write_gif(
+            &[checkers(1), checkers(2)],
+            Duration::from_millis(100),
+            &mut gif,
+        );
+        assert_eq!(
+            mismatch.map_err(|e| e.exit_code()),
+            Err(crate::errors::EXIT_CONFIG)
+        );
+    }
+
+    #[test]
+    fn too_large_image() {
+        assert!(Image::new(u32::MAX / 2, 1, 4, |_, _| BLACK).is_err());
+        assert!(Image::new(1 << 20, 1 << 20, 1 << 12, |_, _| BLACK).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use crate::errors::{ACResult, Error};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Defines an integer vector type with the given fields.
+///
+/// The fields are listed from the least to the most significant for the ordering, so 2D points
+/// sort in reading order: by row, then by column.
+macro_rules! vector {
+    ($(#[$meta:meta])* $name:ident, $dimensions:expr, $($field:ident),+) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        pub struct $name {
+            $(pub $field: i64),+
+        }
+
+        impl $name {
+            pub const ZERO: $name = $name { $($field: 0),+ };
+
+            pub const fn new($($field: i64),+) -> Self {
+                $name { $($field),+ }
+            }
+
+            /// Sum of the absolute differences of all coordinates.
+            pub fn manhattan(self, other: $name) -> i64 {
+                0 $(+ (self.$field - other.$field).abs())+
+            }
+
+            /// Largest absolute difference of any coordinate, the number of king moves.
+            pub fn chebyshev(self, other: $name) -> i64 {
+                0 $(.max((self.$field - other.$field).abs()))+
+            }
+
+            /// Applies the function to every coordinate.
+            pub fn map<F: Fn(i64) -> i64>(self, f: F) -> Self {
+                $name { $($field: f(self.$field)),+ }
+            }
+        }
+
+        impl Add for $name {
+            type Output = $name;
+
+            fn add(self, other: $name) -> $name {
+                $name { $($field: self.$field + other.$field),+ }
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+
+            fn sub(self, other: $name) -> $name {
+                $name { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        impl Mul<i64> for $name {
+            type Output = $name;
+
+            fn mul(self, factor: i64) -> $name {
+                self.map(|c| c * factor)
+            }
+        }
+
+        /// Divides every coordinate, rounding towards zero.
+        impl Div<i64> for $name {
+            type Output = $name;
+
+            fn div(self, divisor: i64) -> $name {
+                self.map(|c| c / divisor)
+            }
+        }
+
+        impl Neg for $name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                self.map(|c| -c)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, other: $name) {
+                *self = *self + other;
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, other: $name) {
+                *self = *self - other;
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &$name) -> Ordering {
+                let mut ordering = Ordering::Equal;
+                $(ordering = self.$field.cmp(&other.$field).then(ordering);)+
+                ordering
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &$name) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        /// Parses comma separated coordinates like `1,-2`, optionally in angle brackets like
+        /// `< 1, -2>`.
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> ACResult<$name> {
+                let coordinates = parse_coordinates(s, $dimensions)?;
+                let mut coordinates = coordinates.into_iter();
+                Ok($name { $($field: coordinates.next().unwrap_or_default()),+ })
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let coordinates: Vec<String> = vec![$(self.$field.to_string()),+];
+                f.write_str(&coordinates.join(","))
+            }
+        }
+    };
+}
+
+vector!(
+    /// Point or offset in the plane, `y` grows downwards like the rows of a map.
+    Vec2, 2, x, y
+);
+vector!(Vec3, 3, x, y, z);
+vector!(Vec4, 4, x, y, z, w);
+
+fn parse_coordinates(s: &str, dimensions: usize) -> ACResult<Vec<i64>> {
+    let trimmed = s.trim();
+    let inner = trimmed
+        .strip_prefix('<')
+        .and_then(|inner| inner.strip_suffix('>'))
+        .unwrap_or(trimmed);
+    let coordinates = inner
+        .split(',')
+        .map(|c| c.trim().parse::<i64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Error::Parse(format!("Invalid coordinates {:?}: {}", s, e)))?;
+    if coordinates.len() != dimensions {
+        return Err(Error::Parse(format!(
+            "Expected {} coordinates instead of {:?}",
+            dimensions, s
+        )));
+    }
+    Ok(coordinates)
+}
+
+impl Vec2 {
+    /// The direct neighbours in reading order.
+    pub fn neighbours4(self) -> [Vec2; 4] {
+        [
+            self + Direction::North.offset(),
+            self + Direction::West.offset(),
+            self + Direction::East.offset(),
+            self + Direction::South.offset(),
+        ]
+    }
+
+    /// The direct and diagonal neighbours in reading order.
+    pub fn neighbours8(self) -> [Vec2; 8] {
+        let Vec2 { x, y } = self;
+        [
+            Vec2::new(x - 1, y - 1),
+            Vec2::new(x, y - 1),
+            Vec2::new(x + 1, y - 1),
+            Vec2::new(x - 1, y),
+            Vec2::new(x + 1, y),
+            Vec2::new(x - 1, y + 1),
+            Vec2::new(x, y + 1),
+            Vec2::new(x + 1, y + 1),
+        ]
+    }
+}
+
+/// Direction on a map with north at the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// Turn relative to the current direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left,
+    Straight,
+    Right,
+    Back,
+}
+
+impl Direction {
+    /// All directions, clockwise from north.
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// The step in this direction.
+    pub fn offset(self) -> Vec2 {
+        match self {
+            Direction::North => Vec2::new(0, -1),
+            Direction::East => Vec2::new(1, 0),
+            Direction::South => Vec2::new(0, 1),
+            Direction::West => Vec2::new(-1, 0),
+        }
+    }
+
+    pub fn turn(self, turn: Turn) -> Direction {
+        let quarters = match turn {
+            Turn::Straight => 0,
+            Turn::Right => 1,
+            Turn::Back => 2,
+            Turn::Left => 3,
+        };
+        let index = Direction::ALL.iter().position(|d| *d == self).unwrap_or(0);
+        Direction::ALL[(index + quarters) % 4]
+    }
+
+    pub fn turn_left(self) -> Direction {
+        self.turn(Turn::Left)
+    }
+
+    pub fn turn_right(self) -> Direction {
+        self.turn(Turn::Right)
+    }
+
+    pub fn reverse(self) -> Direction {
+        self.turn(Turn::Back)
+    }
+
+    pub fn is_horizontal(self) -> bool {
+        self == Direction::East || self == Direction::West
+    }
+}
+
+/// Reads compass letters (`NESW`), relative letters (`URDL`) and arrows (`^>v<`).
+impl TryFrom<char> for Direction {
+    type Error = Error;
+
+    fn try_from(c: char) -> ACResult<Direction> {
+        match c {
+            'N' | 'U' | '^' => Ok(Direction::North),
+            'E' | 'R' | '>' => Ok(Direction::East),
+            'S' | 'D' | 'v' => Ok(Direction::South),
+            'W' | 'L' | '<' => Ok(Direction::West),
+            _ => Err(Error::Parse(format!("Invalid direction {}", c))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vector_arithmetic_and_distances() {
+        let a = Vec3::new(1, -2, 3);
+        let b = Vec3::new(-1, 2, 0);
+        assert_eq!(a + b, Vec3::new(0, 0, 3));
+        assert_eq!(a - b, Vec3::new(2, -4, 3));
+        assert_eq!(-a * 2, Vec3::new(-2, 4, -6));
+        assert_eq!(Vec3::new(5, -5, 4) / 2, Vec3::new(2, -2, 2));
+        assert_eq!(a.manhattan(b), 9);
+        assert_eq!(a.chebyshev(b), 4);
+        assert_eq!(Vec4::new(1, 1, 1, 1).manhattan(Vec4::ZERO), 4);
+    }
+
+    #[test]
+    fn reading_order() {
+        let mut points = vec![Vec2::new(0, 1), Vec2::new(2, 0), Vec2::new(1, 0)];
+        points.sort();
+        assert_eq!(
+            points,
+            vec![Vec2::new(1, 0), Vec2::new(2, 0), Vec2::new(0, 1)]
+        );
+        let mut neighbours = Vec2::new(3, 3).neighbours8();
+        neighbours.sort();
+        assert_eq!(neighbours, Vec2::new(3, 3).neighbours8());
+        assert_eq!(Vec2::new(3, 3).neighbours4()[1], Vec2::new(2, 3));
+    }
+
+    #[test]
+    fn parse_coordinates() {
+        assert_eq!("3,-4".parse(), Ok(Vec2::new(3, -4)));
+        assert_eq!("< 9,  1,-3>".parse(), Ok(Vec3::new(9, 1, -3)));
+        assert_eq!("0,-1,2,3".parse(), Ok(Vec4::new(0, -1, 2, 3)));
+        assert!("1,2,3".parse::<Vec2>().is_err());
+        assert!("<1,x>".parse::<Vec2>().is_err());
+        assert_eq!(Vec3::new(1, -2, 3).to_string(), "1,-2,3");
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(Direction::North.turn_left(), Direction::West);
+        assert_eq!(Direction::West.turn_right(), Direction::North);
+        assert_eq!(Direction::East.reverse(), Direction::West);
+        assert_eq!(Direction::South.turn(Turn::Straight), Direction::South);
+        assert_eq!(Direction::try_from('v'), Ok(Direction::South));
+        assert_eq!(Direction::try_from('L'), Ok(Direction::West));
+        assert!(Direction::try_from('x').is_err());
+        assert_eq!(
+            Vec2::ZERO + Direction::North.offset() + Direction::East.offset(),
+            Vec2::new(1, -1)
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use crate::cache::Cache;
+use advent_of_code_solver::errors::{ACResult, Context, Error};
+use reqwest::header::{HeaderMap, HeaderValue, COOKIE, LOCATION, USER_AGENT};
+use reqwest::{RedirectPolicy, StatusCode};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Minimum time between two requests to adventofcode.com, also across invocations.
+const MIN_REQUEST_DELAY: Duration = Duration::from_secs(5);
+const TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_ATTEMPTS: u32 = 3;
+
+/// File in the cache directory with the time of the last request, in milliseconds since epoch.
+pub const LAST_REQUEST_FILE: &str = "last_request";
+
+/// Client for adventofcode.com, that identifies itself and doesn't send requests too often.
+pub struct Client {
+    user_agent: String,
+    last_request_file: PathBuf,
+    verbose: bool,
+}
+
+impl Client {
+    pub fn new(cache: &Cache, contact: Option<&str>, verbose: bool) -> Self {
+        Client {
+            user_agent: user_agent(contact),
+            last_request_file: cache.dir().join(LAST_REQUEST_FILE),
+            verbose,
+        }
+    }
+
+    /// Downloads the page, logged in with the session cookie if one is given.
+    ///
+    /// Timeouts, connection failures and server errors are retried. A redirect means the session
+    /// cookie was rejected.
+    pub fn get(&self, url: &str, session: Option<&str>) -> ACResult<reqwest::Response> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            USER_AGENT,
+            HeaderValue::from_str(&self.user_agent)
+                .map_err(|_| Error::Config("The contact contains invalid characters".to_owned()))?,
+        );
+        if let Some(session) = session {
+            headers.insert(
+                COOKIE,
+                HeaderValue::from_str(&format!("session={}", session)).map_err(|_| {
+                    Error::Config("The session token contains invalid characters".to_owned())
+                })?,
+            );
+        }
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(TIMEOUT)
+            .redirect(RedirectPolicy::none())
+            .build()?;
+
+        let mut attempt = 1;
+        loop {
+            self.wait_for_turn()?;
+            if self.verbose {
+                eprintln!("Requesting {}", url);
+            }
+            match send(&client, url, session.is_some()) {
+                Err(Error::Http(e)) if attempt < MAX_ATTEMPTS && is_transient(&e) => {
+                    if self.verbose {
+                        eprintln!("Attempt {} failed: {}, retrying", attempt, e);
+                    }
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sleeps until the minimum delay since the last request has passed, then records the time
+    /// of this request.
+    fn wait_for_turn(&self) -> ACResult<()> {
+        let last_request = std::fs::read_to_string(&self.last_request_file)
+            .ok()
+            .and_then(|millis| millis.trim().parse().ok())
+            .map(Duration::from_millis);
+        if let Some(delay) = remaining_delay(last_request, now()) {
+            if self.verbose {
+                eprintln!("Waiting {:?} before the next request", delay);
+            }
+            std::thread::sleep(delay);
+        }
+        if let Some(parent) = self.last_request_file.parent() {
+            std::fs::create_dir_all(parent).context("Failed to store time of last request")?;
+        }
+        std::fs::write(&self.last_request_file, now().as_millis().to_string())
+            .context("Failed to store time of last request")
+    }
+}
+
+fn send(client: &reqwest::Client, url: &str, logged_in: bool) -> ACResult<reqwest::Response> {
+    let mut response = client.get(url).send()?;
+    if response.status().is_redirection() {
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .unwrap_or_default();
+        return Err(Error::Unauthorized(format!(
+            "Redirected to {:?}, the session cookie has probably expired. \
+             Log in again and update the session token in the config file",
+            location
+        )));
+    }
+    if logged_in
+        && response.status() == StatusCode::BAD_REQUEST
+        && response.text()?.starts_with("Puzzle inputs differ by user")
+    {
+        return Err(Error::Unauthorized(
+            "The session cookie was rejected, it has probably expired. \
+             Log in again and update the session token in the config file"
+                .to_owned(),
+        ));
+    }
+    Ok(response.error_for_status()?)
+}
+
+/// Whether the page doesn't exist (yet), e.g. the input of a puzzle right before it unlocks.
+pub fn is_not_found(e: &Error) -> bool {
+    match e.root() {
+        Error::Http(e) => e.status() == Some(StatusCode::NOT_FOUND),
+        _ => false,
+    }
+}
+
+fn is_transient(e: &reqwest::Error) -> bool {
+    e.is_timeout() || e.is_server_error() || e.is_http()
+}
+
+fn user_agent(contact: Option<&str>) -> String {
+    let name = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+    match contact {
+        Some(contact) => format!("{} ({})", name, contact),
+        None => name.to_owned(),
+    }
+}
+
+fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Time left until the next request is allowed. A last request in the future, e.g. after the
+/// clock was changed, delays at most by the minimum delay.
+fn remaining_delay(last_request: Option<Duration>, now: Duration) -> Option<Duration> {
+    let next_request = last_request? + MIN_REQUEST_DELAY;
+    next_request
+        .checked_sub(now)
+        .filter(|delay| *delay > Duration::from_secs(0))
+        .map(|delay| std::cmp::min(delay, MIN_REQUEST_DELAY))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_between_requests() {
+        let now = Duration::from_secs(1000);
+        assert_eq!(remaining_delay(None, now), None);
+        assert_eq!(
+            remaining_delay(Some(now - Duration::from_secs(2)), now),
+            Some(MIN_REQUEST_DELAY - Duration::from_secs(2))
+        );
+        assert_eq!(remaining_delay(Some(now - MIN_REQUEST_DELAY), now), None);
+        assert_eq!(
+            remaining_delay(Some(now + Duration::from_secs(3600)), now),
+            Some(MIN_REQUEST_DELAY)
+        );
+    }
+
+    #[test]
+    fn user_agent_with_contact() {
+        assert_eq!(
+            user_agent(Some("alice@example.com")),
+            format!(
+                "advent_of_code_solver/{} (alice@example.com)",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+        assert!(!user_agent(None).contains('('));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::errors::{ACResult, Error, ParseError};
+use std::collections::HashMap;
+
+#[derive(Debug)]
+enum Opcode {
+    Add(i64, i64, usize),
+    Multiply(i64, i64, usize),
+    Input(usize),
+    Output(i64),
+    JumpIfTrue(i64, usize),
+    JumpIfFalse(i64, usize),
+    LessThan(i64, i64, usize),
+    Equals(i64, i64, usize),
+    Offset(i64),
+    Break,
+}
+
+struct Command {
+    opcode: Opcode,
+    command_length: usize,
+}
+
+/// Computer for the Intcode programs of 2019, with relative addressing and unbounded memory.
+pub struct IntCodeComputer {
+    memory: HashMap<usize, i64>,
+    pos: usize,
+    relative_base: usize,
+}
+
+/// Request of a running program, the callback returns the value for an input.
+pub enum Io {
+    Input,
+    Output(i64),
+}
+
+impl IntCodeComputer {
+    pub fn new(ops: &[i64]) -> Self {
+        let mut memory = HashMap::new();
+        for (i, m) in ops.iter().enumerate() {
+            memory.insert(i, *m);
+        }
+        IntCodeComputer {
+            memory,
+            pos: 0,
+            relative_base: 0,
+        }
+    }
+
+    pub fn read_memory(&self, pos: usize) -> i64 {
+        *self.memory.get(&pos).unwrap_or(&0)
+    }
+
+    pub fn write_memory(&mut self, pos: usize, value: i64) {
+        self.memory.insert(pos, value);
+    }
+
+    fn get_mode(&self, parameter_i: u32) -> i64 {
+        self.read_memory(self.pos) / 10i64.pow(parameter_i + 2) % 10
+    }
+
+    fn invalid_mode(&self, parameter_i: u32) -> Error {
+        Error::new(format!(
+            "Invalid mode {} for parameter {} at pos {}",
+            self.get_mode(parameter_i),
+            parameter_i,
+            self.pos
+        ))
+    }
+
+    fn get_output(&self, parameter_i: u32) -> ACResult<usize> {
+        let i = self.read_memory(self.pos + 1 + parameter_i as usize);
+        match self.get_mode(parameter_i) {
+            0 => Ok(i as usize),
+            2 => Ok((self.relative_base as i64 + i) as usize),
+            _ => Err(self.invalid_mode(parameter_i)),
+        }
+    }
+
+    fn get_input(&self, parameter_i: u32) -> ACResult<i64> {
+        let i = self.read_memory(self.pos + 1 + parameter_i as usize);
+        match self.get_mode(parameter_i) {
+            0 => Ok(self.read_memory(i as usize)),
+            1 => Ok(i),
+            2 => Ok(self.read_memory((self.relative_base as i64 + i) as usize)),
+            _ => Err(self.invalid_mode(parameter_i)),
+        }
+    }
+
+    fn parse_command(&self) -> ACResult<Command> {
+        let opcode = self.read_memory(self.pos) % 100;
+        match opcode {
+            1 => {
+                let (i1, i2, o) = (self.get_input(0)?, self.get_input(1)?, self.get_output(2)?);
+                Ok(Command {
+                    opcode: Opcode::Add(i1, i2, o),
+                    command_length: 4,
+                })
+            }
+            2 => {
+                let (i1, i2, o) = (self.get_input(0)?, self.get_input(1)?, self.get_output(2)?);
+                Ok(Command {
+                    opcode: Opcode::Multiply(i1, i2, o),
+                    command_length: 4,
+                })
+            }
+            3 => {
+                let o = self.get_output(0)?;
+                Ok(Command {
+                    opcode: Opcode::Input(o),
+                    command_length: 2,
+                })
+            }
+            4 => {
+                let i = self.get_input(0)?;
+                Ok(Command {
+                    opcode: Opcode::Output(i),
+                    command_length: 2,
+                })
+            }
+            5 => {
+                let (i1, i2) = (self.get_input(0)?, self.get_input(1)? as usize);
+                Ok(Command {
+                    opcode: Opcode::JumpIfTrue(i1, i2),
+                    command_length: 3,
+                })
+            }
+            6 => {
+                let (i1, i2) = (self.get_input(0)?, self.get_input(1)? as usize);
+                Ok(Command {
+                    opcode: Opcode::JumpIfFalse(i1, i2),
+                    command_length: 3,
+                })
+            }
+            7 => {
+                let (i1, i2, o) = (self.get_input(0)?, self.get_input(1)?, self.get_output(2)?);
+                Ok(Command {
+                    opcode: Opcode::LessThan(i1, i2, o),
+                    command_length: 4,
+                })
+            }
+            8 => {
+                // equals
+                let (i1, i2, o) = (self.get_input(0)?, self.get_input(1)?, self.get_output(2)?);
+                Ok(Command {
+                    opcode: Opcode::Equals(i1, i2, o),
+                    command_length: 4,
+                })
+            }
+            9 => {
+                let i = self.get_input(0)?;
+                Ok(Command {
+                    opcode: Opcode::Offset(i),
+                    command_length: 2,
+                })
+            }
+            99 => Ok(Command {
+                opcode: Opcode::Break,
+                command_length: 1,
+            }),
+            value => Err(Error::new(format!(
+                "Invalid machine state at pos {}: {}",
+                self.pos, value
+            ))),
+        }
+    }
+
+    /// Runs the program until it halts and returns its outputs.
+    pub fn compute(&mut self, inputs: &[i64]) -> ACResult<Vec<i64>> {
+        let mut outputs = Vec::new();
+        let mut inputs = inputs.iter();
+        self.compute_thread(&mut |io| match io {
+            Io::Input => inputs
+                .next()
+                .copied()
+                .ok_or_else(|| Error::new_str("Program requested more inputs than given")),
+            Io::Output(o) => {
+                outputs.push(o);
+                Ok(0)
+            }
+        })?;
+        Ok(outputs)
+    }
+
+    /// Runs the program until it halts, passing inputs and outputs through the callback.
+    ///
+    /// An error returned by the callback stops the program.
+    pub fn compute_thread(&mut self, io: &mut dyn FnMut(Io) -> ACResult<i64>) -> ACResult<()> {
+        loop {
+            let Command {
+                opcode,
+                command_length,
+            } = self.parse_command()?;
+            match opcode {
+                Opcode::Add(i1, i2, o) => {
+                    self.memory.insert(o, i1 + i2);
+                    self.pos += command_length;
+                }
+                Opcode::Multiply(i1, i2, o) => {
+                    self.memory.insert(o, i1 * i2);
+                    self.pos += command_length;
+                }
+                Opcode::Input(o) => {
+                    self.memory.insert(o, io(Io::Input)?);
+                    self.pos += command_length;
+                }
+                Opcode::Output(i) => {
+                    io(Io::Output(i))?;
+                    self.pos += command_length;
+                }
+                Opcode::JumpIfTrue(i1, i2) => {
+                    if i1 != 0 {
+                        self.pos = i2;
+                    } else {
+                        self.pos += command_length;
+                    }
+                }
+                Opcode::JumpIfFalse(i1, i2) => {
+                    if i1 == 0 {
+                        self.pos = i2;
+                    } else {
+                        self.pos += command_length;
+                    }
+                }
+                Opcode::LessThan(i1, i2, o) => {
+                    self.memory.insert(o, if i1 < i2 { 1 } else { 0 });
+                    self.pos += command_length;
+                }
+                Opcode::Equals(i1, i2, o) => {
+                    self.memory.insert(o, if i1 == i2 { 1 } else { 0 });
+                    self.pos += command_length;
+                }
+                Opcode::Offset(i) => {
+                    self.relative_base = (self.relative_base as i64 + i) as usize;
+                    self.pos += command_length;
+                }
+                Opcode::Break => {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses the comma separated values of the program, errors point to the invalid value.
+pub fn parse_intcode(input: &str) -> ACResult<Vec<i64>> {
+    let mut offset = input.len() - input.trim_start().len();
+    input
+        .trim()
+        .split(',')
+        .map(|value| {
+            let start = offset;
+            offset += value.len() + 1;
+            value
+                .parse::<i64>()
+                .map_err(|_| Error::from(ParseError::at(input, &input[start..], "an integer")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(program: &str, inputs: &[i64]) -> ACResult<Vec<i64>> {
+        IntCodeComputer::new(&parse_intcode(program)?).compute(inputs)
+    }
+
+    #[test]
+    fn run_programs() {
+        let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        assert_eq!(run(quine, &[]), Ok(parse_intcode(quine).unwrap()));
+        assert_eq!(
+            run("1102,34915192,34915192,7,4,7,99,0", &[]),
+            Ok(vec![1219070632396864])
+        );
+        // Outputs 1 if the input is equal to 8
+        assert_eq!(run("3,9,8,9,10,9,4,9,99,-1,8", &[8]), Ok(vec![1]));
+        assert_eq!(run("3,9,8,9,10,9,4,9,99,-1,8", &[7]), Ok(vec![0]));
+
+        let mut computer =
+            IntCodeComputer::new(&parse_intcode("1,9,10,3,2,3,11,0,99,30,40,50").unwrap());
+        assert_eq!(computer.compute(&[]), Ok(vec![]));
+        assert_eq!(computer.read_memory(0), 3500);
+    }
+
+    #[test]
+    fn invalid_programs() {
+        assert!(run("3,0,99", &[]).is_err());
+        assert!(run("11101,1,1,0,99", &[]).is_err());
+        assert!(run("42", &[]).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+use crate::answer::Answer;
+use crate::errors::{ACResult, Error};
+
+mod advent2015;
+mod advent2017;
+mod advent2018;
+mod advent2019;
+pub mod answer;
+pub mod automaton;
+pub mod cycle;
+pub mod elfcode;
+pub mod errors;
+pub mod export;
+pub mod geometry;
+pub mod intcode;
+pub mod ocr;
+pub mod parsing;
+pub mod progress;
+pub mod regions;
+pub mod search;
+pub mod utils;
+pub mod visualize;
+pub mod voronoi;
+
+/// Solves the puzzle, the input is normalized first so days don't have to deal with line endings.
+pub fn get_result(input: &str, year: i32, day: u8, level: u8) -> ACResult<Answer> {
+    let input = utils::normalize_input(input);
+    let data = input.as_bytes();
+    match year {
+        2015 => advent2015::get_result(data, day, level),
+        2017 => advent2017::get_result(data, day, level),
+        2018 => advent2018::get_result(data, day, level),
+        2019 => advent2019::get_result(data, day, level),
+        _ => Err(Error::Unimplemented(format!(
+            "Year {} is not implemented",
+            year
+        ))),
+    }
+}
+
+/// Only parses the input, for days that expose their parsing separately.
+pub fn parse_input(input: &str, year: i32, day: u8) -> Option<ACResult<()>> {
+    let input = utils::normalize_input(input);
+    let data = input.as_bytes();
+    match year {
+        2018 => advent2018::parse_input(data, day),
+        _ => None,
+    }
+}
+
+/// Solves the puzzle in its own thread, so it can time out.
+pub fn solve(
+    runner: &progress::Runner,
+    input: &str,
+    year: i32,
+    day: u8,
+    level: u8,
+) -> ACResult<Answer> {
+    let input = input.to_owned();
+    runner.run(move || get_result(&input, year, day, level))
+}
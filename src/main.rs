@@ -1,18 +1,30 @@
-use crate::errors::{ACResult, Error};
+use crate::cache::Cache;
+use crate::report::{Format, Report};
+use advent_of_code_solver::errors::{ACResult, Context, Error};
+use advent_of_code_solver::{export, get_result, parse_input, progress, solve, utils, visualize};
 use chrono::Datelike;
 use clap::value_t;
-use clap::{App, Arg};
-use serde::Deserialize;
-use std::io::Read;
-
-mod advent2015;
-mod advent2017;
-mod advent2018;
-mod advent2019;
-mod errors;
-mod utils;
-
-fn main() -> ACResult<()> {
+use clap::{App, AppSettings, Arg, SubCommand};
+
+mod answers;
+mod bench;
+mod cache;
+mod config;
+mod download;
+mod examples;
+mod http;
+mod report;
+mod scaffold;
+mod unlock;
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(e.exit_code());
+    }
+}
+
+fn run() -> ACResult<()> {
     let matches = App::new("Advent solver")
         .version("0.1.0")
         .author("AsamK <asamk@gmx.de>")
@@ -24,6 +36,7 @@ fn main() -> ACResult<()> {
                 .value_name("YEAR")
                 .help("Choose the advent year [default: current year]")
                 .requires("day")
+                .global(true)
                 .takes_value(true),
         ).arg(
             Arg::with_name("day")
@@ -31,6 +44,7 @@ fn main() -> ACResult<()> {
                 .long("day")
                 .value_name("DAY")
                 .help("Choose the advent day [default: current day]")
+                .global(true)
                 .takes_value(true),
         ).arg(
             Arg::with_name("level")
@@ -39,24 +53,185 @@ fn main() -> ACResult<()> {
                 .value_name("LEVEL")
                 .default_value("1")
                 .help("Choose the level")
+                .global(true)
                 .takes_value(true),
         ).arg(
             Arg::with_name("input-file")
                 .short("f")
                 .long("input-file")
                 .value_name("INPUT_FILE")
-                .help("Specify a file to use as puzzle input. [default: Download user specific input from adventofcode.com]")
+                .help("Specify a file to use as puzzle input, - reads the input from stdin. [default: Download user specific input from adventofcode.com]")
+                .takes_value(true),
+        ).arg(
+            Arg::with_name("input")
+                .long("input")
+                .value_name("INPUT")
+                .conflicts_with("input-file")
+                .help("Pass a short puzzle input directly, e.g. a single line key")
+                .takes_value(true),
+        ).arg(
+            Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .possible_values(&["text", "json"])
+                .default_value("text")
+                .help("Choose the output format of the result")
+                .global(true)
                 .takes_value(true),
         ).arg(
             Arg::with_name("verbose")
                 .short("v")
                 .long("verbose")
-                .help("Make output more verbose"),
+                .help("Make output more verbose")
+                .global(true),
+        ).arg(
+            Arg::with_name("timeout")
+                .long("timeout")
+                .value_name("SECONDS")
+                .help("Stop the solver if it takes longer, benchmarks apply it to all runs of a puzzle [default: no timeout]")
+                .global(true)
+                .takes_value(true),
+        ).arg(
+            Arg::with_name("visualize")
+                .long("visualize")
+                .help("Animate the simulation in the terminal, for days that support it"),
+        ).arg(
+            Arg::with_name("frame-delay")
+                .long("frame-delay")
+                .value_name("MILLISECONDS")
+                .default_value("100")
+                .help("Pause between two frames of the animation, also used for exported GIF animations")
+                .takes_value(true),
+        ).arg(
+            Arg::with_name("follow")
+                .long("follow")
+                .requires("visualize")
+                .help("Move the animation's viewport along with the action on fields larger than the terminal"),
+        ).arg(
+            Arg::with_name("export")
+                .long("export")
+                .value_name("DIR")
+                .help("Export the frames of the simulation as images to the directory, for days that support it")
+                .takes_value(true),
+        ).arg(
+            Arg::with_name("export-format")
+                .long("export-format")
+                .value_name("FORMAT")
+                .possible_values(&["png", "ppm", "gif"])
+                .default_value("png")
+                .help("Export one PNG or PPM image per frame or one animated GIF")
+                .takes_value(true),
+        ).arg(
+            Arg::with_name("export-scale")
+                .long("export-scale")
+                .value_name("PIXELS")
+                .default_value("4")
+                .help("Width and height of a cell in exported images")
+                .takes_value(true),
+        ).arg(
+            Arg::with_name("profile")
+                .short("p")
+                .long("profile")
+                .value_name("PROFILE")
+                .help("Choose the account profile from the config file [default: top level session token]")
+                .global(true)
+                .takes_value(true),
+        ).arg(
+            Arg::with_name("all-profiles")
+                .long("all-profiles")
+                .conflicts_with_all(&["profile", "input-file", "input", "wait"])
+                .help("Run the solver against the input of every configured profile"),
+        ).arg(
+            Arg::with_name("record-answer")
+                .long("record-answer")
+                .conflicts_with_all(&["input-file", "input", "all-profiles"])
+                .help("Record the result as the correct answer of the profile"),
+        ).arg(
+            Arg::with_name("wait")
+                .short("w")
+                .long("wait")
+                .conflicts_with_all(&["input-file", "input", "offline"])
+                .help("Wait until the puzzle unlocks, then download the input and solve it"),
+        ).arg(
+            Arg::with_name("offline")
+                .long("offline")
+                .global(true)
+                .help("Only use cached inputs and descriptions, never download anything"),
+        ).subcommand(
+            SubCommand::with_name("extract")
+                .about("Extracts examples from the puzzle description and stores them as test cases"),
+        ).subcommand(
+            SubCommand::with_name("new")
+                .about("Creates and registers the module for a new day from a template")
+                .arg(
+                    Arg::with_name("src-dir")
+                        .long("src-dir")
+                        .value_name("SRC_DIR")
+                        .help("Source directory of the solver [default: src directory of this crate]")
+                        .takes_value(true),
+                ).arg(
+                    Arg::with_name("download")
+                        .long("download")
+                        .help("Download the input and extract the examples of the new day"),
+                ),
+        ).subcommand(
+            SubCommand::with_name("test")
+                .about("Runs the stored examples through the solver")
+                .after_help("Only the examples of the chosen level are run, if a level is given explicitly."),
+        ).subcommand(
+            SubCommand::with_name("bench")
+                .about("Measures how long the solver takes for the real input")
+                .after_help("Both levels are measured, unless a level is given explicitly. Days that expose their input parsing also report the parse time.")
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .help("Measure all puzzles with a cached input, skipping unimplemented ones"),
+                ).arg(
+                    Arg::with_name("runs")
+                        .long("runs")
+                        .value_name("RUNS")
+                        .default_value("10")
+                        .help("Number of measured runs after one warm-up run")
+                        .takes_value(true),
+                ).arg(
+                    Arg::with_name("threshold")
+                        .long("threshold")
+                        .value_name("PERCENT")
+                        .default_value("10")
+                        .help("Flag medians that are slower than the previous run by more than this")
+                        .takes_value(true),
+                ).arg(
+                    Arg::with_name("no-save")
+                        .long("no-save")
+                        .help("Don't add the results to the benchmark history"),
+                ),
+        ).subcommand(
+            SubCommand::with_name("cache")
+                .about("Manages the cached inputs, descriptions and answers")
+                .after_help("Only the files of the chosen puzzle are used, if a day is given explicitly.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(SubCommand::with_name("list").about("Lists the cached files"))
+                .subcommand(
+                    SubCommand::with_name("export")
+                        .about("Copies the cached files to a directory, e.g. to share them")
+                        .arg(Arg::with_name("dir").value_name("DIR").required(true)),
+                ).subcommand(
+                    SubCommand::with_name("import")
+                        .about("Imports an exported cache directory or the input file of the chosen puzzle")
+                        .after_help("Existing files are kept. An input file is stored for the chosen profile.")
+                        .arg(Arg::with_name("path").value_name("PATH").required(true)),
+                ).subcommand(
+                    SubCommand::with_name("prune")
+                        .about("Removes empty files and inputs that contain an error page from a failed download")
+                        .arg(
+                            Arg::with_name("dry-run")
+                                .long("dry-run")
+                                .help("Only list the files that would be removed"),
+                        ),
+                ),
         ).get_matches();
 
-    let hour = 3600;
-    // Advent website is in TimeZone EST/UTC-5
-    let timezone = chrono::FixedOffset::west(5 * hour);
+    let timezone = unlock::advent_timezone();
 
     let year = if matches.is_present("year") {
         value_t!(matches.value_of("year"), i32).unwrap_or_else(|e| e.exit())
@@ -72,91 +247,457 @@ fn main() -> ACResult<()> {
 
     let level = value_t!(matches.value_of("level"), u8).unwrap_or_else(|e| e.exit());
 
+    let format = value_t!(matches.value_of("format"), Format).unwrap_or_else(|e| e.exit());
+
     let verbose = matches.is_present("verbose");
 
+    let timeout = if matches.is_present("timeout") {
+        let seconds = value_t!(matches.value_of("timeout"), f64).unwrap_or_else(|e| e.exit());
+        if !(seconds > 0.0 && seconds.is_finite()) {
+            return Err(Error::Config(
+                "The timeout must be a positive number of seconds".to_owned(),
+            ));
+        }
+        Some(std::time::Duration::from_secs_f64(seconds))
+    } else {
+        None
+    };
+    let runner = progress::Runner { timeout, verbose };
+
+    if matches.is_present("visualize") {
+        let delay = value_t!(matches.value_of("frame-delay"), u64).unwrap_or_else(|e| e.exit());
+        visualize::enable(visualize::Settings::new(
+            std::time::Duration::from_millis(delay),
+            matches.is_present("follow"),
+        ));
+    }
+
+    if let Some(dir) = matches.value_of_os("export") {
+        let format = value_t!(matches.value_of("export-format"), export::Format)
+            .unwrap_or_else(|e| e.exit());
+        let scale = value_t!(matches.value_of("export-scale"), u32).unwrap_or_else(|e| e.exit());
+        if scale == 0 {
+            return Err(Error::Config(
+                "The export scale must be at least one pixel".to_owned(),
+            ));
+        }
+        let delay = value_t!(matches.value_of("frame-delay"), u64).unwrap_or_else(|e| e.exit());
+        export::enable(export::Settings {
+            dir: dir.into(),
+            format,
+            scale,
+            delay: std::time::Duration::from_millis(delay),
+        });
+    }
+
     if verbose {
         eprintln!("Solving puzzle for {} day {} level {}", year, day, level);
     }
 
     let xdg_dirs = xdg::BaseDirectories::with_prefix("advent_of_code_solver").unwrap();
 
-    let config: Option<Config> = if let Some(config_path) = xdg_dirs.find_config_file("config.toml")
-    {
-        let mut logo_file = std::fs::File::open(config_path)
-            .map_err(|_| Error::new_str("Failed to load config file."))?;
-        let mut config_string = String::new();
-        logo_file
-            .read_to_string(&mut config_string)
-            .map_err(|_| Error::new_str("Failed to load config file."))?;
-        Some(
-            toml::from_str(&config_string)
-                .map_err(|_| Error::new_str("Failed to load config file."))?,
-        )
+    let config = config::Config::load(&xdg_dirs)?;
+    let cache = Cache::load(&xdg_dirs, &config, matches.is_present("offline"));
+    let client = http::Client::new(&cache, config.contact.as_deref(), verbose);
+    let profile = config.profile(matches.value_of("profile"))?;
+
+    match matches.subcommand() {
+        ("extract", Some(_)) => {
+            let description = download::get_description(
+                &cache,
+                &client,
+                profile.session_token.as_deref(),
+                year,
+                day,
+                verbose,
+            )?;
+            let mut examples = examples::load_examples(&cache, year, day)?;
+            let stdin = std::io::stdin();
+            let new_examples = examples::select_examples(&description, stdin.lock())?;
+            println!("Extracted {} examples", new_examples.len());
+            examples.extend(new_examples);
+            return examples::store_examples(&cache, year, day, &examples, verbose);
+        }
+        ("new", Some(sub_matches)) => {
+            let src_dir = sub_matches
+                .value_of_os("src-dir")
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|| std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src"));
+            for path in scaffold::create_day(&src_dir, year, day)? {
+                println!("Wrote {}", path.display());
+            }
+            if sub_matches.is_present("download") {
+                download::get_input_file(&cache, &client, &profile, year, day, verbose)?;
+                let description = download::get_description(
+                    &cache,
+                    &client,
+                    profile.session_token.as_deref(),
+                    year,
+                    day,
+                    verbose,
+                )?;
+                let stdin = std::io::stdin();
+                let examples = examples::select_examples(&description, stdin.lock())?;
+                examples::store_examples(&cache, year, day, &examples, verbose)?;
+            }
+            return Ok(());
+        }
+        ("test", Some(_)) => {
+            let examples = examples::load_examples(&cache, year, day)?;
+            let level = if matches.occurrences_of("level") > 0 {
+                Some(level)
+            } else {
+                None
+            };
+            return examples::run_examples(&examples, year, day, level, &runner);
+        }
+        ("bench", Some(sub_matches)) => {
+            let levels = if matches.occurrences_of("level") > 0 {
+                vec![level]
+            } else {
+                vec![1, 2]
+            };
+            let puzzles: Vec<(i32, u8, u8)> = if sub_matches.is_present("all") {
+                let current_year = chrono::Local::now().with_timezone(&timezone).year();
+                (2015..=current_year)
+                    .flat_map(|year| (1..=25).map(move |day| (year, day)))
+                    .flat_map(|(year, day)| levels.iter().map(move |&level| (year, day, level)))
+                    .collect()
+            } else {
+                levels.iter().map(|&level| (year, day, level)).collect()
+            };
+            return run_benchmarks(
+                &cache,
+                &client,
+                &profile,
+                sub_matches,
+                &puzzles,
+                format,
+                &runner,
+            );
+        }
+        ("cache", Some(sub_matches)) => {
+            let puzzle = if matches.occurrences_of("day") > 0 {
+                Some((year, day))
+            } else {
+                None
+            };
+            return run_cache_command(&cache, sub_matches, &profile, puzzle, verbose);
+        }
+        _ => {}
+    }
+
+    if matches.is_present("all-profiles") {
+        let (reports, first_error) =
+            solve_all_profiles(&cache, &client, &config, &runner, year, day, level)?;
+        return print_profile_reports(&reports, first_error, format);
+    }
+
+    let local_input = matches.is_present("input-file") || matches.is_present("input");
+    let input = if let Some(input) = matches.value_of("input") {
+        input.to_owned()
+    } else if let Some(input_file) = matches.value_of_os("input-file") {
+        if input_file == "-" {
+            utils::read_all(std::io::stdin())?
+        } else {
+            std::fs::read_to_string(input_file).context("Failed to load input file")?
+        }
+    } else if matches.is_present("wait") {
+        if profile.session_token.is_none() {
+            return Err(Error::Config(
+                "No session cookie to download input".to_owned(),
+            ));
+        }
+        unlock::wait_for_unlock(year, day)?;
+        // Right after the unlock the input may not be available yet. Transient failures are
+        // already retried by the client.
+        let input_file = unlock::retry_with_backoff(
+            unlock::UNLOCK_ATTEMPTS,
+            unlock::UNLOCK_RETRY_DELAY,
+            verbose,
+            http::is_not_found,
+            || download::get_input_file(&cache, &client, &profile, year, day, verbose),
+        )?;
+        utils::read_all(input_file)?
     } else {
-        None
+        utils::read_all(download::get_input_file(
+            &cache, &client, &profile, year, day, verbose,
+        )?)?
+    };
+
+    let start = std::time::Instant::now();
+    let result = solve(&runner, &input, year, day, level);
+    if let Err(e) = &result {
+        // Waiting is mostly used for days that aren't solved yet, getting the input is enough then
+        if matches!(e.root(), Error::Unimplemented(_)) && matches.is_present("wait") {
+            eprintln!(
+                "Input of {} day {} saved, there is no solver for level {} yet",
+                year, day, level
+            );
+            return Ok(());
+        }
+    }
+    let mut report = if local_input {
+        Report::new(year, day, level, None)
+    } else {
+        Report::new(year, day, level, Some(profile.display_name()))
     };
+    report.set_result(&result, start.elapsed());
+    // Also finishes the export of a failed solver, its frames may help debugging
+    let exported_frames = export::finish();
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            // The report carries the error, e.g. for scripts reading the JSON output
+            report.print(format)?;
+            return Err(e);
+        }
+    };
+    let exported_frames = exported_frames?;
+    if verbose && matches.is_present("export") {
+        eprintln!("Exported {} frames", exported_frames);
+    }
 
-    let input_file = if matches.is_present("input-file") {
-        std::fs::File::open(matches.value_of_os("input-file").unwrap())
-            .map_err(|_| Error::new_str("Failed to load input file."))?
+    if matches.is_present("record-answer") {
+        report.print(format)?;
+        answers::store_answer(
+            &cache,
+            &profile,
+            year,
+            day,
+            level,
+            &result.canonical(),
+            verbose,
+        )
     } else {
-        let input_file_name = format!("input/{}/{}", year, day);
-        if let Some(input_file_path) = xdg_dirs.find_data_file(&input_file_name) {
-            std::fs::File::open(input_file_path)
-                .map_err(|_| Error::new_str("Failed to load input file."))?
+        if !local_input {
+            report.check_answer(answers::load_answer(&cache, &profile, year, day, level)?);
+        }
+        report.print(format)
+    }
+}
+
+/// Measures the puzzles and compares the results with the previous run from the history.
+///
+/// With `--all` only cached inputs are used and puzzles that are unimplemented or fail are skipped.
+fn run_benchmarks(
+    cache: &Cache,
+    client: &http::Client,
+    profile: &config::Profile,
+    matches: &clap::ArgMatches,
+    puzzles: &[(i32, u8, u8)],
+    format: Format,
+    runner: &progress::Runner,
+) -> ACResult<()> {
+    let verbose = runner.verbose;
+    let runs = value_t!(matches.value_of("runs"), usize).unwrap_or_else(|e| e.exit());
+    let threshold =
+        value_t!(matches.value_of("threshold"), f64).unwrap_or_else(|e| e.exit()) / 100.0;
+    let all = matches.is_present("all");
+    let history = bench::load_history(cache)?;
+    let mut results = Vec::new();
+    for &(year, day, level) in puzzles {
+        let input = if all {
+            match cache.find_file(profile.input_file_name(year, day)) {
+                Some(path) => std::fs::read_to_string(path).context("Failed to load input file")?,
+                None => continue,
+            }
         } else {
-            // Download
-            let session = config
-                .ok_or_else(|| Error::new_str("No session cookie to download input"))?
-                .session_token
-                .ok_or_else(|| Error::new_str("No session cookie to download input"))?;
-            let request_url = format!("https://adventofcode.com/{}/day/{}/input", year, day);
-            let mut body = reqwest::Client::new()
-                .request(reqwest::Method::GET, &request_url)
-                .header(reqwest::header::COOKIE, format!("session={}", session))
-                .send()
-                .map_err(|_| Error::new_str("Failed to download input file."))?;
-            if !body.status().is_success() {
-                return Err(Error::new(format!(
-                    "Failed to download input file: {}",
-                    body.status()
-                )));
+            utils::read_all(download::get_input_file(
+                cache, client, profile, year, day, verbose,
+            )?)?
+        };
+        if verbose {
+            eprintln!("Measuring {} day {} level {}", year, day, level);
+        }
+        let measured_input = input.clone();
+        let samples = match runner
+            .run(move || bench::measure(runs, || get_result(&measured_input, year, day, level)))
+        {
+            Ok(samples) => samples,
+            Err(e) if all => {
+                match e.root() {
+                    Error::Unimplemented(_) => {}
+                    Error::Timeout(_) => eprintln!(
+                        "Warning: {} day {} level {} skipped: {}",
+                        year, day, level, e
+                    ),
+                    _ => eprintln!(
+                        "Warning: {} day {} level {} failed: {}",
+                        year, day, level, e
+                    ),
+                }
+                continue;
             }
+            Err(e) => return Err(e),
+        };
+        let parse_samples = match parse_input(&input, year, day) {
+            Some(_) => Some(bench::measure(runs, || {
+                parse_input(&input, year, day).unwrap_or(Ok(()))
+            })?),
+            None => None,
+        };
+        let mut result = bench::BenchResult {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            year,
+            day,
+            level,
+            profile: profile.display_name().to_owned(),
+            runs,
+            total: bench::Stats::from_samples(&samples)
+                .ok_or_else(|| Error::Config("At least one run is required".to_owned()))?,
+            parse: parse_samples.and_then(|samples| bench::Stats::from_samples(&samples)),
+            change: None,
+        };
+        bench::compare_with_history(&mut result, &history);
+        results.push(result);
+    }
+    if !matches.is_present("no-save") {
+        bench::append_history(cache, &results)?;
+    }
+    match format {
+        Format::Text => bench::print_table(&results, threshold),
+        Format::Json => println!("{}", report::to_json(&results)?),
+    }
+    let regressions = results
+        .iter()
+        .filter(|r| r.is_regression(threshold))
+        .count();
+    if regressions > 0 {
+        eprintln!(
+            "Warning: {} puzzles got slower since the previous run",
+            regressions
+        );
+    }
+    Ok(())
+}
 
-            if let Ok(input_file_path) = xdg_dirs.place_data_file(&input_file_name) {
-                if verbose {
-                    eprintln!("Storing input file to: {:?}", &input_file_path);
-                }
-                {
-                    let mut input_file = std::fs::File::create(&input_file_path)
-                        .map_err(|_| Error::new_str("Failed to store downloaded input file."))?;
-                    body.copy_to(&mut input_file)
-                        .map_err(|_| Error::new_str("Failed to store downloaded input file."))?;
+fn run_cache_command(
+    cache: &Cache,
+    matches: &clap::ArgMatches,
+    profile: &config::Profile,
+    puzzle: Option<(i32, u8)>,
+    verbose: bool,
+) -> ACResult<()> {
+    if verbose {
+        eprintln!("Cache directory: {:?}", cache.dir());
+    }
+    let files = cache.files(puzzle.map(|p| p.0), puzzle.map(|p| p.1))?;
+    match matches.subcommand() {
+        ("list", Some(_)) => {
+            for file in files.iter() {
+                println!("{}", file.display());
+            }
+        }
+        ("export", Some(sub_matches)) => {
+            let dir = std::path::Path::new(sub_matches.value_of_os("dir").unwrap());
+            cache.export(&files, dir)?;
+            println!("Exported {} files to {}", files.len(), dir.display());
+        }
+        ("import", Some(sub_matches)) => {
+            let path = std::path::Path::new(sub_matches.value_of_os("path").unwrap());
+            if path.is_dir() {
+                let (imported, conflicts) = cache.import(path)?;
+                for file in conflicts.iter() {
+                    eprintln!("Warning: kept existing {}, it differs", file.display());
                 }
-
-                std::fs::File::open(&input_file_path)
-                    .map_err(|_| Error::new_str("Failed to load input file."))?
+                println!("Imported {} files", imported.len());
             } else {
-                Err(Error::new_str("Failed to store download file"))?
+                let (year, day) = puzzle.ok_or_else(|| {
+                    Error::Config("Choose the day of the imported input file".to_owned())
+                })?;
+                let input_file_path = cache
+                    .place_file(profile.input_file_name(year, day))
+                    .context("Failed to import input file")?;
+                std::fs::copy(path, input_file_path).context("Failed to import input file")?;
+                println!("Imported input of {} day {}", year, day);
             }
         }
-    };
-    let data = std::io::BufReader::new(input_file);
-
-    let result = match year {
-        2015 => advent2015::get_result(data, day, level),
-        2017 => advent2017::get_result(data, day, level),
-        2018 => advent2018::get_result(data, day, level),
-        2019 => advent2019::get_result(data, day, level),
-        _ => Err(Error::new(format!("Year {} is not implemented", year))),
-    }?;
-
-    println!("{}", result);
-
+        ("prune", Some(sub_matches)) => {
+            for file in cache.invalid_files(&files).iter() {
+                println!("Removing {}", file.display());
+                if !sub_matches.is_present("dry-run") {
+                    cache.remove(file)?;
+                }
+            }
+        }
+        _ => {}
+    }
     Ok(())
 }
 
-#[derive(Deserialize)]
-struct Config {
-    session_token: Option<String>,
+/// Returns the reports of all profiles and the first error, to exit with the code of its kind.
+fn solve_all_profiles(
+    cache: &Cache,
+    client: &http::Client,
+    config: &config::Config,
+    runner: &progress::Runner,
+    year: i32,
+    day: u8,
+    level: u8,
+) -> ACResult<(Vec<Report>, Option<Error>)> {
+    let profiles = config.profiles();
+    if profiles.is_empty() {
+        return Err(Error::Config("No profiles configured".to_owned()));
+    }
+    let mut reports = Vec::new();
+    let mut first_error = None;
+    for profile in profiles.iter() {
+        let mut report = Report::new(year, day, level, Some(profile.display_name()));
+        let start = std::time::Instant::now();
+        let result = download::get_input_file(cache, client, profile, year, day, runner.verbose)
+            .and_then(utils::read_all)
+            .and_then(|input| solve(runner, &input, year, day, level));
+        report.set_result(&result, start.elapsed());
+        report.check_answer(answers::load_answer(cache, profile, year, day, level)?);
+        reports.push(report);
+        if let Err(e) = result {
+            first_error.get_or_insert(e);
+        }
+    }
+    Ok((reports, first_error))
+}
+
+/// Prints one line per profile, fails if solving failed for any profile.
+fn print_profile_reports(
+    reports: &[Report],
+    first_error: Option<Error>,
+    format: Format,
+) -> ACResult<()> {
+    match format {
+        Format::Text => {
+            for report in reports.iter() {
+                let name = report.profile.as_deref().unwrap_or_default();
+                match (&report.answer, &report.error) {
+                    (Some(answer), _) => {
+                        let check = match (report.matches_recorded_answer, &report.recorded_answer)
+                        {
+                            (Some(true), _) => " (correct)".to_owned(),
+                            (Some(false), Some(recorded)) => {
+                                format!(" (differs from recorded answer {})", recorded)
+                            }
+                            _ => String::new(),
+                        };
+                        println!("{}: {}{}", name, answer, check);
+                    }
+                    (None, error) => {
+                        println!("{}: failed: {}", name, error.as_deref().unwrap_or_default())
+                    }
+                }
+            }
+        }
+        Format::Json => println!("{}", report::to_json(reports)?),
+    }
+    if let Some(e) = first_error {
+        let failed = reports.iter().filter(|r| r.error.is_some()).count();
+        return Err(Error::Context(
+            format!(
+                "Solving failed for {} of {} profiles",
+                failed,
+                reports.len()
+            ),
+            Box::new(e),
+        ));
+    }
+    Ok(())
 }
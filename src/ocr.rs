@@ -0,0 +1,171 @@
+use crate::utils::Field;
+
+/// The font used for 6 pixel high letters, e.g. in 2019 day 8 and 11.
+const SMALL_LETTERS: &str = "ABCEFGHIJKLOPRSUYZ";
+const SMALL_FONT: [&str; 6] = [
+    ".##...###....##...####..####...##...#..#..###....##..#..#..#......##...###...###....###..#..#..#...#..####",
+    "#..#..#..#..#..#..#.....#.....#..#..#..#...#......#..#.#...#.....#..#..#..#..#..#..#.....#..#..#...#.....#",
+    "#..#..###...#.....###...###...#.....####...#......#..##....#.....#..#..#..#..#..#..#.....#..#...#.#.....#.",
+    "####..#..#..#.....#.....#.....#.##..#..#...#......#..#.#...#.....#..#..###...###....##...#..#....#.....#..",
+    "#..#..#..#..#..#..#.....#.....#..#..#..#...#...#..#..#.#...#.....#..#..#.....#.#......#..#..#....#....#...",
+    "#..#..###....##...####..#......###..#..#..###...##...#..#..####...##...#.....#..#..###....##.....#....####",
+];
+
+/// The font used for 10 pixel high letters, e.g. in 2018 day 10.
+const LARGE_LETTERS: &str = "ABCEFGHJKLNPRXZ";
+const LARGE_FONT: [&str; 10] = [
+    "..##....#####....####...######..######...####...#....#.....###..#....#..#.......#....#..#####...#####...#....#..######",
+    ".#..#...#....#..#....#..#.......#.......#....#..#....#......#...#...#...#.......##...#..#....#..#....#..#....#.......#",
+    "#....#..#....#..#.......#.......#.......#.......#....#......#...#..#....#.......##...#..#....#..#....#...#..#........#",
+    "#....#..#....#..#.......#.......#.......#.......#....#......#...#.#.....#.......#.#..#..#....#..#....#...#..#.......#.",
+    "#....#..#####...#.......#####...#####...#.......######......#...##......#.......#.#..#..#####...#####.....##.......#..",
+    "######..#....#..#.......#.......#.......#..###..#....#......#...##......#.......#..#.#..#.......#..#......##......#...",
+    "#....#..#....#..#.......#.......#.......#....#..#....#......#...#.#.....#.......#..#.#..#.......#...#....#..#....#....",
+    "#....#..#....#..#.......#.......#.......#....#..#....#..#...#...#..#....#.......#...##..#.......#...#....#..#...#.....",
+    "#....#..#....#..#....#..#.......#.......#...##..#....#..#...#...#...#...#.......#...##..#.......#....#..#....#..#.....",
+    "#....#..#####....####...######..#........###.#..#....#...###....#....#..######..#....#..#.......#....#..#....#..######",
+];
+
+struct Glyph {
+    letter: char,
+    width: u32,
+    /// Pixels of the letter without surrounding empty columns, row by row
+    pixels: Vec<bool>,
+}
+
+struct Font {
+    height: u32,
+    glyphs: Vec<Glyph>,
+}
+
+impl Font {
+    fn parse(letters: &str, rows: &[&str]) -> Font {
+        let width = rows[0].len() as u32;
+        let height = rows.len() as u32;
+        let pixels = rows
+            .iter()
+            .flat_map(|row| row.chars().map(|c| c == '#'))
+            .collect();
+        let picture = Field::from(pixels, width, height);
+
+        let mut glyphs = Vec::new();
+        let mut letters = letters.chars();
+        let mut x = 0;
+        while x < width {
+            if column_is_empty(&picture, x) {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            while x < width && !column_is_empty(&picture, x) {
+                x += 1;
+            }
+            glyphs.push(Glyph {
+                letter: letters.next().expect("More glyphs than letters in font"),
+                width: x - start,
+                pixels: (0..height)
+                    .flat_map(|y| (start..x).map(move |x| (x, y)))
+                    .map(|(x, y)| *picture.get(x, y))
+                    .collect(),
+            });
+        }
+        // Wider glyphs first, so a letter that touches its neighbour is still recognized
+        glyphs.sort_by_key(|glyph| std::cmp::Reverse(glyph.width));
+        Font { height, glyphs }
+    }
+}
+
+fn column_is_empty(picture: &Field<bool>, x: u32) -> bool {
+    picture.column(x).all(|pixel| !pixel)
+}
+
+fn row_is_empty(picture: &Field<bool>, y: u32) -> bool {
+    picture.row(y).iter().all(|pixel| !pixel)
+}
+
+/// Reads the letters shown in the picture.
+///
+/// Returns `None` if the picture contains no letters or any glyph is unknown.
+pub fn recognize(picture: &Field<bool>) -> Option<String> {
+    let top = (0..picture.height()).find(|&y| !row_is_empty(picture, y))?;
+    let bottom = (0..picture.height())
+        .rev()
+        .find(|&y| !row_is_empty(picture, y))?;
+    let font = vec![
+        Font::parse(SMALL_LETTERS, &SMALL_FONT),
+        Font::parse(LARGE_LETTERS, &LARGE_FONT),
+    ]
+    .into_iter()
+    .find(|font| font.height == bottom - top + 1)?;
+
+    let mut text = String::new();
+    let mut x = 0;
+    while x < picture.width() {
+        if column_is_empty(picture, x) {
+            x += 1;
+            continue;
+        }
+        let glyph = font.glyphs.iter().find(|glyph| {
+            x + glyph.width <= picture.width()
+                && (0..font.height)
+                    .flat_map(|y| (0..glyph.width).map(move |dx| (dx, y)))
+                    .all(|(dx, y)| {
+                        *picture.get(x + dx, top + y)
+                            == glyph.pixels[(y * glyph.width + dx) as usize]
+                    })
+        })?;
+        text.push(glyph.letter);
+        x += glyph.width;
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picture(rows: &[&str]) -> Field<bool> {
+        let pixels = rows
+            .iter()
+            .flat_map(|row| row.chars().map(|c| c == '#'))
+            .collect();
+        Field::from(pixels, rows[0].len() as u32, rows.len() as u32)
+    }
+
+    #[test]
+    fn recognize_small_letters() {
+        let rows = [
+            ".........................",
+            ".#..#.#...####..####.###.",
+            ".#..#.#...##..#....#.#..#",
+            ".####..#.#.###....#..#..#",
+            ".#..#...#..#..#..#...###.",
+            ".#..#...#..#..#.#....#...",
+            ".#..#...#..###..####.#...",
+        ];
+        assert_eq!(recognize(&picture(&rows)), Some("HYBZP".to_owned()));
+    }
+
+    #[test]
+    fn recognize_large_letters() {
+        let rows = [
+            "#....#.....###..",
+            "#....#......#...",
+            "#....#......#...",
+            "#....#......#...",
+            "######......#...",
+            "#....#......#...",
+            "#....#......#...",
+            "#....#..#...#...",
+            "#....#..#...#...",
+            "#....#...###....",
+        ];
+        assert_eq!(recognize(&picture(&rows)), Some("HJ".to_owned()));
+    }
+
+    #[test]
+    fn unknown_glyphs() {
+        assert_eq!(recognize(&picture(&["#.#", ".#.", "#.#"])), None);
+        assert_eq!(recognize(&picture(&["...", "..."])), None);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::errors::{ACResult, Error, ParseError};
+use nom::character::complete::digit1;
+use nom::combinator::{map, map_res};
+use nom::error::ErrorKind;
+use nom::IResult;
+use std::str::FromStr;
+
+/// Parses the complete input, so errors point to the line and column in the input.
+///
+/// Trailing whitespace is ignored, any other unparsed content is an error.
+pub fn parse_all<'a, O, F>(input: &'a str, parser: F) -> ACResult<O>
+where
+    F: Fn(&'a str) -> IResult<&'a str, O>,
+{
+    parse_complete(input, &parser, "end of input").map_err(Error::Syntax)
+}
+
+/// Parses every line on its own, errors use the line number in the input.
+pub fn parse_lines<'a, O, F>(lines: &'a [String], parser: F) -> ACResult<Vec<O>>
+where
+    F: Fn(&'a str) -> IResult<&'a str, O>,
+{
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            parse_complete(line, &parser, "end of line").map_err(|mut e| {
+                e.line += index;
+                Error::Syntax(e)
+            })
+        })
+        .collect()
+}
+
+/// Parses an unsigned decimal number, a value that doesn't fit into the type is an error.
+pub fn number<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(digit1, str::parse)(input)
+}
+
+/// Parses a sequence of decimal digits into their values.
+pub fn digits(input: &str) -> IResult<&str, Vec<u8>> {
+    map(digit1, |d: &str| d.bytes().map(|b| b - b'0').collect())(input)
+}
+
+fn parse_complete<'a, O, F>(input: &'a str, parser: &F, end: &str) -> Result<O, ParseError>
+where
+    F: Fn(&'a str) -> IResult<&'a str, O>,
+{
+    match parser(input) {
+        Ok((remaining, _)) if !remaining.trim().is_empty() => {
+            Err(ParseError::at(input, remaining.trim_start(), end))
+        }
+        Ok((_, output)) => Ok(output),
+        Err(nom::Err::Incomplete(_)) => {
+            let truncated = &input[input.trim_end().len()..];
+            Err(ParseError::at(input, truncated, "more input"))
+        }
+        Err(nom::Err::Error((remaining, kind))) | Err(nom::Err::Failure((remaining, kind))) => {
+            Err(ParseError::at(input, remaining, describe(kind)))
+        }
+    }
+}
+
+fn describe(kind: ErrorKind) -> String {
+    match kind {
+        ErrorKind::Tag => "literal text",
+        ErrorKind::Char => "a specific character",
+        ErrorKind::OneOf | ErrorKind::IsA => "one of the allowed characters",
+        ErrorKind::Digit => "a digit",
+        ErrorKind::Alpha => "a letter",
+        ErrorKind::AlphaNumeric => "a letter or digit",
+        ErrorKind::Space | ErrorKind::MultiSpace => "whitespace",
+        ErrorKind::CrLf => "a line break",
+        ErrorKind::Alt | ErrorKind::Switch => "one of the alternatives",
+        ErrorKind::TakeWhile1 | ErrorKind::TakeWhileMN | ErrorKind::TakeTill1 => {
+            "at least one matching character"
+        }
+        ErrorKind::MapRes | ErrorKind::MapOpt | ErrorKind::ParseTo | ErrorKind::Verify => {
+            "a valid value"
+        }
+        ErrorKind::Many1
+        | ErrorKind::Many1Count
+        | ErrorKind::ManyMN
+        | ErrorKind::SeparatedNonEmptyList => "at least one item",
+        ErrorKind::Eof => "end of input",
+        ErrorKind::Complete => "more input",
+        kind => return format!("{:?}", kind),
+    }
+    .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::{do_parse, map_res, named, tag, take_while1};
+
+    named!(number<&str, i32>, map_res!(take_while1!(|c: char| c.is_ascii_digit()), str::parse));
+
+    named!(pair<&str, (i32, i32)>,
+      do_parse!(
+        tag!("<") >>
+        x: number >>
+        tag!(",") >>
+        y: number >>
+        tag!(">") >>
+        ((x, y))
+      )
+    );
+
+    #[test]
+    fn locate_errors_in_lines() {
+        let lines = vec!["<1,2>".to_owned(), "<3,4>".to_owned(), "<5;6>".to_owned()];
+        match parse_lines(&lines, pair) {
+            Err(Error::Syntax(e)) => {
+                assert_eq!((e.line, e.column), (3, 3));
+                assert_eq!(e.source_line, "<5;6>");
+                assert_eq!(e.expected, "literal text");
+            }
+            r => panic!("Unexpected result {:?}", r),
+        }
+        assert_eq!(parse_lines(&lines[..2], pair), Ok(vec![(1, 2), (3, 4)]));
+    }
+
+    #[test]
+    fn truncated_and_trailing_input() {
+        match parse_all("<1,2", pair) {
+            Err(Error::Syntax(e)) => assert_eq!(
+                (e.line, e.column, e.expected.as_str()),
+                (1, 5, "more input")
+            ),
+            r => panic!("Unexpected result {:?}", r),
+        }
+        match parse_all("<1,2>\nx\n", pair) {
+            Err(Error::Syntax(e)) => assert_eq!(
+                (e.line, e.column, e.expected.as_str()),
+                (2, 1, "end of input")
+            ),
+            r => panic!("Unexpected result {:?}", r),
+        }
+        assert_eq!(parse_all("<1,2>\n", pair), Ok((1, 2)));
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_all("42", super::number::<u8>), Ok(42));
+        match parse_all("300", super::number::<u8>) {
+            Err(Error::Syntax(e)) => assert_eq!(e.expected, "a valid value"),
+            r => panic!("Unexpected result {:?}", r),
+        }
+        assert!(parse_all("", super::number::<u32>).is_err());
+        assert_eq!(parse_all("0729", digits), Ok(vec![0, 7, 2, 9]));
+    }
+}
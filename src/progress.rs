@@ -0,0 +1,210 @@
+use crate::errors::{ACResult, Context, Error};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Solvers report their progress every this many steps.
+const PROGRESS_INTERVAL: u64 = 100_000;
+
+/// Solvers may recurse deeply, so they get more stack than the default for new threads.
+const SOLVER_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Interval for printing the progress in verbose mode.
+const PRINT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// State of one run, shared by the runner and the thread of its solver.
+#[derive(Default)]
+struct Run {
+    cancelled: AtomicBool,
+    /// Latest progress message
+    progress: Mutex<Option<String>>,
+}
+
+impl Run {
+    fn progress(&self) -> Option<String> {
+        self.progress.lock().unwrap().clone()
+    }
+}
+
+thread_local! {
+    /// The run of the solver on this thread, `None` outside of a runner.
+    static RUN: RefCell<Option<Arc<Run>>> = const { RefCell::new(None) };
+}
+
+/// Reports how far a long running solver got, e.g. the number of simulated steps.
+///
+/// The message is shown in verbose mode and when the solver times out.
+pub fn report(message: String) {
+    RUN.with(|run| {
+        if let Some(run) = &*run.borrow() {
+            *run.progress.lock().unwrap() = Some(message);
+        }
+    })
+}
+
+/// Fails if the run was cancelled after a timeout, so the solver stops instead of wasting time.
+pub fn check_cancelled() -> ACResult<()> {
+    let cancelled = RUN.with(|run| {
+        run.borrow()
+            .as_ref()
+            .is_some_and(|run| run.cancelled.load(Ordering::SeqCst))
+    });
+    if cancelled {
+        Err(Error::new_str("Solver was cancelled"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Reports the progress every few steps of a loop and checks whether the run was cancelled.
+pub fn step<F: FnOnce() -> String>(step: u64, message: F) -> ACResult<()> {
+    step_every(step, PROGRESS_INTERVAL, message)
+}
+
+/// Like `step`, for loops with fewer but slower steps.
+pub fn step_every<F: FnOnce() -> String>(step: u64, interval: u64, message: F) -> ACResult<()> {
+    if step.is_multiple_of(interval) {
+        report(message());
+        check_cancelled()?;
+    }
+    Ok(())
+}
+
+/// Runs solvers with an optional timeout, printing their progress in verbose mode.
+pub struct Runner {
+    pub timeout: Option<Duration>,
+    pub verbose: bool,
+}
+
+impl Runner {
+    /// Runs the solver in its own thread and fails if it doesn't finish in time or panics.
+    ///
+    /// Every run has its own progress and cancellation, so runners can be used in parallel. Threads
+    /// can't be killed, so a solver that times out keeps running in the background until its next
+    /// `step` or `check_cancelled` fails. A solver that never checks runs until it finishes or the
+    /// process exits.
+    pub fn run<T, F>(&self, solver: F) -> ACResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> ACResult<T> + Send + 'static,
+    {
+        let run = Arc::new(Run::default());
+        let solver_run = Arc::clone(&run);
+        let (sender, receiver) = mpsc::channel();
+        let handle = std::thread::Builder::new()
+            .name("solver".to_owned())
+            .stack_size(SOLVER_STACK_SIZE)
+            .spawn(move || {
+                RUN.with(|r| *r.borrow_mut() = Some(solver_run));
+                // The receiver is gone after a timeout
+                let _ = sender.send(solver());
+            })
+            .context("Failed to start solver")?;
+
+        let start = Instant::now();
+        let mut printed_progress = None;
+        loop {
+            let wait = match self.timeout {
+                Some(timeout) => match timeout.checked_sub(start.elapsed()) {
+                    Some(remaining) => std::cmp::min(remaining, PRINT_INTERVAL),
+                    None => {
+                        run.cancelled.store(true, Ordering::SeqCst);
+                        return Err(Error::Timeout(match run.progress() {
+                            Some(progress) => format!(
+                                "Solver timed out after {:?}, last progress: {}",
+                                timeout, progress
+                            ),
+                            None => format!("Solver timed out after {:?}", timeout),
+                        }));
+                    }
+                },
+                None => PRINT_INTERVAL,
+            };
+            match receiver.recv_timeout(wait) {
+                Ok(result) => return result,
+                Err(RecvTimeoutError::Timeout) => {
+                    let progress = run.progress();
+                    if self.verbose && progress.is_some() && progress != printed_progress {
+                        eprintln!("Progress: {}", progress.as_deref().unwrap_or_default());
+                        printed_progress = progress;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    let panic = handle.join().err();
+                    let message = panic
+                        .as_ref()
+                        .and_then(|p| {
+                            p.downcast_ref::<&str>()
+                                .copied()
+                                .or_else(|| p.downcast_ref::<String>().map(String::as_str))
+                        })
+                        .unwrap_or("unknown cause");
+                    return Err(Error::new(format!("Solver panicked: {}", message)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_and_panic() {
+        let runner = Runner {
+            timeout: Some(Duration::from_secs(30)),
+            verbose: false,
+        };
+        assert_eq!(runner.run(|| Ok(42)), Ok(42));
+
+        let result: ACResult<()> = runner.run(|| panic!("Not found"));
+        assert_eq!(result, Err(Error::new_str("Solver panicked: Not found")));
+    }
+
+    #[test]
+    fn timeout_cancels_only_its_own_run() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let other_stop = Arc::clone(&stop);
+        let other = std::thread::spawn(move || {
+            let runner = Runner {
+                timeout: Some(Duration::from_secs(30)),
+                verbose: false,
+            };
+            runner.run(move || {
+                let mut i = 0;
+                while !other_stop.load(Ordering::SeqCst) {
+                    i += 1;
+                    step(i, || format!("other step {}", i))?;
+                }
+                Ok(())
+            })
+        });
+
+        let runner = Runner {
+            timeout: Some(Duration::from_millis(50)),
+            verbose: false,
+        };
+        let (sender, receiver) = mpsc::channel();
+        let result: ACResult<()> = runner.run(move || {
+            for i in 0.. {
+                if let Err(e) = step(i, || format!("step {}", i)) {
+                    sender.send(i).unwrap();
+                    return Err(e);
+                }
+            }
+            Ok(())
+        });
+        let e = result.unwrap_err();
+        assert!(matches!(e, Error::Timeout(_)), "{:?}", e);
+        let message = e.to_string();
+        assert!(message.starts_with("Solver timed out"), "{}", message);
+        assert!(message.contains("last progress: step"), "{}", message);
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_ok());
+
+        stop.store(true, Ordering::SeqCst);
+        assert_eq!(other.join().unwrap(), Ok(()));
+    }
+}
@@ -0,0 +1,233 @@
+use crate::utils::{Field, Neighbourhood};
+
+/// Positions reachable from the start through passable cells, in reading order.
+///
+/// The start is only included if it's passable itself, a start outside of the field gives no
+/// positions.
+pub fn flood_fill<T, F: Fn(&T) -> bool>(
+    field: &Field<T>,
+    start: (u32, u32),
+    neighbourhood: Neighbourhood,
+    passable: F,
+) -> Vec<(u32, u32)> {
+    let mut filled = Field::new(field.width(), field.height());
+    let mut positions = Vec::new();
+    let mut next = Vec::new();
+    if field.get_opt(start.0, start.1).is_some_and(&passable) {
+        *filled.get_mut(start.0, start.1) = true;
+        next.push(start);
+    }
+    while let Some((x, y)) = next.pop() {
+        positions.push((x, y));
+        for (nx, ny) in field.neighbours(x, y, neighbourhood) {
+            if !*filled.get(nx, ny) && passable(field.get(nx, ny)) {
+                *filled.get_mut(nx, ny) = true;
+                next.push((nx, ny));
+            }
+        }
+    }
+    positions.sort_by_key(|&(x, y)| (y, x));
+    positions
+}
+
+/// A connected region of cells with the same key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region<K> {
+    pub key: K,
+    /// Number of cells
+    pub size: usize,
+    /// Whether a cell is at the edge of the field, e.g. because the region may continue outside
+    pub touches_border: bool,
+    /// First cell in reading order
+    pub start: (u32, u32),
+}
+
+/// Connected component labelling of a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Components<K> {
+    /// Index of the region of every cell, `None` for cells without key
+    pub labels: Field<Option<usize>>,
+    /// Regions in reading order of their first cell
+    pub regions: Vec<Region<K>>,
+}
+
+/// Labels the connected regions of neighbouring cells with the same key, cells without a key are
+/// left out.
+pub fn components<T, K, F>(field: &Field<T>, neighbourhood: Neighbourhood, key: F) -> Components<K>
+where
+    K: PartialEq,
+    F: Fn(&T) -> Option<K>,
+{
+    let mut labels: Field<Option<usize>> = Field::new(field.width(), field.height());
+    let mut regions = Vec::new();
+    for (x, y) in field.positions() {
+        if labels.get(x, y).is_some() {
+            continue;
+        }
+        let region_key = match key(field.get(x, y)) {
+            Some(region_key) => region_key,
+            None => continue,
+        };
+        let label = regions.len();
+        let mut region = Region {
+            key: region_key,
+            size: 0,
+            touches_border: false,
+            start: (x, y),
+        };
+        *labels.get_mut(x, y) = Some(label);
+        let mut next = vec![(x, y)];
+        while let Some((x, y)) = next.pop() {
+            region.size += 1;
+            region.touches_border |= field.is_border(x, y);
+            for (nx, ny) in field.neighbours(x, y, neighbourhood) {
+                if labels.get(nx, ny).is_none()
+                    && key(field.get(nx, ny)).as_ref() == Some(&region.key)
+                {
+                    *labels.get_mut(nx, ny) = Some(label);
+                    next.push((nx, ny));
+                }
+            }
+        }
+        regions.push(region);
+    }
+    Components { labels, regions }
+}
+
+/// Disjoint sets of the elements `0..len`, e.g. indices of points that are merged when they are
+/// close to each other.
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    sets: usize,
+}
+
+impl UnionFind {
+    /// Every element starts in its own set.
+    pub fn new(len: usize) -> Self {
+        UnionFind {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+            sets: len,
+        }
+    }
+
+    /// The representative element of the set containing the element.
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        // Point the whole way directly to the root to keep later lookups short
+        let mut current = element;
+        while self.parents[current] != root {
+            let parent = self.parents[current];
+            self.parents[current] = root;
+            current = parent;
+        }
+        root
+    }
+
+    /// Merges the sets of both elements, returns false if they were already in the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let (large, small) = if self.sizes[a] >= self.sizes[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parents[small] = large;
+        self.sizes[large] += self.sizes[small];
+        self.sets -= 1;
+        true
+    }
+
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Number of elements in the set of the element.
+    pub fn set_size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.sizes[root]
+    }
+
+    /// Number of disjoint sets.
+    pub fn sets(&self) -> usize {
+        self.sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> Field<char> {
+        Field::parse("aa.b\n.a.b\nbb.b\n", Ok).unwrap()
+    }
+
+    #[test]
+    fn flood_fill_with_passability() {
+        let field = map();
+        let open = |c: &char| *c != '.';
+        assert_eq!(
+            flood_fill(&field, (0, 0), Neighbourhood::VonNeumann, open),
+            vec![(0, 0), (1, 0), (1, 1), (0, 2), (1, 2)]
+        );
+        assert_eq!(
+            flood_fill(&field, (3, 0), Neighbourhood::VonNeumann, open).len(),
+            3
+        );
+        assert!(flood_fill(&field, (2, 0), Neighbourhood::Moore, open).is_empty());
+        assert!(flood_fill(&field, (4, 0), Neighbourhood::Moore, open).is_empty());
+    }
+
+    #[test]
+    fn labelled_components() {
+        let field = Field::parse("aa..\n.a..\n...b\n", Ok).unwrap();
+        let labelled = components(&field, Neighbourhood::VonNeumann, |c| {
+            if *c == '.' {
+                None
+            } else {
+                Some(*c)
+            }
+        });
+        assert_eq!(labelled.regions.len(), 2);
+        assert_eq!(
+            labelled.regions[0],
+            Region {
+                key: 'a',
+                size: 3,
+                touches_border: true,
+                start: (0, 0)
+            }
+        );
+        assert_eq!(labelled.regions[1].size, 1);
+        assert_eq!(labelled.labels.get(1, 1), &Some(0));
+        assert_eq!(labelled.labels.get(2, 1), &None);
+
+        let inner = Field::parse(".....\n.xx..\n..x..\n.....\n", Ok).unwrap();
+        let labelled = components(&inner, Neighbourhood::Moore, |c| Some(*c));
+        assert_eq!(labelled.regions.len(), 2);
+        assert!(labelled.regions[0].touches_border);
+        assert!(!labelled.regions[1].touches_border);
+        assert_eq!(labelled.regions[1].size, 3);
+    }
+
+    #[test]
+    fn union_find() {
+        let mut sets = UnionFind::new(5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 4));
+        assert!(sets.union(1, 4));
+        assert!(!sets.union(0, 3));
+        assert!(sets.same_set(0, 4));
+        assert!(!sets.same_set(0, 2));
+        assert_eq!(sets.set_size(3), 4);
+        assert_eq!(sets.sets(), 2);
+    }
+}
@@ -0,0 +1,124 @@
+use advent_of_code_solver::answer::Answer;
+use advent_of_code_solver::errors::{ACResult, Context, Error};
+use serde::Serialize;
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> ACResult<Format> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(Error::Config(format!("Unknown output format {}", s))),
+        }
+    }
+}
+
+/// Outcome of solving one puzzle level, printed as one object with `--format json`.
+#[derive(Serialize, Debug, Default)]
+pub struct Report {
+    pub year: i32,
+    pub day: u8,
+    pub level: u8,
+    /// Not set if an input file was given
+    pub profile: Option<String>,
+    /// Canonical form of the answer
+    pub answer: Option<String>,
+    #[serde(skip)]
+    pub value: Option<Answer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub elapsed_seconds: f64,
+    pub recorded_answer: Option<String>,
+    /// Not set if there is no recorded answer to compare with
+    pub matches_recorded_answer: Option<bool>,
+    pub warnings: Vec<String>,
+}
+
+impl Report {
+    pub fn new(year: i32, day: u8, level: u8, profile: Option<&str>) -> Self {
+        Report {
+            year,
+            day,
+            level,
+            profile: profile.map(str::to_owned),
+            ..Report::default()
+        }
+    }
+
+    pub fn set_result(&mut self, result: &ACResult<Answer>, elapsed: Duration) {
+        match result {
+            Ok(answer) => {
+                self.answer = Some(answer.canonical());
+                self.value = Some(answer.clone());
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        self.elapsed_seconds = elapsed.as_secs_f64();
+    }
+
+    /// Compares the answer with the answer that was recorded as correct.
+    pub fn check_answer(&mut self, recorded_answer: Option<String>) {
+        if let (Some(answer), Some(recorded)) = (&self.value, &recorded_answer) {
+            let matches = answer.matches(recorded);
+            if !matches {
+                self.warnings
+                    .push(format!("result differs from recorded answer {}", recorded));
+            }
+            self.matches_recorded_answer = Some(matches);
+        }
+        self.recorded_answer = recorded_answer;
+    }
+
+    /// Prints the answer to stdout and the warnings to stderr.
+    pub fn print(&self, format: Format) -> ACResult<()> {
+        match format {
+            Format::Text => {
+                if let Some(answer) = &self.value {
+                    println!("{}", answer);
+                }
+                for warning in &self.warnings {
+                    eprintln!("Warning: {}", warning);
+                }
+            }
+            Format::Json => println!("{}", to_json(self)?),
+        }
+        Ok(())
+    }
+}
+
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> ACResult<String> {
+    serde_json::to_string(value)
+        .map_err(std::io::Error::from)
+        .context("Failed to write json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_report() {
+        let mut report = Report::new(2019, 8, 2, Some("alice"));
+        let picture =
+            advent_of_code_solver::utils::Field::from(vec![true, false, false, true], 2, 2);
+        report.set_result(&Ok(Answer::from(picture)), Duration::from_millis(1500));
+        report.check_answer(Some("ABCD".to_owned()));
+        assert_eq!(
+            to_json(&report),
+            Ok(concat!(
+                r##"{"year":2019,"day":8,"level":2,"profile":"alice","answer":"#.\n.#","##,
+                r#""elapsed_seconds":1.5,"recorded_answer":"ABCD","matches_recorded_answer":false,"#,
+                r#""warnings":["result differs from recorded answer ABCD"]}"#
+            )
+            .to_owned())
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use advent_of_code_solver::errors::{ACResult, Context, Error};
+use std::path::{Path, PathBuf};
+
+/// Creates the module for a new day from a template and registers it in the year module.
+///
+/// If the year doesn't exist yet, the year module is created and registered in `lib.rs`.
+/// Returns the list of created or modified files.
+pub fn create_day(src_dir: &Path, year: i32, day: u8) -> ACResult<Vec<PathBuf>> {
+    if !(1..=25).contains(&day) {
+        return Err(Error::Config(format!(
+            "There is no day {}, puzzles are numbered 1 to 25",
+            day
+        )));
+    }
+    let year_module = format!("advent{}", year);
+    let day_module = format!("day{:02}", day);
+    let year_file = src_dir.join(format!("{}.rs", year_module));
+    let day_file = src_dir
+        .join(&year_module)
+        .join(format!("{}.rs", day_module));
+
+    if day_file.exists() {
+        return Err(Error::Config(format!(
+            "Module for {} day {} already exists: {:?}",
+            year, day, day_file
+        )));
+    }
+
+    let mut changed = Vec::new();
+    if year_file.exists() {
+        let content = read_file(&year_file)?;
+        let content = register_module(&content, &day_module)?;
+        let content = register_match_arm(
+            &content,
+            day as i32,
+            &format!("{}::get_result(data, level)", day_module),
+        )?;
+        write_file(&year_file, &content)?;
+    } else {
+        let lib_file = src_dir.join("lib.rs");
+        let content = read_file(&lib_file)?;
+        let content = register_module(&content, &year_module)?;
+        let content = register_match_arm(
+            &content,
+            year,
+            &format!("{}::get_result(data, day, level)", year_module),
+        )?;
+        write_file(&lib_file, &content)?;
+        changed.push(lib_file);
+
+        write_file(&year_file, &year_template(day))?;
+    }
+    changed.push(year_file);
+
+    std::fs::create_dir_all(src_dir.join(&year_module))
+        .context("Failed to create year directory")?;
+    write_file(&day_file, DAY_TEMPLATE)?;
+    changed.push(day_file);
+
+    Ok(changed)
+}
+
+fn read_file(path: &Path) -> ACResult<String> {
+    std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))
+}
+
+fn write_file(path: &Path, content: &str) -> ACResult<()> {
+    std::fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Adds a `mod` declaration, keeping the existing declarations sorted.
+fn register_module(content: &str, module: &str) -> ACResult<String> {
+    let declaration = format!("mod {};", module);
+    let lines: Vec<&str> = content.lines().collect();
+    let mod_lines: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| l.starts_with("mod ") || l.starts_with("pub mod "))
+        .map(|(i, _)| i)
+        .collect();
+    if mod_lines.iter().any(|i| lines[*i] == declaration) {
+        return Err(Error::Config(format!(
+            "Module {} already registered",
+            module
+        )));
+    }
+    let last = *mod_lines
+        .last()
+        .ok_or_else(|| Error::Parse("No module declarations found".to_owned()))?;
+    let index = mod_lines
+        .iter()
+        .find(|i| lines[**i].trim_start_matches("pub ") > declaration.as_str())
+        .cloned()
+        .unwrap_or(last + 1);
+
+    Ok(insert_line(&lines, index, &declaration))
+}
+
+/// Adds a `number => call,` arm to the match statement with the catch-all error arm, keeping
+/// the numeric arms sorted.
+fn register_match_arm(content: &str, number: i32, call: &str) -> ACResult<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let fallback = lines
+        .iter()
+        .position(|l| l.trim_start().starts_with("_ => Err("))
+        .ok_or_else(|| Error::Parse("No match statement found".to_owned()))?;
+    let indent = &lines[fallback][..lines[fallback].len() - lines[fallback].trim_start().len()];
+
+    let mut index = fallback;
+    for i in (0..fallback).rev() {
+        let arm_number = match lines[i]
+            .trim_start()
+            .split(" => ")
+            .next()
+            .and_then(|n| n.parse::<i32>().ok())
+        {
+            Some(n) => n,
+            None => break,
+        };
+        if arm_number == number {
+            return Err(Error::Config(format!(
+                "Match arm {} already registered",
+                number
+            )));
+        }
+        if arm_number < number {
+            break;
+        }
+        index = i;
+    }
+
+    Ok(insert_line(
+        &lines,
+        index,
+        &format!("{}{} => {},", indent, number, call),
+    ))
+}
+
+fn insert_line(lines: &[&str], index: usize, line: &str) -> String {
+    let mut result: Vec<&str> = lines.to_vec();
+    result.insert(index, line);
+    result.join("\n") + "\n"
+}
+
+fn year_template(day: u8) -> String {
+    format!(
+        "use crate::answer::Answer;
+use crate::errors::{{ACResult, Error}};
+use std::io::{{BufRead, Read}};
+
+mod day{day:02};
+
+pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<Answer> {{
+    match day {{
+        {day} => day{day:02}::get_result(data, level),
+        _ => Err(Error::Unimplemented(format!(\"Day {{}} not implemented\", day))),
+    }}
+}}
+",
+        day = day
+    )
+}
+
+const DAY_TEMPLATE: &str = "use crate::answer::Answer;
+use crate::errors::{ACResult, Error};
+use std::io::BufRead;
+
+pub fn get_result<T: BufRead>(data: T, level: u8) -> ACResult<Answer> {
+    match level {
+        1 => level_1(&crate::utils::read_lines(data)?).map(Answer::from),
+        2 => level_2(&crate::utils::read_lines(data)?).map(Answer::from),
+        _ => Err(Error::Unimplemented(format!(
+            \"Level {} not implemented\",
+            level
+        ))),
+    }
+}
+
+fn level_1(_lines: &[String]) -> ACResult<u64> {
+    Err(Error::Unimplemented(\"Level 1 not implemented\".to_owned()))
+}
+
+fn level_2(_lines: &[String]) -> ACResult<u64> {
+    Err(Error::Unimplemented(\"Level 2 not implemented\".to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Example input from the puzzle description
+    const EXAMPLE: &str = \"\";
+
+    /// Expected answers of both levels for the example
+    const EXPECTED: [u64; 2] = [0, 0];
+
+    fn example_lines() -> Vec<String> {
+        EXAMPLE.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    #[ignore = \"fill in the example and its expected answer, then remove this attribute\"]
+    fn run_level_1_examples() {
+        assert_eq!(level_1(&example_lines()), Ok(EXPECTED[0]));
+    }
+
+    #[test]
+    #[ignore = \"fill in the example and its expected answer, then remove this attribute\"]
+    fn run_level_2_examples() {
+        assert_eq!(level_2(&example_lines()), Ok(EXPECTED[1]));
+    }
+}
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YEAR: &str = "use std::io::{BufRead, Read};
+
+mod day01;
+mod day03;
+
+pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<Answer> {
+    match day {
+        1 => day01::get_result(data, level),
+        3 => day03::get_result(data, level),
+        _ => Err(Error::Unimplemented(format!(\"Day {} not implemented\", day))),
+    }
+}
+";
+
+    #[test]
+    fn register_new_day() {
+        let content = register_module(YEAR, "day02").unwrap();
+        let content = register_match_arm(&content, 2, "day02::get_result(data, level)").unwrap();
+        assert_eq!(
+            content,
+            "use std::io::{BufRead, Read};
+
+mod day01;
+mod day02;
+mod day03;
+
+pub fn get_result<T: Read + BufRead>(data: T, day: u8, level: u8) -> ACResult<Answer> {
+    match day {
+        1 => day01::get_result(data, level),
+        2 => day02::get_result(data, level),
+        3 => day03::get_result(data, level),
+        _ => Err(Error::Unimplemented(format!(\"Day {} not implemented\", day))),
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn register_last_day() {
+        let content = register_module(YEAR, "day25").unwrap();
+        let content = register_match_arm(&content, 25, "day25::get_result(data, level)").unwrap();
+        assert!(content.contains("mod day03;\nmod day25;\n\n"));
+        assert!(content.contains(
+            "        3 => day03::get_result(data, level),
+        25 => day25::get_result(data, level),
+        _ => Err("
+        ));
+    }
+
+    #[test]
+    fn reject_invalid_day() {
+        let dir = std::env::temp_dir().join(format!("advent-scaffold-{}", std::process::id()));
+        for day in &[0, 26] {
+            assert!(matches!(
+                create_day(&dir, 2018, *day),
+                Err(Error::Config(_))
+            ));
+        }
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn register_existing_day() {
+        assert!(register_module(YEAR, "day03").is_err());
+        assert!(register_match_arm(YEAR, 1, "day01::get_result(data, level)").is_err());
+    }
+}
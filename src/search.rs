@@ -0,0 +1,237 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Result of a search: the distance of every reached node and how it was reached.
+#[derive(Debug, Clone)]
+pub struct Paths<N, C> {
+    /// Distance from the start and the previous node on a shortest path
+    visited: HashMap<N, (C, Option<N>)>,
+    goal: Option<N>,
+}
+
+impl<N: Eq + Hash + Clone, C: Copy> Paths<N, C> {
+    /// The goal the search stopped at, `None` if no goal was reached.
+    pub fn goal(&self) -> Option<&N> {
+        self.goal.as_ref()
+    }
+
+    /// Distance of the node from the start, `None` if it wasn't reached.
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.visited.get(node).map(|(distance, _)| *distance)
+    }
+
+    /// Distance of the goal the search stopped at.
+    pub fn goal_distance(&self) -> Option<C> {
+        self.distance(self.goal.as_ref()?)
+    }
+
+    /// All reached nodes with their distance, in no particular order.
+    ///
+    /// When the search stopped at a goal, this only contains the nodes reached until then.
+    pub fn distances(&self) -> impl Iterator<Item = (&N, C)> {
+        self.visited
+            .iter()
+            .map(|(node, (distance, _))| (node, *distance))
+    }
+
+    /// Nodes of a shortest path from the start to the node, both included.
+    pub fn path(&self, node: &N) -> Option<Vec<N>> {
+        let mut path = vec![node.clone()];
+        let mut previous = &self.visited.get(node)?.1;
+        while let Some(node) = previous {
+            path.push(node.clone());
+            previous = &self.visited[node].1;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth first search for graphs where every step has the same cost.
+///
+/// Stops at the first node for which `goal` returns true, use `|_| false` to get the distances to
+/// all reachable nodes. Nodes with the same distance are visited in the order they are found.
+pub fn bfs<N, F, I, G>(start: N, mut neighbours: F, mut goal: G) -> Paths<N, usize>
+where
+    N: Eq + Hash + Clone,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+    G: FnMut(&N) -> bool,
+{
+    let mut visited = HashMap::new();
+    visited.insert(start.clone(), (0, None));
+    let mut next = VecDeque::new();
+    next.push_back((start, 0));
+    while let Some((node, distance)) = next.pop_front() {
+        if goal(&node) {
+            return Paths {
+                visited,
+                goal: Some(node),
+            };
+        }
+        for neighbour in neighbours(&node) {
+            if let Entry::Vacant(entry) = visited.entry(neighbour.clone()) {
+                entry.insert((distance + 1, Some(node.clone())));
+                next.push_back((neighbour, distance + 1));
+            }
+        }
+    }
+    Paths {
+        visited,
+        goal: None,
+    }
+}
+
+/// Dijkstra's algorithm for graphs with non-negative step costs, `neighbours` returns the next
+/// nodes with the cost of getting there.
+///
+/// Stops at the first node for which `goal` returns true. Nodes with the same distance are visited
+/// in their order, e.g. a point type that orders by row first visits them in reading order.
+pub fn dijkstra<N, C, F, I, G>(start: N, neighbours: F, goal: G) -> Paths<N, C>
+where
+    N: Eq + Hash + Clone + Ord,
+    C: Copy + Ord + Default + Add<Output = C>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    G: FnMut(&N) -> bool,
+{
+    astar(start, neighbours, |_| C::default(), goal)
+}
+
+/// A* search, like `dijkstra` but guided by a heuristic that estimates the remaining cost to the
+/// nearest goal.
+///
+/// The heuristic must never overestimate, otherwise the found path may not be the shortest.
+pub fn astar<N, C, F, I, H, G>(
+    start: N,
+    mut neighbours: F,
+    mut heuristic: H,
+    mut goal: G,
+) -> Paths<N, C>
+where
+    N: Eq + Hash + Clone + Ord,
+    C: Copy + Ord + Default + Add<Output = C>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    H: FnMut(&N) -> C,
+    G: FnMut(&N) -> bool,
+{
+    let mut visited = HashMap::new();
+    let mut best = HashMap::new();
+    let mut next = BinaryHeap::new();
+    best.insert(start.clone(), C::default());
+    next.push(Reverse((heuristic(&start), start, C::default(), None)));
+    while let Some(Reverse((_, node, distance, previous))) = next.pop() {
+        if visited.contains_key(&node) {
+            continue;
+        }
+        visited.insert(node.clone(), (distance, previous));
+        if goal(&node) {
+            return Paths {
+                visited,
+                goal: Some(node),
+            };
+        }
+        for (neighbour, cost) in neighbours(&node) {
+            let neighbour_distance = distance + cost;
+            if visited.contains_key(&neighbour)
+                || best
+                    .get(&neighbour)
+                    .is_some_and(|best| *best <= neighbour_distance)
+            {
+                continue;
+            }
+            best.insert(neighbour.clone(), neighbour_distance);
+            next.push(Reverse((
+                neighbour_distance + heuristic(&neighbour),
+                neighbour,
+                neighbour_distance,
+                Some(node.clone()),
+            )));
+        }
+    }
+    Paths {
+        visited,
+        goal: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Open cells of a small maze, `(row, column)` so the order is the reading order.
+    fn maze() -> Vec<Vec<bool>> {
+        ["#######", "#.....#", "#.#.#.#", "#.....#", "#######"]
+            .iter()
+            .map(|row| row.chars().map(|c| c == '.').collect())
+            .collect()
+    }
+
+    fn open_neighbours(maze: &[Vec<bool>], (y, x): (usize, usize)) -> Vec<(usize, usize)> {
+        vec![(y - 1, x), (y, x - 1), (y, x + 1), (y + 1, x)]
+            .into_iter()
+            .filter(|(y, x)| maze[*y][*x])
+            .collect()
+    }
+
+    #[test]
+    fn bfs_distances_and_path() {
+        let maze = maze();
+        let paths = bfs((1, 1), |p| open_neighbours(&maze, *p), |_| false);
+        assert_eq!(paths.goal(), None);
+        assert_eq!(paths.distances().count(), 13);
+        assert_eq!(paths.distance(&(3, 5)), Some(6));
+        assert_eq!(paths.distance(&(2, 2)), None);
+        assert_eq!(paths.path(&(1, 3)), Some(vec![(1, 1), (1, 2), (1, 3)]));
+
+        let paths = bfs((1, 1), |p| open_neighbours(&maze, *p), |p| p.0 == 3);
+        assert_eq!(paths.goal(), Some(&(3, 1)));
+        assert_eq!(paths.goal_distance(), Some(2));
+    }
+
+    #[test]
+    fn dijkstra_prefers_cheap_steps_and_reading_order() {
+        let maze = maze();
+        let paths = dijkstra(
+            (3, 3),
+            |p| open_neighbours(&maze, *p).into_iter().map(|n| (n, 1)),
+            |p| *p == (3, 1) || *p == (3, 5) || *p == (1, 3),
+        );
+        // All goals are 2 steps away, the first in reading order wins
+        assert_eq!(paths.goal(), Some(&(1, 3)));
+        assert_eq!(paths.goal_distance(), Some(2));
+
+        let edges = [("a", "b", 5), ("a", "c", 1), ("c", "b", 1), ("b", "d", 1)];
+        let paths = dijkstra(
+            "a",
+            |node| {
+                edges
+                    .iter()
+                    .filter(|(from, _, _)| from == node)
+                    .map(|(_, to, cost)| (*to, *cost))
+                    .collect::<Vec<_>>()
+            },
+            |node| *node == "d",
+        );
+        assert_eq!(paths.goal_distance(), Some(3));
+        assert_eq!(paths.path(&"d"), Some(vec!["a", "c", "b", "d"]));
+    }
+
+    #[test]
+    fn astar_with_manhattan_distance() {
+        let maze = maze();
+        let target = (3, 5);
+        let paths = astar(
+            (1, 1),
+            |p| open_neighbours(&maze, *p).into_iter().map(|n| (n, 1)),
+            |&(y, x): &(usize, usize)| (target.0 - y) + (target.1 - x),
+            |p| *p == target,
+        );
+        assert_eq!(paths.goal_distance(), Some(6));
+        assert_eq!(paths.path(&target).map(|path| path.len()), Some(7));
+    }
+}
@@ -0,0 +1,178 @@
+use advent_of_code_solver::errors::{ACResult, Error};
+use chrono::{DateTime, FixedOffset, TimeZone};
+use std::io::Write;
+use std::time::Duration;
+
+/// Extra wait after the unlock time, in case the local clock is a bit ahead of the server.
+const UNLOCK_MARGIN: Duration = Duration::from_secs(2);
+/// Attempts to download the input after the unlock, and the delay before the first retry.
+pub const UNLOCK_ATTEMPTS: u32 = 5;
+pub const UNLOCK_RETRY_DELAY: Duration = Duration::from_secs(5);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Advent website is in TimeZone EST/UTC-5
+pub fn advent_timezone() -> FixedOffset {
+    let hour = 3600;
+    FixedOffset::west_opt(5 * hour).unwrap()
+}
+
+/// Puzzles are released at midnight EST.
+pub fn unlock_time(year: i32, day: u8) -> ACResult<DateTime<FixedOffset>> {
+    advent_timezone()
+        .with_ymd_and_hms(year, 12, u32::from(day), 0, 0, 0)
+        .single()
+        .ok_or_else(|| Error::Config(format!("Invalid puzzle date: {} day {}", year, day)))
+}
+
+/// Shows a countdown on stderr until the puzzle is released.
+pub fn wait_for_unlock(year: i32, day: u8) -> ACResult<()> {
+    let unlock = unlock_time(year, day)? + chrono::Duration::from_std(UNLOCK_MARGIN).unwrap();
+    let mut waited = false;
+    loop {
+        let remaining = unlock.signed_duration_since(chrono::Utc::now());
+        let remaining = match remaining.to_std() {
+            Ok(remaining) if remaining > Duration::from_secs(0) => remaining,
+            _ => break,
+        };
+        waited = true;
+        eprint!(
+            "\rPuzzle {} day {} unlocks in {}  ",
+            year,
+            day,
+            format_duration(remaining)
+        );
+        std::io::stderr().flush().ok();
+
+        let fraction = Duration::from_nanos(u64::from(remaining.subsec_nanos()));
+        std::thread::sleep(if fraction > Duration::from_secs(0) {
+            fraction
+        } else {
+            Duration::from_secs(1)
+        });
+    }
+    if waited {
+        eprintln!("\rPuzzle {} day {} is unlocked.{:20}", year, day, "");
+    }
+    Ok(())
+}
+
+/// Retries the operation with an exponential backoff while it fails with a retryable error, as
+/// the input may not be available the very second the puzzle unlocks.
+pub fn retry_with_backoff<T>(
+    max_attempts: u32,
+    initial_delay: Duration,
+    verbose: bool,
+    retryable: impl Fn(&Error) -> bool,
+    mut operation: impl FnMut() -> ACResult<T>,
+) -> ACResult<T> {
+    let mut delay = initial_delay;
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Err(e) if attempt < max_attempts && retryable(&e) => {
+                if verbose {
+                    eprintln!("Attempt {} failed: {}, retrying in {:?}", attempt, e, delay);
+                }
+                std::thread::sleep(delay);
+                delay = std::cmp::min(delay * 2, MAX_RETRY_DELAY);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+    let days = seconds / (24 * 3600);
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    if days > 0 {
+        format!("{}d {}", days, time)
+    } else {
+        time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlock_at_midnight_est() {
+        assert_eq!(
+            unlock_time(2019, 1).map(|t| t.timestamp()),
+            Ok(chrono::Utc
+                .with_ymd_and_hms(2019, 12, 1, 5, 0, 0)
+                .unwrap()
+                .timestamp())
+        );
+        assert!(unlock_time(2019, 32).is_err());
+    }
+
+    #[test]
+    fn countdown_format() {
+        assert_eq!(format_duration(Duration::from_millis(59_500)), "00:01:00");
+        assert_eq!(format_duration(Duration::from_secs(3_725)), "01:02:05");
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 24 * 3600 + 7)),
+            "2d 00:00:07"
+        );
+    }
+
+    #[test]
+    fn retry_until_success() {
+        let mut attempts = 0;
+        let result = retry_with_backoff(
+            3,
+            Duration::from_millis(1),
+            false,
+            |_| true,
+            || {
+                attempts += 1;
+                if attempts < 2 {
+                    Err(Error::new_str("Not yet"))
+                } else {
+                    Ok(attempts)
+                }
+            },
+        );
+        assert_eq!(result, Ok(2));
+    }
+
+    #[test]
+    fn retry_only_retryable_errors() {
+        let mut attempts = 0;
+        let result: ACResult<()> = retry_with_backoff(
+            3,
+            Duration::from_millis(1),
+            false,
+            |e| matches!(e, Error::Solver(_)),
+            || {
+                attempts += 1;
+                Err(Error::Config("Invalid".to_owned()))
+            },
+        );
+        assert_eq!(
+            (result, attempts),
+            (Err(Error::Config("Invalid".to_owned())), 1)
+        );
+
+        let mut attempts = 0;
+        let result: ACResult<()> = retry_with_backoff(
+            3,
+            Duration::from_millis(1),
+            false,
+            |_| true,
+            || {
+                attempts += 1;
+                Err(Error::new_str("Not yet"))
+            },
+        );
+        assert_eq!((result, attempts), (Err(Error::new_str("Not yet")), 3));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::errors::{ACResult, Error};
+use crate::errors::{ACResult, Context, Error, ParseError};
+use crate::geometry::Vec2;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
 use std::io::{BufRead, Read};
 
 pub fn read_line<T: BufRead>(data: T) -> ACResult<String> {
@@ -6,7 +9,7 @@ pub fn read_line<T: BufRead>(data: T) -> ACResult<String> {
         .lines()
         .take(1)
         .collect::<Result<String, _>>()
-        .map_err(|_| Error::new_str("Failed to read lines"))?;
+        .context("Failed to read lines")?;
     Ok(line)
 }
 
@@ -14,17 +17,52 @@ pub fn read_lines<T: BufRead>(data: T) -> ACResult<Vec<String>> {
     let lines = data
         .lines()
         .collect::<Result<Vec<String>, _>>()
-        .map_err(|_| Error::new_str("Failed to read lines"))?;
+        .context("Failed to read lines")?;
     Ok(lines)
 }
 
 pub fn read_all<T: Read>(mut data: T) -> ACResult<String> {
     let mut contents = String::new();
     data.read_to_string(&mut contents)
-        .map_err(|_| Error::new_str("Failed to read stdin"))?;
+        .context("Failed to read input")?;
     Ok(contents)
 }
 
+/// Converts CRLF line endings and makes sure the input ends with exactly one newline.
+pub fn normalize_input(input: &str) -> String {
+    let mut normalized = input.replace("\r\n", "\n");
+    normalized.truncate(normalized.trim_end_matches('\n').len());
+    normalized.push('\n');
+    normalized
+}
+
+/// Offsets of the direct neighbours in reading order.
+const NEIGHBOURS_4: [(i64, i64); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Offsets of the direct and diagonal neighbours in reading order.
+const NEIGHBOURS_8: [(i64, i64); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Which cells around a cell of a field are its neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// The 8 direct and diagonal neighbours
+    Moore,
+    /// The 4 direct neighbours
+    VonNeumann,
+}
+
+/// Two dimensional grid, stored row by row. `x` is the column and `y` the row, starting at the
+/// top left.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Field<T> {
     field: Vec<T>,
     width: u32,
@@ -52,6 +90,62 @@ impl<T> Field<T> {
         }
     }
 
+    /// Creates the field with the value of each position.
+    pub fn from_fn<F: FnMut(u32, u32) -> T>(width: u32, height: u32, mut value: F) -> Self {
+        let field = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| value(x, y))
+            .collect();
+        Field::from(field, width, height)
+    }
+
+    /// Parses a grid of characters, one row per line. All lines must have the same length.
+    ///
+    /// Errors point to the offending cell, the message of a parse error of the cell function
+    /// describes what was expected there.
+    pub fn parse<F: FnMut(char) -> ACResult<T>>(input: &str, mut cell: F) -> ACResult<Self> {
+        let mut field = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        let mut rest = input;
+        while !rest.is_empty() {
+            let (line, next) = match rest.find('\n') {
+                Some(end) => (&rest[..end], &rest[end + 1..]),
+                None => (rest, ""),
+            };
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let len = field.len();
+            for (i, c) in line.char_indices() {
+                let value = cell(c).map_err(|e| {
+                    let expected = match e {
+                        Error::Parse(message) => message,
+                        e => e.to_string(),
+                    };
+                    ParseError::at(input, &rest[i..], expected)
+                })?;
+                field.push(value);
+            }
+            let line_width = (field.len() - len) as u32;
+            let width = *width.get_or_insert(line_width);
+            if line_width != width {
+                let end = line
+                    .char_indices()
+                    .nth(width as usize)
+                    .map_or(line.len(), |(i, _)| i);
+                let expected = if line_width < width {
+                    "more cells"
+                } else {
+                    "end of line"
+                };
+                let expected = format!("{}, rows have {} cells", expected, width);
+                return Err(ParseError::at(input, &rest[end..], expected).into());
+            }
+            height += 1;
+            rest = next;
+        }
+        Ok(Field::from(field, width.unwrap_or_default(), height))
+    }
+
     pub fn get(&self, x: u32, y: u32) -> &T {
         &self.field[(y * self.width + x) as usize]
     }
@@ -60,6 +154,40 @@ impl<T> Field<T> {
         &mut self.field[(y * self.width + x) as usize]
     }
 
+    /// Like `get`, but `None` for positions outside of the field.
+    pub fn get_opt(&self, x: u32, y: u32) -> Option<&T> {
+        if x < self.width && y < self.height {
+            Some(self.get(x, y))
+        } else {
+            None
+        }
+    }
+
+    /// Like `get_mut`, but `None` for positions outside of the field.
+    pub fn get_opt_mut(&mut self, x: u32, y: u32) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            Some(self.get_mut(x, y))
+        } else {
+            None
+        }
+    }
+
+    /// Like `get_opt` for a signed position, e.g. the result of adding an offset.
+    pub fn at(&self, position: Vec2) -> Option<&T> {
+        let (x, y) = self.position(position)?;
+        Some(self.get(x, y))
+    }
+
+    /// Like `get_opt_mut` for a signed position, e.g. the result of adding an offset.
+    pub fn at_mut(&mut self, position: Vec2) -> Option<&mut T> {
+        let (x, y) = self.position(position)?;
+        Some(self.get_mut(x, y))
+    }
+
+    fn position(&self, position: Vec2) -> Option<(u32, u32)> {
+        offset(self.width, self.height, (0, 0), (position.x, position.y))
+    }
+
     pub fn width(&self) -> u32 {
         self.width
     }
@@ -67,6 +195,339 @@ impl<T> Field<T> {
     pub fn height(&self) -> u32 {
         self.height
     }
+
+    /// The position moved by the offset, `None` if it leaves the field.
+    pub fn offset(&self, x: u32, y: u32, dx: i64, dy: i64) -> Option<(u32, u32)> {
+        offset(self.width, self.height, (x, y), (dx, dy))
+    }
+
+    /// Positions of the up to 4 direct neighbours inside the field, in reading order.
+    pub fn neighbours4(&self, x: u32, y: u32) -> impl Iterator<Item = (u32, u32)> {
+        let (width, height) = (self.width, self.height);
+        NEIGHBOURS_4
+            .iter()
+            .filter_map(move |delta| offset(width, height, (x, y), *delta))
+    }
+
+    /// Positions of the up to 8 direct and diagonal neighbours inside the field, in reading
+    /// order.
+    pub fn neighbours8(&self, x: u32, y: u32) -> impl Iterator<Item = (u32, u32)> {
+        let (width, height) = (self.width, self.height);
+        NEIGHBOURS_8
+            .iter()
+            .filter_map(move |delta| offset(width, height, (x, y), *delta))
+    }
+
+    /// Positions of the neighbours inside the field, in reading order.
+    pub fn neighbours(
+        &self,
+        x: u32,
+        y: u32,
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = (u32, u32)> {
+        let (width, height) = (self.width, self.height);
+        let deltas: &[(i64, i64)] = match neighbourhood {
+            Neighbourhood::Moore => &NEIGHBOURS_8,
+            Neighbourhood::VonNeumann => &NEIGHBOURS_4,
+        };
+        deltas
+            .iter()
+            .filter_map(move |delta| offset(width, height, (x, y), *delta))
+    }
+
+    /// Whether the position is in the first or last row or column.
+    pub fn is_border(&self, x: u32, y: u32) -> bool {
+        x == 0 || y == 0 || x + 1 == self.width || y + 1 == self.height
+    }
+
+    /// All positions in reading order.
+    pub fn positions(&self) -> impl Iterator<Item = (u32, u32)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// All values in reading order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.field.iter()
+    }
+
+    pub fn row(&self, y: u32) -> &[T] {
+        let start = (y * self.width) as usize;
+        &self.field[start..start + self.width as usize]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: u32) -> impl Iterator<Item = &T> {
+        (0..self.height).map(move |y| self.get(x, y))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// Creates a field of the same size with the mapped values.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, value: F) -> Field<U> {
+        Field::from(
+            self.field.iter().map(value).collect(),
+            self.width,
+            self.height,
+        )
+    }
+
+    /// Renders the field with one character per value, rows are separated by newlines.
+    pub fn display<F: Fn(&T) -> char>(&self, cell: F) -> FieldDisplay<'_, T, F> {
+        FieldDisplay { field: self, cell }
+    }
+}
+
+impl<T: Clone> Field<T> {
+    /// Mirrors the field at the diagonal from the top left.
+    pub fn transpose(&self) -> Self {
+        Field::from_fn(self.height, self.width, |x, y| self.get(y, x).clone())
+    }
+
+    /// Rotates the field by 90 degrees clockwise.
+    pub fn rotate_right(&self) -> Self {
+        Field::from_fn(self.height, self.width, |x, y| {
+            self.get(y, self.height - 1 - x).clone()
+        })
+    }
+
+    /// Rotates the field by 90 degrees counterclockwise.
+    pub fn rotate_left(&self) -> Self {
+        Field::from_fn(self.height, self.width, |x, y| {
+            self.get(self.width - 1 - y, x).clone()
+        })
+    }
+
+    /// Mirrors the field left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        Field::from_fn(self.width, self.height, |x, y| {
+            self.get(self.width - 1 - x, y).clone()
+        })
+    }
+
+    /// Mirrors the field top to bottom.
+    pub fn flip_vertical(&self) -> Self {
+        Field::from_fn(self.width, self.height, |x, y| {
+            self.get(x, self.height - 1 - y).clone()
+        })
+    }
+}
+
+fn offset(width: u32, height: u32, (x, y): (u32, u32), (dx, dy): (i64, i64)) -> Option<(u32, u32)> {
+    let x = i64::from(x) + dx;
+    let y = i64::from(y) + dy;
+    if x >= 0 && y >= 0 && x < i64::from(width) && y < i64::from(height) {
+        Some((x as u32, y as u32))
+    } else {
+        None
+    }
+}
+
+/// Rendering of a field, see `Field::display`.
+pub struct FieldDisplay<'a, T, F> {
+    field: &'a Field<T>,
+    cell: F,
+}
+
+impl<T, F: Fn(&T) -> char> fmt::Display for FieldDisplay<'_, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.field.rows().enumerate() {
+            if y > 0 {
+                f.write_char('\n')?;
+            }
+            for value in row {
+                f.write_char((self.cell)(value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Smallest and largest coordinates, both inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: (i64, i64),
+    pub max: (i64, i64),
+}
+
+impl Bounds {
+    pub fn width(&self) -> u32 {
+        (self.max.0 - self.min.0 + 1) as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        (self.max.1 - self.min.1 + 1) as u32
+    }
+
+    fn extend(self, (x, y): (i64, i64)) -> Self {
+        Bounds {
+            min: (self.min.0.min(x), self.min.1.min(y)),
+            max: (self.max.0.max(x), self.max.1.max(y)),
+        }
+    }
+}
+
+/// Two dimensional grid that grows in all directions, for puzzles without a known size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<(i64, i64), T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        SparseGrid {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> Option<&T> {
+        self.cells.get(&(x, y))
+    }
+
+    pub fn get_mut(&mut self, x: i64, y: i64) -> Option<&mut T> {
+        self.cells.get_mut(&(x, y))
+    }
+
+    /// Sets the value and returns the previous one.
+    pub fn insert(&mut self, x: i64, y: i64, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => bounds.extend((x, y)),
+            None => Bounds {
+                min: (x, y),
+                max: (x, y),
+            },
+        });
+        self.cells.insert((x, y), value)
+    }
+
+    /// Number of positions with a value.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Positions with a value, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = ((i64, i64), &T)> {
+        self.cells
+            .iter()
+            .map(|(position, value)| (*position, value))
+    }
+
+    /// Box around all positions that were ever set, `None` if the grid is empty.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    /// Converts the area within the bounds into a field, the top left corner of the bounds
+    /// becomes `(0, 0)`.
+    pub fn to_field<U, F: FnMut(Option<&T>) -> U>(&self, mut cell: F) -> Field<U> {
+        match self.bounds {
+            Some(bounds) => Field::from_fn(bounds.width(), bounds.height(), |x, y| {
+                cell(self.get(bounds.min.0 + i64::from(x), bounds.min.1 + i64::from(y)))
+            }),
+            None => Field::from(Vec::new(), 0, 0),
+        }
+    }
+
+    /// Renders the area within the bounds with one character per position.
+    pub fn render<F: Fn(Option<&T>) -> char>(&self, cell: F) -> String {
+        self.to_field(cell).display(|c| *c).to_string()
+    }
+}
+
+/// One dimensional counterpart of `SparseGrid`, ordered by position.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SparseLine<T> {
+    cells: BTreeMap<i64, T>,
+}
+
+impl<T> Default for SparseLine<T> {
+    fn default() -> Self {
+        SparseLine {
+            cells: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> SparseLine<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, i: i64) -> Option<&T> {
+        self.cells.get(&i)
+    }
+
+    pub fn get_mut(&mut self, i: i64) -> Option<&mut T> {
+        self.cells.get_mut(&i)
+    }
+
+    /// Sets the value and returns the previous one.
+    pub fn insert(&mut self, i: i64, value: T) -> Option<T> {
+        self.cells.insert(i, value)
+    }
+
+    pub fn remove(&mut self, i: i64) -> Option<T> {
+        self.cells.remove(&i)
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Moves all values by the offset.
+    pub fn shift(&mut self, offset: i64) {
+        let cells = std::mem::take(&mut self.cells);
+        self.cells = cells
+            .into_iter()
+            .map(|(i, value)| (i + offset, value))
+            .collect();
+    }
+
+    /// Number of positions with a value.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Positions with a value, from left to right.
+    pub fn iter(&self) -> impl Iterator<Item = (i64, &T)> {
+        self.cells.iter().map(|(i, value)| (*i, value))
+    }
+
+    /// Smallest and largest position with a value, both inclusive.
+    pub fn bounds(&self) -> Option<(i64, i64)> {
+        let min = self.cells.keys().next()?;
+        let max = self.cells.keys().next_back()?;
+        Some((*min, *max))
+    }
+
+    /// Renders the positions within the bounds with one character per position.
+    pub fn render<F: Fn(Option<&T>) -> char>(&self, cell: F) -> String {
+        match self.bounds() {
+            Some((min, max)) => (min..=max).map(|i| cell(self.get(i))).collect(),
+            None => String::new(),
+        }
+    }
 }
 
 impl<T> Into<Field<T>> for Field<Option<T>> {
@@ -78,3 +539,122 @@ impl<T> Into<Field<T>> for Field<Option<T>> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_line_endings() {
+        assert_eq!(normalize_input("a\r\nb\r\n\r\n"), "a\nb\n");
+        assert_eq!(normalize_input("abc"), "abc\n");
+    }
+
+    fn digit(c: char) -> ACResult<u32> {
+        c.to_digit(10)
+            .ok_or_else(|| Error::Parse("a digit".to_owned()))
+    }
+
+    fn parse_digits(input: &str) -> Field<u32> {
+        Field::parse(input, digit).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let field = parse_digits("123\n456\n");
+        assert_eq!((field.width(), field.height()), (3, 2));
+        assert_eq!(*field.get(2, 1), 6);
+        assert_eq!(field.get_opt(3, 0), None);
+        assert_eq!(field.at(Vec2::new(1, 1)), Some(&5));
+        assert_eq!(field.at(Vec2::new(-1, 0)), None);
+        let rendered = field.display(|d| std::char::from_digit(*d, 10).unwrap());
+        assert_eq!(rendered.to_string(), "123\n456");
+        match Field::parse("12\n3\n", digit) {
+            Err(Error::Syntax(e)) => {
+                assert_eq!((e.line, e.column), (2, 2));
+                assert_eq!(e.expected, "more cells, rows have 2 cells");
+            }
+            r => panic!("Unexpected result {:?}", r),
+        }
+        match Field::parse("12\n1x\n", digit) {
+            Err(Error::Syntax(e)) => {
+                assert_eq!((e.line, e.column), (2, 2));
+                assert_eq!(e.source_line, "1x");
+                assert_eq!(e.expected, "a digit");
+            }
+            r => panic!("Unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn neighbours_rows_and_columns() {
+        let field = parse_digits("123\n456\n789\n");
+        assert_eq!(
+            field.neighbours4(0, 1).collect::<Vec<_>>(),
+            vec![(0, 0), (1, 1), (0, 2)]
+        );
+        assert_eq!(field.neighbours8(1, 1).count(), 8);
+        assert_eq!(
+            field.neighbours8(2, 2).collect::<Vec<_>>(),
+            vec![(1, 1), (2, 1), (1, 2)]
+        );
+        assert_eq!(field.row(1), &[4, 5, 6]);
+        assert_eq!(field.column(2).copied().collect::<Vec<_>>(), vec![3, 6, 9]);
+        assert_eq!(
+            field.columns().map(|c| c.sum::<u32>()).collect::<Vec<_>>(),
+            vec![12, 15, 18]
+        );
+        assert_eq!(field.rows().count(), 3);
+        assert_eq!(field.positions().nth(4), Some((1, 1)));
+    }
+
+    #[test]
+    fn transforms() {
+        let field = parse_digits("123\n456\n");
+        assert_eq!(field.transpose(), parse_digits("14\n25\n36\n"));
+        assert_eq!(field.rotate_right(), parse_digits("41\n52\n63\n"));
+        assert_eq!(field.rotate_left(), parse_digits("36\n25\n14\n"));
+        assert_eq!(field.flip_horizontal(), parse_digits("321\n654\n"));
+        assert_eq!(field.flip_vertical(), parse_digits("456\n123\n"));
+        assert_eq!(field.rotate_right().rotate_left(), field);
+    }
+
+    #[test]
+    fn sparse_grid() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.bounds(), None);
+        assert_eq!(grid.render(|_| '#'), "");
+        grid.insert(-2, 1, 'a');
+        grid.insert(1, -1, 'b');
+        assert_eq!(grid.insert(1, -1, 'c'), Some('b'));
+        assert_eq!(grid.len(), 2);
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds {
+                min: (-2, -1),
+                max: (1, 1)
+            })
+        );
+        assert_eq!(grid.render(|c| *c.unwrap_or(&'.')), "...c\n....\na...");
+        let field = grid.to_field(|c| c.is_some());
+        assert_eq!((field.width(), field.height()), (4, 3));
+        assert!(*field.get(0, 2));
+    }
+
+    #[test]
+    fn sparse_line() {
+        let mut line = SparseLine::new();
+        line.insert(3, true);
+        line.insert(-2, true);
+        line.insert(0, false);
+        assert_eq!(line.bounds(), Some((-2, 3)));
+        assert_eq!(
+            line.render(|p| if p == Some(&true) { '#' } else { '.' }),
+            "#....#"
+        );
+        assert_eq!(line.remove(-2), Some(true));
+        assert_eq!(line.iter().map(|(i, _)| i).collect::<Vec<_>>(), vec![0, 3]);
+        line.shift(-5);
+        assert_eq!(line.bounds(), Some((-5, -2)));
+    }
+}
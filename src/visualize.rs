@@ -0,0 +1,248 @@
+use crate::utils::Field;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Size used when the terminal doesn't tell its size through `COLUMNS` and `LINES`.
+const DEFAULT_TERMINAL_SIZE: (u32, u32) = (80, 24);
+
+/// Settings for animating simulations in the terminal, `None` while visualization is off.
+static SETTINGS: Mutex<Option<Settings>> = Mutex::new(None);
+
+/// Set once the screen was cleared for the first frame.
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// Pause after every frame
+    pub delay: Duration,
+    /// Keep the focus of a frame in the middle of the viewport instead of showing the top left
+    pub follow: bool,
+    /// Maximum number of cells shown, the caption takes one more line
+    pub viewport: (u32, u32),
+}
+
+impl Settings {
+    /// Settings with a viewport that fits the terminal.
+    pub fn new(delay: Duration, follow: bool) -> Self {
+        let size = |name: &str, default: u32| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|size| *size > 1)
+                .unwrap_or(default)
+        };
+        Settings {
+            delay,
+            follow,
+            viewport: (
+                size("COLUMNS", DEFAULT_TERMINAL_SIZE.0),
+                size("LINES", DEFAULT_TERMINAL_SIZE.1) - 1,
+            ),
+        }
+    }
+}
+
+/// Turns on the animation of simulations that support it.
+pub fn enable(settings: Settings) {
+    *SETTINGS.lock().unwrap() = Some(settings);
+}
+
+pub fn is_enabled() -> bool {
+    SETTINGS.lock().unwrap().is_some()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Gray,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    fn ansi_code(self) -> u8 {
+        match self {
+            Color::Gray => 90,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 97,
+        }
+    }
+}
+
+/// How a single cell of a frame looks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub symbol: char,
+    pub color: Option<Color>,
+}
+
+impl Cell {
+    pub fn new(symbol: char, color: Color) -> Self {
+        Cell {
+            symbol,
+            color: Some(color),
+        }
+    }
+
+    /// Cell in the default colour of the terminal.
+    pub fn plain(symbol: char) -> Self {
+        Cell {
+            symbol,
+            color: None,
+        }
+    }
+}
+
+/// The part of a field that is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    /// The whole field if it fits into the maximum size, otherwise the part with the focus in the
+    /// middle, or the top left part without focus.
+    pub fn around(
+        width: u32,
+        height: u32,
+        max_size: (u32, u32),
+        focus: Option<(u32, u32)>,
+    ) -> Viewport {
+        let (view_width, view_height) = (width.min(max_size.0), height.min(max_size.1));
+        let (focus_x, focus_y) = focus.unwrap_or((0, 0));
+        let start =
+            |focus: u32, view: u32, size: u32| focus.saturating_sub(view / 2).min(size - view);
+        Viewport {
+            x: start(focus_x, view_width, width),
+            y: start(focus_y, view_height, height),
+            width: view_width,
+            height: view_height,
+        }
+    }
+}
+
+/// Renders the cells of the viewport, colours are set with ANSI escape codes.
+pub fn render<F: Fn(u32, u32) -> Cell>(viewport: Viewport, cell: F) -> String {
+    let mut lines = Vec::with_capacity(viewport.height as usize);
+    for y in viewport.y..viewport.y + viewport.height {
+        let mut line = String::new();
+        let mut color = None;
+        for x in viewport.x..viewport.x + viewport.width {
+            let cell = cell(x, y);
+            if cell.color != color {
+                match cell.color {
+                    Some(c) => line += &format!("\x1b[{}m", c.ansi_code()),
+                    None => line += "\x1b[0m",
+                }
+                color = cell.color;
+            }
+            line.push(cell.symbol);
+        }
+        if color.is_some() {
+            line += "\x1b[0m";
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Draws a frame of a simulation over the previous one if visualization is enabled, then waits
+/// for the frame delay.
+///
+/// Frames go to stderr, so they don't mix with the answer. In follow mode the viewport is centred
+/// on the focus.
+pub fn show<F: Fn(u32, u32) -> Cell>(
+    width: u32,
+    height: u32,
+    focus: Option<(u32, u32)>,
+    caption: &str,
+    cell: F,
+) {
+    let settings = match *SETTINGS.lock().unwrap() {
+        Some(settings) => settings,
+        None => return,
+    };
+    let focus = if settings.follow { focus } else { None };
+    let viewport = Viewport::around(width, height, settings.viewport, focus);
+
+    let mut frame = String::new();
+    if !STARTED.swap(true, Ordering::SeqCst) {
+        frame += "\x1b[2J";
+    }
+    frame += "\x1b[H";
+    frame += caption;
+    frame += "\x1b[K\n";
+    frame += &render(viewport, cell).replace('\n', "\x1b[K\n");
+    frame += "\x1b[J";
+    let stderr = std::io::stderr();
+    let mut stderr = stderr.lock();
+    let _ = writeln!(stderr, "{}", frame);
+    let _ = stderr.flush();
+    std::thread::sleep(settings.delay);
+}
+
+/// Draws a frame of the field, see `show`.
+pub fn show_field<T, F: Fn(&T) -> Cell>(
+    field: &Field<T>,
+    focus: Option<(u32, u32)>,
+    caption: &str,
+    style: F,
+) {
+    show(field.width(), field.height(), focus, caption, |x, y| {
+        style(field.get(x, y))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport_follows_focus() {
+        let whole = Viewport::around(10, 5, (80, 23), Some((7, 3)));
+        assert_eq!(
+            whole,
+            Viewport {
+                x: 0,
+                y: 0,
+                width: 10,
+                height: 5
+            }
+        );
+        let top_left = Viewport::around(100, 50, (20, 10), None);
+        assert_eq!((top_left.x, top_left.y), (0, 0));
+        let centred = Viewport::around(100, 50, (20, 10), Some((40, 20)));
+        assert_eq!((centred.x, centred.y), (30, 15));
+        let clamped = Viewport::around(100, 50, (20, 10), Some((99, 2)));
+        assert_eq!((clamped.x, clamped.y, clamped.width), (80, 0, 20));
+    }
+
+    #[test]
+    fn render_with_colours() {
+        let viewport = Viewport {
+            x: 1,
+            y: 0,
+            width: 3,
+            height: 2,
+        };
+        let frame = render(viewport, |x, y| match (x, y) {
+            (2, 0) | (3, 0) => Cell::new('#', Color::Red),
+            _ => Cell::plain('.'),
+        });
+        assert_eq!(frame, ".\x1b[31m##\x1b[0m\n...");
+    }
+}
@@ -0,0 +1,477 @@
+use crate::errors::{ACResult, Error};
+use crate::geometry::Vec2;
+use crate::progress;
+use std::cmp::Ordering;
+
+/// Voronoi diagram of sites on the integer grid with the Manhattan distance.
+///
+/// Points with several nearest sites belong to no region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Voronoi {
+    sites: Vec<Vec2>,
+    min: Vec2,
+    max: Vec2,
+}
+
+/// Distance and the first and last index of the nearest sites found so far.
+type Nearest = (i64, usize, usize);
+
+const NONE: Nearest = (i64::MAX, usize::MAX, 0);
+
+fn closer(a: Nearest, b: Nearest) -> Nearest {
+    match a.0.cmp(&b.0) {
+        Ordering::Less => a,
+        Ordering::Greater => b,
+        Ordering::Equal => (a.0, a.1.min(b.1), a.2.max(b.2)),
+    }
+}
+
+fn owner(nearest: Nearest) -> Option<usize> {
+    if nearest.1 == nearest.2 {
+        Some(nearest.1)
+    } else {
+        None
+    }
+}
+
+impl Voronoi {
+    /// Fails without sites.
+    pub fn new(sites: Vec<Vec2>) -> ACResult<Self> {
+        let first = *sites
+            .first()
+            .ok_or_else(|| Error::new_str("A Voronoi diagram needs at least one site"))?;
+        let (min, max) = sites.iter().fold((first, first), |(min, max), site| {
+            (
+                Vec2::new(min.x.min(site.x), min.y.min(site.y)),
+                Vec2::new(max.x.max(site.x), max.y.max(site.y)),
+            )
+        });
+        Ok(Voronoi { sites, min, max })
+    }
+
+    pub fn sites(&self) -> &[Vec2] {
+        &self.sites
+    }
+
+    /// Index of the only nearest site of the point.
+    pub fn nearest(&self, point: Vec2) -> Option<usize> {
+        let nearest = self
+            .sites
+            .iter()
+            .enumerate()
+            .map(|(i, site)| (site.manhattan(point), i, i))
+            .fold(NONE, closer);
+        owner(nearest)
+    }
+
+    /// Size of the region of every site, `None` for infinite regions.
+    ///
+    /// Outside the bounding box of the sites, the nearest site doesn't change when moving away
+    /// from it, so a region is infinite exactly if it reaches the ring around the bounding box.
+    /// A row splits into segments between neighbouring columns of sites. Between two rows of
+    /// sites the segment ends move linearly, so only the rows where the order of the segments
+    /// changes are computed and the rows between them are added up at once. The run time grows
+    /// with the number of sites and these changes, not with the size of the bounding box.
+    pub fn areas(&self) -> ACResult<Vec<Option<u64>>> {
+        let mut sweep = Sweep::new(self);
+        for y in [self.min.y - 1, self.max.y + 1].iter() {
+            let row = sweep.row(*y)?;
+            for (i, size) in row.sizes.iter().enumerate() {
+                if *size > 0 {
+                    sweep.infinite[i] = true;
+                }
+            }
+            for site in row.outer.iter().flatten() {
+                sweep.infinite[*site] = true;
+            }
+        }
+
+        let mut site_rows: Vec<i64> = self.sites.iter().map(|site| site.y).collect();
+        site_rows.sort_unstable();
+        site_rows.dedup();
+        let mut previous = sweep.row(site_rows[0])?;
+        sweep.add(&previous, &previous, 1);
+        for rows in site_rows.windows(2) {
+            let next = sweep.row(rows[1])?;
+            sweep.add_between(rows[0], &previous, rows[1], &next)?;
+            previous = next;
+        }
+
+        Ok(sweep
+            .sizes
+            .into_iter()
+            .zip(sweep.infinite)
+            .map(|(size, infinite)| if infinite { None } else { Some(size) })
+            .collect())
+    }
+
+    /// Size of the largest finite region.
+    pub fn largest_finite_area(&self) -> ACResult<Option<u64>> {
+        Ok(self.areas()?.into_iter().flatten().max())
+    }
+
+    /// Number of points whose distances to all sites add up to less than the limit.
+    ///
+    /// The sum splits into a sum over the columns and one over the rows of the sites, so every
+    /// row only needs the range of columns that stay below the rest of the limit.
+    pub fn total_distance_area(&self, limit: i64) -> u64 {
+        let columns = AxisDistances::new(self.sites.iter().map(|site| site.x).collect());
+        let rows = AxisDistances::new(self.sites.iter().map(|site| site.y).collect());
+        match rows.below(limit) {
+            Some((top, bottom)) => (top..=bottom)
+                .filter_map(|y| columns.below(limit - rows.total(y)))
+                .map(|(left, right)| (right - left + 1) as u64)
+                .sum(),
+            None => 0,
+        }
+    }
+}
+
+/// Sums of the distances to the sites along one axis, from prefix sums of the sorted coordinates.
+struct AxisDistances {
+    coordinates: Vec<i64>,
+    /// Sum of the first `i` coordinates at index `i`
+    prefix_sums: Vec<i64>,
+}
+
+impl AxisDistances {
+    fn new(mut coordinates: Vec<i64>) -> Self {
+        coordinates.sort_unstable();
+        let mut prefix_sums = Vec::with_capacity(coordinates.len() + 1);
+        prefix_sums.push(0);
+        for c in coordinates.iter() {
+            prefix_sums.push(prefix_sums.last().unwrap() + c);
+        }
+        AxisDistances {
+            coordinates,
+            prefix_sums,
+        }
+    }
+
+    fn total(&self, v: i64) -> i64 {
+        let n = self.coordinates.len();
+        let below = self.coordinates.partition_point(|c| *c <= v);
+        let (lower, upper) = (
+            self.prefix_sums[below],
+            self.prefix_sums[n] - self.prefix_sums[below],
+        );
+        v * below as i64 - lower + upper - v * (n - below) as i64
+    }
+
+    /// The range of positions with a total below the limit, the total only falls up to the median
+    /// and only rises after it.
+    fn below(&self, limit: i64) -> Option<(i64, i64)> {
+        let median = self.coordinates[self.coordinates.len() / 2];
+        if self.total(median) >= limit {
+            return None;
+        }
+        // Beyond the outermost coordinates the total changes by at least one per step
+        let first = self.coordinates[0] - limit.max(0);
+        let last = self.coordinates[self.coordinates.len() - 1] + limit.max(0);
+        let start = first + partition_point(first, median, |v| self.total(v) >= limit);
+        let end = median + partition_point(median, last + 1, |v| self.total(v) < limit) - 1;
+        Some((start, end))
+    }
+}
+
+/// Number of positions from `start` before `end` for which the predicate holds, it must hold for
+/// a prefix of the range.
+fn partition_point<F: Fn(i64) -> bool>(start: i64, end: i64, predicate: F) -> i64 {
+    let (mut low, mut high) = (start, end);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if predicate(middle) {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    low - start
+}
+
+/// Region sizes of one row inside the bounding box.
+struct Row {
+    /// Nearest sites of every column of sites from the left, from the right and from both sides
+    nearest: Vec<(usize, usize)>,
+    /// Position of the split between every two neighbouring columns relative to the columns
+    splits: Vec<(Ordering, Ordering)>,
+    sizes: Vec<u64>,
+    /// Only nearest sites left and right of the bounding box
+    outer: [Option<usize>; 2],
+}
+
+impl Row {
+    /// Rows with the same shape between two rows of sites, and all rows between them, have
+    /// sizes that change linearly with the row.
+    fn same_shape(&self, other: &Row) -> bool {
+        self.nearest == other.nearest && self.splits == other.splits
+    }
+}
+
+/// Position of the value relative to the range.
+fn position(value: i64, low: i64, high: i64) -> Ordering {
+    if value < low {
+        Ordering::Less
+    } else if value > high {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+struct Sweep<'a> {
+    voronoi: &'a Voronoi,
+    /// Sites by column, ordered from left to right
+    columns: Vec<(i64, Vec<usize>)>,
+    sizes: Vec<u64>,
+    infinite: Vec<bool>,
+    computed_rows: u64,
+}
+
+impl<'a> Sweep<'a> {
+    fn new(voronoi: &'a Voronoi) -> Self {
+        let mut order: Vec<usize> = (0..voronoi.sites.len()).collect();
+        order.sort_by_key(|i| voronoi.sites[*i].x);
+        let mut columns: Vec<(i64, Vec<usize>)> = Vec::new();
+        for i in order {
+            let x = voronoi.sites[i].x;
+            match columns.last_mut() {
+                Some((column, sites)) if *column == x => sites.push(i),
+                _ => columns.push((x, vec![i])),
+            }
+        }
+        Sweep {
+            voronoi,
+            columns,
+            sizes: vec![0; voronoi.sites.len()],
+            infinite: vec![false; voronoi.sites.len()],
+            computed_rows: 0,
+        }
+    }
+
+    fn row(&mut self, y: i64) -> ACResult<Row> {
+        self.computed_rows += 1;
+        progress::step(self.computed_rows, || {
+            format!("{} rows computed", self.computed_rows)
+        })?;
+
+        let sites = &self.voronoi.sites;
+        let columns = &self.columns;
+        let n = columns.len();
+        let own: Vec<Nearest> = columns
+            .iter()
+            .map(|(_, column)| {
+                column
+                    .iter()
+                    .map(|i| ((sites[*i].y - y).abs(), *i, *i))
+                    .fold(NONE, closer)
+            })
+            .collect();
+        let shifted =
+            |nearest: Nearest, distance: i64| (nearest.0 + distance, nearest.1, nearest.2);
+        let mut from_left = own.clone();
+        for k in 1..n {
+            let distance = columns[k].0 - columns[k - 1].0;
+            from_left[k] = closer(from_left[k], shifted(from_left[k - 1], distance));
+        }
+        let mut from_right = own;
+        for k in (0..n - 1).rev() {
+            let distance = columns[k + 1].0 - columns[k].0;
+            from_right[k] = closer(from_right[k], shifted(from_right[k + 1], distance));
+        }
+
+        let mut nearest = Vec::with_capacity(3 * n);
+        let mut splits = Vec::with_capacity(n - 1);
+        let mut sizes = vec![0; sites.len()];
+        let mut add = |nearest: Nearest, size: i64| {
+            if let Some(i) = owner(nearest) {
+                sizes[i] += size as u64;
+            }
+        };
+        for k in 0..n {
+            let both = closer(from_left[k], from_right[k]);
+            add(both, 1);
+            nearest.extend_from_slice(&[
+                (from_left[k].1, from_left[k].2),
+                (from_right[k].1, from_right[k].2),
+                (both.1, both.2),
+            ]);
+            if k + 1 < n {
+                let (left, right) = (columns[k].0, columns[k + 1].0);
+                let (from_left, from_right) = (from_left[k], from_right[k + 1]);
+                let gap = right - left - 1;
+                // Points before the middle between both sides are closer to the left one
+                let twice_middle = from_right.0 - from_left.0 + left + right;
+                let before_middle =
+                    twice_middle.div_euclid(2) + twice_middle.rem_euclid(2) - left - 1;
+                let on_middle = (twice_middle.rem_euclid(2) == 0
+                    && 2 * left < twice_middle
+                    && twice_middle < 2 * right) as i64;
+                let left_size = before_middle.clamp(0, gap);
+                add(from_left, left_size);
+                add(from_right, gap - left_size - on_middle);
+                splits.push((
+                    position(before_middle, 0, gap),
+                    position(twice_middle, 2 * left + 1, 2 * right - 1),
+                ));
+            }
+        }
+        Ok(Row {
+            nearest,
+            splits,
+            sizes,
+            outer: [owner(from_right[0]), owner(from_left[n - 1])],
+        })
+    }
+
+    /// Adds `count` rows after `start` up to `end`, whose sizes change linearly between both.
+    fn add(&mut self, start: &Row, end: &Row, count: u64) {
+        for site in start.outer.iter().chain(end.outer.iter()).flatten() {
+            self.infinite[*site] = true;
+        }
+        let count = count as i128;
+        for (total, (start, end)) in self
+            .sizes
+            .iter_mut()
+            .zip(start.sizes.iter().zip(end.sizes.iter()))
+        {
+            let (start, end) = (*start as i128, *end as i128);
+            *total += (count * start + (end - start) * (count + 1) / 2) as u64;
+        }
+    }
+
+    /// Adds the rows after `top` up to `bottom`, with no site strictly between them.
+    fn add_between(
+        &mut self,
+        top: i64,
+        top_row: &Row,
+        bottom: i64,
+        bottom_row: &Row,
+    ) -> ACResult<()> {
+        let count = bottom - top;
+        if count == 1 || top_row.same_shape(bottom_row) {
+            self.add(top_row, bottom_row, count as u64);
+        } else {
+            let middle = top + count / 2;
+            let middle_row = self.row(middle)?;
+            self.add_between(top, top_row, middle, &middle_row)?;
+            self.add_between(middle, &middle_row, bottom, bottom_row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Voronoi {
+        let sites = ["1, 1", "1, 6", "8, 3", "3, 4", "5, 5", "8, 9"];
+        Voronoi::new(sites.iter().map(|s| s.parse().unwrap()).collect()).unwrap()
+    }
+
+    #[test]
+    fn finite_areas() {
+        let voronoi = example();
+        assert_eq!(
+            voronoi.areas(),
+            Ok(vec![None, None, None, Some(9), Some(17), None])
+        );
+        assert_eq!(voronoi.largest_finite_area(), Ok(Some(17)));
+        assert_eq!(voronoi.nearest(Vec2::new(5, 0)), None);
+        assert_eq!(voronoi.nearest(Vec2::new(4, 4)), Some(3));
+        assert!(Voronoi::new(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn areas_match_nearest_sites() {
+        let voronoi = example();
+        let areas = voronoi.areas().unwrap();
+        for (i, area) in areas.iter().enumerate() {
+            if let Some(area) = area {
+                let count = (0..=10)
+                    .flat_map(|y| (0..=10).map(move |x| Vec2::new(x, y)))
+                    .filter(|point| voronoi.nearest(*point) == Some(i))
+                    .count();
+                assert_eq!(*area, count as u64);
+            }
+        }
+    }
+
+    /// Sizes of the regions from the nearest site of every point in the bounding box.
+    fn count_areas(voronoi: &Voronoi) -> Vec<Option<u64>> {
+        let (min, max) = (voronoi.min, voronoi.max);
+        let mut areas = vec![Some(0); voronoi.sites().len()];
+        for y in min.y - 1..=max.y + 1 {
+            for x in min.x - 1..=max.x + 1 {
+                if let Some(i) = voronoi.nearest(Vec2::new(x, y)) {
+                    if x < min.x || x > max.x || y < min.y || y > max.y {
+                        areas[i] = None;
+                    } else if let Some(area) = &mut areas[i] {
+                        *area += 1;
+                    }
+                }
+            }
+        }
+        areas
+    }
+
+    #[test]
+    fn areas_of_generated_sites() {
+        let mut seed = 12345u64;
+        let mut random = |range: i64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as i64 % range
+        };
+        for _ in 0..200 {
+            let count = 1 + random(12);
+            let size = 1 + random(40);
+            let sites = (0..count)
+                .map(|_| Vec2::new(random(size), random(size)))
+                .collect();
+            let voronoi = Voronoi::new(sites).unwrap();
+            assert_eq!(voronoi.areas(), Ok(count_areas(&voronoi)), "{:?}", voronoi);
+        }
+    }
+
+    #[test]
+    fn areas_of_far_sites() {
+        let far = 1_000_000_000;
+        let sites = vec![
+            Vec2::new(0, 0),
+            Vec2::new(far, 0),
+            Vec2::new(-far, 0),
+            Vec2::new(0, far),
+            Vec2::new(0, -far),
+        ];
+        let voronoi = Voronoi::new(sites).unwrap();
+        assert_eq!(
+            voronoi.areas(),
+            Ok(vec![Some((far as u64 - 1).pow(2)), None, None, None, None])
+        );
+    }
+
+    #[test]
+    fn total_distance_below_limit() {
+        let voronoi = example();
+        assert_eq!(voronoi.total_distance_area(32), 16);
+        assert_eq!(voronoi.total_distance_area(0), 0);
+        let count = (-20..=30)
+            .flat_map(|y| (-20..=30).map(move |x| Vec2::new(x, y)))
+            .filter(|point| {
+                voronoi
+                    .sites()
+                    .iter()
+                    .map(|site| site.manhattan(*point))
+                    .sum::<i64>()
+                    < 100
+            })
+            .count();
+        assert_eq!(voronoi.total_distance_area(100), count as u64);
+
+        let far = Voronoi::new(vec![Vec2::new(-1_000_000_000, 3)]).unwrap();
+        assert_eq!(far.total_distance_area(2), 5);
+    }
+}
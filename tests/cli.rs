@@ -0,0 +1,27 @@
+use std::process::Command;
+
+/// Runs the solver binary with empty config and data directories.
+fn solver(name: &str) -> Command {
+    let dir = std::env::temp_dir().join(format!("advent-cli-test-{}-{}", name, std::process::id()));
+    let mut command = Command::new(env!("CARGO_BIN_EXE_advent_of_code_solver"));
+    command
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("XDG_DATA_HOME", dir.join("data"))
+        .env_remove("ADVENT_OF_CODE_SOLVER_DATA_DIR");
+    command
+}
+
+#[test]
+fn json_report_of_failed_solver() {
+    let output = solver("json")
+        .args(&["-y", "2018", "-d", "11", "-l", "1", "--input", "x"])
+        .args(&["--format", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["year"], 2018);
+    assert_eq!(report["day"], 11);
+    assert!(report["answer"].is_null());
+    assert!(report["error"].as_str().unwrap().contains("line 1"));
+}
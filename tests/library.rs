@@ -0,0 +1,70 @@
+use advent_of_code_solver::answer::Answer;
+use advent_of_code_solver::elfcode::{parse_program, Registers};
+use advent_of_code_solver::errors::Error;
+use advent_of_code_solver::get_result;
+use advent_of_code_solver::intcode::{parse_intcode, IntCodeComputer};
+
+#[test]
+fn solver_registry() {
+    assert_eq!(get_result("12\r\n", 2019, 1, 1), Ok(Answer::Integer(2)));
+    assert!(get_result("1969\n", 2019, 1, 2).unwrap().matches("966"));
+    assert!(matches!(
+        get_result("", 2010, 1, 1),
+        Err(Error::Unimplemented(_))
+    ));
+}
+
+#[test]
+fn invalid_input_is_a_parse_error() {
+    let cases = [
+        (2015, 8, "\"abc\"\n\"a\\x4\"\n", (2, 3)),
+        (2017, 1, "", (1, 1)),
+        (2017, 2, "5 1 9 5\n7 x\n", (2, 3)),
+        (2018, 1, "+1\n\n-2\n", (2, 1)),
+        (2018, 5, "", (1, 1)),
+        (2018, 6, "1, 1\n1, x\n", (2, 4)),
+        (2018, 8, "2 3 0 3 10 11 12 1 1 0 1 99 2 1\n", (1, 32)),
+        (2018, 11, "", (1, 1)),
+        (2018, 15, "#####\n#.X.#\n", (2, 3)),
+        (2018, 15, "#####\n#.#\n", (2, 4)),
+        (2018, 15, "", (1, 1)),
+        (2019, 1, "12\n1a\n", (2, 2)),
+        (2019, 2, "1,0,x,3\n", (1, 5)),
+        (2019, 2, "", (1, 1)),
+        (2019, 3, "R8,U5\nR8,X3\n", (2, 4)),
+        (2019, 4, "", (1, 1)),
+        (2019, 6, "COM)B\nB-C\n", (2, 2)),
+        (2019, 8, "", (1, 1)),
+        (2019, 10, ".#\n.x\n", (2, 2)),
+        (2019, 10, "..\n..\n", (2, 3)),
+    ];
+    for (year, day, input, location) in cases.iter() {
+        match get_result(input, *year, *day, 1) {
+            Err(Error::Syntax(e)) => {
+                assert_eq!((e.line, e.column), *location, "{} day {}", year, day)
+            }
+            r => panic!("{} day {} gave {:?} for {:?}", year, day, r, input),
+        }
+    }
+}
+
+#[test]
+fn intcode_program() {
+    let ops = parse_intcode("3,9,8,9,10,9,4,9,99,-1,8\n").unwrap();
+    assert_eq!(IntCodeComputer::new(&ops).compute(&[8]), Ok(vec![1]));
+    assert_eq!(IntCodeComputer::new(&ops).compute(&[7]), Ok(vec![0]));
+}
+
+#[test]
+fn elfcode_program() {
+    let program = parse_program(
+        "#ip 0\nseti 5 0 1\nseti 6 0 2\naddi 0 1 0\naddr 1 2 3\nsetr 1 0 0\nseti 8 0 4\nseti 9 0 5\n",
+    )
+    .unwrap();
+    let mut registers = Registers::empty(6);
+    let mut ip = 0;
+    while ip < program.instructions.len() {
+        ip = program.step(ip, &mut registers);
+    }
+    assert_eq!(*registers.get(0), 6);
+}